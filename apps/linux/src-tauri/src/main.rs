@@ -0,0 +1,618 @@
+use std::{
+    fs,
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use lockpilot_core::{
+    close::{self, AppCloser},
+    model::{
+        ActionAttempt, ActionOutcome, ActionOutcomePayload, CloseAppsSpec, CreateTimerRequest, HistoryEntry,
+        HistoryFilter, PowerSource, PreActionWarningPayload, PreviewRecurrenceRequest, RecurrencePreview,
+        ResolvePreActionRequest, TimerInfo,
+    },
+    open::OpenTargetKind,
+    prewarning, recurrence,
+    updater::{self, InstallerExtensions, ReleaseVersion, UpdateChannel, UpdateInfo},
+    ActionBackend, HistoryStore, IdleSource, LockStore, PreActionPrompter, Scheduler, SystemProbe, TimerStore,
+};
+use tauri::{Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
+
+// ─── Tauri commands ───────────────────────────────────────────────
+
+#[tauri::command]
+fn list_timers(state: State<'_, Scheduler>) -> Result<Vec<TimerInfo>, String> {
+    state.list_timers()
+}
+
+#[tauri::command]
+fn cancel_timer(id: String, pin: Option<String>, state: State<'_, Scheduler>) -> Result<bool, String> {
+    state.cancel_timer(&id, pin.as_deref())
+}
+
+#[tauri::command]
+fn skip_next_occurrence(
+    id: String,
+    pin: Option<String>,
+    state: State<'_, Scheduler>,
+) -> Result<bool, String> {
+    state.skip_next_occurrence(&id, pin.as_deref())
+}
+
+#[tauri::command]
+fn pause_timer(id: String, pin: Option<String>, state: State<'_, Scheduler>) -> Result<bool, String> {
+    state.pause_timer(&id, pin.as_deref())
+}
+
+#[tauri::command]
+fn resume_timer(id: String, pin: Option<String>, state: State<'_, Scheduler>) -> Result<bool, String> {
+    state.resume_timer(&id, pin.as_deref())
+}
+
+#[tauri::command]
+fn resolve_pre_action(
+    request: ResolvePreActionRequest,
+    state: State<'_, Scheduler>,
+) -> Result<bool, String> {
+    state.resolve_pre_action(request)
+}
+
+#[tauri::command]
+fn create_timer(request: CreateTimerRequest, state: State<'_, Scheduler>) -> Result<TimerInfo, String> {
+    state.create_timer(request)
+}
+
+#[tauri::command]
+fn update_timer(
+    id: String,
+    request: CreateTimerRequest,
+    pin: Option<String>,
+    state: State<'_, Scheduler>,
+) -> Result<TimerInfo, String> {
+    state.update_timer(&id, request, pin.as_deref())
+}
+
+#[tauri::command]
+fn list_history(filter: Option<HistoryFilter>, state: State<'_, Scheduler>) -> Result<Vec<HistoryEntry>, String> {
+    state.list_history(&filter.unwrap_or_default())
+}
+
+#[tauri::command]
+fn clear_history(filter: Option<HistoryFilter>, state: State<'_, Scheduler>) -> Result<usize, String> {
+    state.clear_history(&filter.unwrap_or_default())
+}
+
+#[tauri::command]
+fn preview_recurrence(request: PreviewRecurrenceRequest) -> Result<RecurrencePreview, String> {
+    recurrence::preview_recurrence(request)
+}
+
+// ─── Update / release commands ────────────────────────────────────
+
+#[tauri::command]
+fn list_release_versions() -> Result<Vec<ReleaseVersion>, String> {
+    updater::list_release_versions(installer_extensions())
+}
+
+#[tauri::command]
+fn check_channel_update(
+    current_version: String,
+    channel: UpdateChannel,
+) -> Result<Option<UpdateInfo>, String> {
+    updater::check_channel_update(&current_version, &channel, installer_extensions())
+}
+
+#[tauri::command]
+fn install_channel_update(channel: UpdateChannel) -> Result<String, String> {
+    let installer = updater::download_channel_installer(&channel, installer_extensions())?;
+    let applied = apply_installer(&installer)?;
+
+    Ok(format!(
+        "{} channel update {}: {applied}",
+        updater::channel_name(&channel),
+        installer.tag
+    ))
+}
+
+#[tauri::command]
+fn install_release(tag: String) -> Result<String, String> {
+    let installer = updater::download_release_installer(&tag, installer_extensions())?;
+    let applied = apply_installer(&installer)?;
+
+    Ok(format!("Release {}: {applied}", installer.tag))
+}
+
+/// Updates come in the format LockPilot was installed from: AppImage
+/// builds (which run with `APPIMAGE` set) take `.AppImage` assets, package
+/// installs take `.deb`.
+fn installer_extensions() -> InstallerExtensions {
+    if std::env::var_os("APPIMAGE").is_some() {
+        &[".appimage"]
+    } else {
+        &[".deb"]
+    }
+}
+
+/// Hand a downloaded update to the system, describing what was done.
+fn apply_installer(installer: &updater::DownloadedInstaller) -> Result<String, String> {
+    let path = &installer.path;
+    if let Some(current) = std::env::var_os("APPIMAGE") {
+        let current = PathBuf::from(current);
+        let expected = installer.sha256.as_deref().ok_or_else(|| {
+            format!(
+                "Release {} publishes no SHA-256 for its AppImage; not replacing the running one",
+                installer.tag
+            )
+        })?;
+        replace_appimage(path, &current, expected)?;
+        return Ok(format!("replaced {}; restart LockPilot to finish", current.display()));
+    }
+
+    Command::new("xdg-open")
+        .arg(path)
+        .spawn()
+        .map_err(|err| format!("Failed to open installer package: {err}"))?;
+    Ok(format!("opened {} in the package installer", path.display()))
+}
+
+/// Swap the running AppImage for `download`, which must hash to `sha256`.
+/// The running file can't be written in place, so the new image is staged
+/// in a fresh file beside it, checked, and renamed over it in one step.
+fn replace_appimage(download: &Path, current: &Path, sha256: &str) -> Result<(), String> {
+    let file_name = current
+        .file_name()
+        .ok_or_else(|| format!("Failed to stage AppImage update: bad path {}", current.display()))?;
+    let staged = current.with_file_name(format!(
+        ".{}.{}.new",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = stage_appimage(download, &staged, sha256).and_then(|_| {
+        fs::rename(&staged, current).map_err(|err| format!("Failed to replace AppImage: {err}"))
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&staged);
+    }
+    result
+}
+
+fn stage_appimage(download: &Path, staged: &Path, sha256: &str) -> Result<(), String> {
+    let mut source =
+        fs::File::open(download).map_err(|err| format!("Failed to open AppImage update: {err}"))?;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o700)
+        .open(staged)
+        .map_err(|err| format!("Failed to stage AppImage update: {err}"))?;
+    std::io::copy(&mut source, &mut file).map_err(|err| format!("Failed to stage AppImage update: {err}"))?;
+    file.sync_all()
+        .map_err(|err| format!("Failed to stage AppImage update: {err}"))?;
+    drop(file);
+
+    // Check what actually landed on disk, not just what was downloaded.
+    if updater::sha256_file(staged)? != sha256 {
+        return Err("AppImage update does not match its published SHA-256".to_string());
+    }
+    fs::set_permissions(staged, fs::Permissions::from_mode(0o755))
+        .map_err(|err| format!("Failed to mark AppImage update executable: {err}"))
+}
+
+// ─── Pre-warning window ───────────────────────────────────────────
+
+struct TauriPrompter {
+    app: tauri::AppHandle,
+}
+
+impl PreActionPrompter for TauriPrompter {
+    fn open(&self, payload: &PreActionWarningPayload) -> Result<(), String> {
+        open_pre_action_window(&self.app, payload);
+        self.app
+            .emit("pre_action_warning", payload.clone())
+            .map_err(|err| format!("Failed to emit pre-action warning: {err}"))
+    }
+
+    fn close(&self, timer_id: &str) {
+        let label = pre_action_window_label(timer_id);
+        if let Some(window) = self.app.get_webview_window(&label) {
+            let _ = window.close();
+        }
+    }
+
+    fn action_finished(&self, payload: &ActionOutcomePayload) {
+        let _ = self.app.emit("action_outcome", payload.clone());
+    }
+}
+
+fn pre_action_window_label(timer_id: &str) -> String {
+    format!("prewarning-{timer_id}")
+}
+
+fn open_pre_action_window(app: &tauri::AppHandle, payload: &PreActionWarningPayload) {
+    let label = pre_action_window_label(&payload.timer_id);
+    let action_key = payload.action.key();
+    let (warning_seconds, countdown_seconds) = (payload.warning_seconds, payload.countdown_seconds);
+    let apps = prewarning::encode_app_list(&payload.apps);
+    if let Some(existing) = app.get_webview_window(&label) {
+        // A later warning for the same run: refresh the open window.
+        let _ = existing.eval(format!(
+            "window.applyWarning(\"{action_key}\", {warning_seconds}, {countdown_seconds}, \
+             decodeURIComponent(\"{apps}\"))"
+        ));
+        let _ = existing.set_focus();
+        let _ = existing.show();
+        return;
+    }
+
+    let url = format!(
+        "prewarning.html?action={action_key}&warning={warning_seconds}&seconds={countdown_seconds}&apps={apps}"
+    );
+    // Room for the list of apps a CloseApps run will close.
+    let height = 250.0 + 22.0 * payload.apps.len().min(6) as f64;
+
+    let _ = WebviewWindowBuilder::new(app, &label, WebviewUrl::App(url.into()))
+        .title("LockPilot - Pre Warning")
+        .inner_size(420.0, height)
+        .resizable(false)
+        .maximizable(false)
+        .minimizable(false)
+        .always_on_top(true)
+        .visible(true)
+        .focused(true)
+        .build();
+}
+
+// ─── Linux system state ───────────────────────────────────────────
+
+/// Reads the machine state timers' conditions check from sysfs, procfs and
+/// the desktop's idle monitor.
+struct LinuxSystemProbe;
+
+impl SystemProbe for LinuxSystemProbe {
+    // On battery when there is a battery and no mains supply is online.
+    fn power_source(&self) -> Result<PowerSource, String> {
+        let supplies = fs::read_dir("/sys/class/power_supply")
+            .map_err(|err| format!("Failed to read power supplies: {err}"))?;
+        let mut has_battery = false;
+        for supply in supplies.flatten() {
+            let path = supply.path();
+            let read = |name: &str| fs::read_to_string(path.join(name)).unwrap_or_default();
+            match read("type").trim() {
+                "Mains" | "USB" if read("online").trim() == "1" => return Ok(PowerSource::Ac),
+                "Battery" => has_battery = true,
+                _ => {}
+            }
+        }
+        Ok(if has_battery { PowerSource::Battery } else { PowerSource::Ac })
+    }
+
+    fn is_running(&self, name: &str) -> Result<bool, String> {
+        pgrep(name).map(|pids| !pids.is_empty())
+    }
+
+    // Busy share of CPU time over half a second.
+    fn cpu_percent(&self) -> Result<f32, String> {
+        let (total_before, idle_before) = cpu_times()?;
+        thread::sleep(Duration::from_millis(500));
+        let (total, idle) = cpu_times()?;
+        let total = total.saturating_sub(total_before);
+        let idle = idle.saturating_sub(idle_before);
+        if total == 0 {
+            return Ok(0.0);
+        }
+        Ok(total.saturating_sub(idle) as f32 * 100.0 / total as f32)
+    }
+}
+
+impl IdleSource for LinuxSystemProbe {
+    // X11 sessions answer through xprintidle, GNOME on Wayland through
+    // Mutter's idle monitor; both report milliseconds.
+    fn idle_seconds(&self) -> Result<u64, String> {
+        if let Ok(Ok(millis)) = command_stdout("xprintidle", &[]).map(|out| out.trim().parse::<u64>()) {
+            return Ok(millis / 1000);
+        }
+        let reply = command_stdout(
+            "gdbus",
+            &[
+                "call",
+                "--session",
+                "--dest=org.gnome.Mutter.IdleMonitor",
+                "--object-path=/org/gnome/Mutter/IdleMonitor/Core",
+                "--method=org.gnome.Mutter.IdleMonitor.GetIdletime",
+            ],
+        )
+        .map_err(|err| format!("install xprintidle, or use GNOME, to read idle time ({err})"))?;
+        // The reply looks like `(uint64 12345,)`.
+        reply
+            .trim()
+            .trim_start_matches("(uint64")
+            .trim_end_matches(",)")
+            .trim()
+            .parse::<u64>()
+            .map(|millis| millis / 1000)
+            .map_err(|_| format!("Unexpected idle monitor reply: {}", reply.trim()))
+    }
+}
+
+/// Total and idle (including I/O wait) CPU time so far, in ticks, from the
+/// `cpu` line of `/proc/stat`.
+fn cpu_times() -> Result<(u64, u64), String> {
+    let stat = fs::read_to_string("/proc/stat").map_err(|err| format!("Failed to read /proc/stat: {err}"))?;
+    let line = stat
+        .lines()
+        .find_map(|line| line.strip_prefix("cpu "))
+        .ok_or_else(|| "No cpu line in /proc/stat".to_string())?;
+    // user nice system idle iowait irq softirq steal; guest time is already
+    // counted in user.
+    let ticks: Vec<u64> = line.split_whitespace().take(8).filter_map(|tick| tick.parse().ok()).collect();
+    let idle = ticks.get(3).copied().unwrap_or_default() + ticks.get(4).copied().unwrap_or_default();
+    Ok((ticks.iter().sum(), idle))
+}
+
+fn command_stdout(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| format!("Failed to run {program}: {err}"))?;
+    if !output.status.success() {
+        return Err(format!("{program} exited with status {}", output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// ─── Linux system actions ─────────────────────────────────────────
+
+struct LinuxActionBackend;
+
+impl ActionBackend for LinuxActionBackend {
+    // Lock through logind, first with loginctl and then over D-Bus, and
+    // fall back to xdg-screensaver on desktops without systemd.
+    fn lock(&self) -> ActionOutcome {
+        ActionOutcome::first_success(&[
+            &|| run_program("loginctl:lock-session", "loginctl", &["lock-session"]),
+            &|| {
+                run_program(
+                    "logind-dbus:Session.Lock",
+                    "busctl",
+                    &[
+                        "call",
+                        "--system",
+                        "org.freedesktop.login1",
+                        "/org/freedesktop/login1/session/auto",
+                        "org.freedesktop.login1.Session",
+                        "Lock",
+                    ],
+                )
+            },
+            &|| run_program("xdg-screensaver:lock", "xdg-screensaver", &["lock"]),
+        ])
+    }
+
+    fn shutdown(&self) -> ActionOutcome {
+        ActionOutcome::from_attempts(vec![run_program("systemctl:poweroff", "systemctl", &["poweroff"])])
+    }
+
+    fn reboot(&self) -> ActionOutcome {
+        ActionOutcome::from_attempts(vec![run_program("systemctl:reboot", "systemctl", &["reboot"])])
+    }
+
+    fn sleep(&self) -> ActionOutcome {
+        ActionOutcome::from_attempts(vec![run_program("systemctl:suspend", "systemctl", &["suspend"])])
+    }
+
+    // Ask logind first so a missing swap partition or disabled hibernation
+    // is reported plainly rather than as a failed systemctl call.
+    fn hibernate(&self) -> ActionOutcome {
+        let mut attempts = vec![check_can_hibernate()];
+        if attempts[0].success {
+            attempts.push(run_program("systemctl:hibernate", "systemctl", &["hibernate"]));
+        }
+        ActionOutcome::from_attempts(attempts)
+    }
+
+    // Let GNOME end the session so apps can save, then have logind
+    // terminate the caller's session on other desktops.
+    fn logout(&self) -> ActionOutcome {
+        ActionOutcome::first_success(&[
+            &|| run_program("gnome-session-quit", "gnome-session-quit", &["--logout", "--no-prompt"]),
+            &|| {
+                run_program(
+                    "logind-dbus:Session.Terminate",
+                    "busctl",
+                    &[
+                        "call",
+                        "--system",
+                        "org.freedesktop.login1",
+                        "/org/freedesktop/login1/session/auto",
+                        "org.freedesktop.login1.Session",
+                        "Terminate",
+                    ],
+                )
+            },
+        ])
+    }
+
+    // A critical desktop notification stays up until dismissed; gdbus
+    // reaches the notification daemon when notify-send isn't installed.
+    fn popup(&self, message: &str) -> ActionOutcome {
+        let body = format!("\"{}\"", message.replace('\\', "\\\\").replace('"', "\\\""));
+        ActionOutcome::first_success(&[
+            &|| {
+                run_program(
+                    "notify-send",
+                    "notify-send",
+                    &["--app-name=LockPilot", "--urgency=critical", "--", "LockPilot", message],
+                )
+            },
+            &|| {
+                run_program(
+                    "gdbus:Notifications.Notify",
+                    "gdbus",
+                    &[
+                        "call",
+                        "--session",
+                        "--dest=org.freedesktop.Notifications",
+                        "--object-path=/org/freedesktop/Notifications",
+                        "--method=org.freedesktop.Notifications.Notify",
+                        "'LockPilot'",
+                        "0",
+                        "''",
+                        "'LockPilot'",
+                        &body,
+                        "[]",
+                        "{'urgency': <byte 2>}",
+                        "0",
+                    ],
+                )
+            },
+        ])
+    }
+
+    // Links and files go to their default app via xdg-open; apps launch
+    // from their desktop entry id (`slack` or `slack.desktop`).
+    fn open(&self, target: &str, kind: OpenTargetKind) -> ActionOutcome {
+        let attempt = match kind {
+            OpenTargetKind::App => {
+                run_program("gtk-launch", "gtk-launch", &[target.trim_end_matches(".desktop")])
+            }
+            OpenTargetKind::Url | OpenTargetKind::File => run_program("xdg-open", "xdg-open", &[target]),
+        };
+        ActionOutcome::from_attempts(vec![attempt])
+    }
+
+    fn close_apps(&self, spec: &CloseAppsSpec) -> ActionOutcome {
+        close::close_apps(&LinuxAppCloser, spec)
+    }
+}
+
+/// Closes apps by process name (`slack`, `code`): SIGTERM lets them save
+/// and exit, SIGKILL ends them.
+struct LinuxAppCloser;
+
+impl AppCloser for LinuxAppCloser {
+    fn running(&self, app: &str) -> Result<Vec<u32>, String> {
+        pgrep(app)
+    }
+
+    fn quit(&self, _app: &str, pids: &[u32]) -> Result<(), String> {
+        send_signal("-TERM", pids)
+    }
+
+    fn kill(&self, pids: &[u32]) -> Result<(), String> {
+        send_signal("-KILL", pids)
+    }
+}
+
+fn check_can_hibernate() -> ActionAttempt {
+    let method = "logind-dbus:CanHibernate";
+    let output = match Command::new("busctl")
+        .args([
+            "call",
+            "--system",
+            "org.freedesktop.login1",
+            "/org/freedesktop/login1",
+            "org.freedesktop.login1.Manager",
+            "CanHibernate",
+        ])
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        result => return ActionAttempt::from_output(method, result),
+    };
+    // busctl prints the reply as `s "yes"`.
+    let reply = String::from_utf8_lossy(&output.stdout);
+    let answer = reply.trim().trim_start_matches('s').trim().trim_matches('"');
+    let result = match answer {
+        "yes" => Ok(()),
+        answer => Err(format!("Hibernate is not available: logind CanHibernate returned \"{answer}\"")),
+    };
+    ActionAttempt::from_result(method, result)
+}
+
+fn run_program(method: &str, program: &str, args: &[&str]) -> ActionAttempt {
+    ActionAttempt::from_output(method, Command::new(program).args(args).output())
+}
+
+/// Ids of processes named exactly `name`.
+fn pgrep(name: &str) -> Result<Vec<u32>, String> {
+    let output = Command::new("pgrep")
+        .args(["-x", name])
+        .output()
+        .map_err(|err| format!("Failed to run pgrep: {err}"))?;
+    // pgrep exits with 1 when nothing matches.
+    if !output.status.success() && output.status.code() != Some(1) {
+        return Err(format!("pgrep exited with status {}", output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect())
+}
+
+fn send_signal(signal: &str, pids: &[u32]) -> Result<(), String> {
+    let output = Command::new("kill")
+        .arg(signal)
+        .args(pids.iter().map(u32::to_string))
+        .output()
+        .map_err(|err| format!("Failed to run kill: {err}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+fn data_file_path(app: &tauri::AppHandle, file_name: &str) -> PathBuf {
+    let base = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("lockpilot"));
+    base.join(file_name)
+}
+
+// ─── Entry point ──────────────────────────────────────────────────
+
+fn main() {
+    tauri::Builder::default()
+        .setup(|app| {
+            let scheduler = Scheduler::start(
+                TimerStore::new(data_file_path(app.handle(), "timers.json")),
+                HistoryStore::new(data_file_path(app.handle(), "history.jsonl")),
+                LockStore::new(data_file_path(app.handle(), "locks.json"))?,
+                Arc::new(LinuxActionBackend),
+                Arc::new(LinuxSystemProbe),
+                Arc::new(TauriPrompter {
+                    app: app.handle().clone(),
+                }),
+            );
+            if let Err(err) = scheduler.restore_timers() {
+                eprintln!("Failed to restore timers: {err}");
+            }
+            app.manage(scheduler);
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            create_timer,
+            update_timer,
+            list_timers,
+            cancel_timer,
+            skip_next_occurrence,
+            pause_timer,
+            resume_timer,
+            resolve_pre_action,
+            preview_recurrence,
+            list_history,
+            clear_history,
+            list_release_versions,
+            check_channel_update,
+            install_channel_update,
+            install_release
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
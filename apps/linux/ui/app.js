@@ -0,0 +1,1520 @@
+const { invoke } = window.__TAURI__.core;
+const { getVersion } = window.__TAURI__.app;
+const { listen } = window.__TAURI__.event;
+
+const form = document.getElementById("timer-form");
+const actionInput = document.getElementById("action");
+const targetTimeInput = document.getElementById("target-time");
+const setNowBtn = document.getElementById("set-now");
+const idleMinutesInput = document.getElementById("idle-minutes");
+const recurrencePresetInput = document.getElementById("recurrence-preset");
+const actionChoiceBoxes = document.querySelectorAll(".choice-box[data-action]");
+const recurrenceChoiceBoxes = document.querySelectorAll(".choice-box[data-recurrence]");
+const quickChipButtons = document.querySelectorAll("[data-quick-minutes]");
+const quickCustomInput = document.getElementById("custom-minutes-input");
+const quickCustomApplyBtn = document.getElementById("quick-custom-apply");
+const preWarnRow = document.getElementById("prewarn-row");
+const customPrewarnInput = document.getElementById("custom-prewarn-input");
+const prewarnCustomApplyBtn = document.getElementById("prewarn-custom-apply");
+const intervalWrap = document.getElementById("interval-wrap");
+const intervalHoursInput = document.getElementById("interval-hours");
+const specificDaysWrap = document.getElementById("specific-days-wrap");
+const specificDayInputs = document.querySelectorAll('input[name="specific-day"]');
+const cronWrap = document.getElementById("cron-wrap");
+const cronExpressionInput = document.getElementById("cron-expression");
+const cronPreviewBtn = document.getElementById("cron-preview-btn");
+const cronPreviewEl = document.getElementById("cron-preview");
+const rruleWrap = document.getElementById("rrule-wrap");
+const rruleInput = document.getElementById("rrule-input");
+const rrulePreviewBtn = document.getElementById("rrule-preview-btn");
+const rrulePreviewEl = document.getElementById("rrule-preview");
+const endsWrap = document.getElementById("ends-wrap");
+const endsAtInput = document.getElementById("ends-at");
+const maxOccurrencesInput = document.getElementById("max-occurrences");
+const skipWrap = document.getElementById("skip-wrap");
+const skipDatesInput = document.getElementById("skip-dates");
+const holidayCalendarInput = document.getElementById("holiday-calendar");
+const snoozeDurationsInput = document.getElementById("snooze-durations");
+const snoozeMaxCountInput = document.getElementById("snooze-max-count");
+const snoozeMaxTotalInput = document.getElementById("snooze-max-total");
+const conditionPowerInput = document.getElementById("condition-power");
+const conditionIdleInput = document.getElementById("condition-idle");
+const conditionCpuInput = document.getElementById("condition-cpu");
+const conditionProcessesInput = document.getElementById("condition-processes");
+const conditionUnmetInput = document.getElementById("condition-unmet");
+const conditionDeferInput = document.getElementById("condition-defer");
+const lockPinInput = document.getElementById("lock-pin");
+const messageWrap = document.getElementById("message-wrap");
+const messageInput = document.getElementById("message");
+const openWrap = document.getElementById("open-wrap");
+const openTargetInput = document.getElementById("open-target");
+const closeAppsWrap = document.getElementById("close-apps-wrap");
+const closeAppsInput = document.getElementById("close-apps");
+const closeGraceInput = document.getElementById("close-grace");
+const sequenceWrap = document.getElementById("sequence-wrap");
+const sequenceStepsEl = document.getElementById("sequence-steps");
+const addStepBtn = document.getElementById("add-step");
+const commandWrap = document.getElementById("command-wrap");
+const commandProgramInput = document.getElementById("command-program");
+const commandArgsInput = document.getElementById("command-args");
+const commandEnvInput = document.getElementById("command-env");
+const commandWorkingDirInput = document.getElementById("command-working-dir");
+const commandTimeoutInput = document.getElementById("command-timeout");
+const commandShellInput = document.getElementById("command-shell");
+const submitBtn = form.querySelector('button[type="submit"]');
+const cancelEditBtn = document.getElementById("cancel-edit");
+const timersEl = document.getElementById("timers");
+const statusEl = document.getElementById("status");
+const refreshBtn = document.getElementById("refresh");
+const unlockPinInput = document.getElementById("unlock-pin");
+const historyEl = document.getElementById("history");
+const historyActionSelect = document.getElementById("history-action");
+const historyFromInput = document.getElementById("history-from");
+const historyToInput = document.getElementById("history-to");
+const historyTimerInput = document.getElementById("history-timer");
+const historyRefreshBtn = document.getElementById("history-refresh");
+const historyClearBtn = document.getElementById("history-clear");
+const preActionModal = document.getElementById("pre-action-modal");
+const preActionTitleEl = document.getElementById("pre-action-title");
+const preActionLineEl = document.getElementById("pre-action-line");
+const preActionAppsEl = document.getElementById("pre-action-apps");
+const preActionSnoozeNoteEl = document.getElementById("pre-action-snooze-note");
+const preActionPinInput = document.getElementById("pre-action-pin");
+const preActionButtonsEl = document.getElementById("pre-action-buttons");
+const preActionSkipBtn = document.getElementById("pre-action-skip");
+const preActionRunBtn = document.getElementById("pre-action-run");
+
+const checkUpdatesBtn = document.getElementById("check-updates");
+const autoCheckUpdatesInput = document.getElementById("auto-check-updates");
+const updateChannelSelect = document.getElementById("update-channel");
+const currentVersionEl = document.getElementById("current-version");
+const updateStatusEl = document.getElementById("update-status");
+const updateResultEl = document.getElementById("update-result");
+const latestVersionEl = document.getElementById("latest-version");
+const latestNotesEl = document.getElementById("latest-notes");
+const installLatestBtn = document.getElementById("install-latest");
+const rollbackVersionSelect = document.getElementById("rollback-version");
+const rollbackInstallBtn = document.getElementById("rollback-install");
+const updateLoadingEl = document.getElementById("update-loading");
+const updateLoadingTextEl = document.getElementById("update-loading-text");
+
+const AUTO_UPDATE_KEY = "lockpilot.autoCheckUpdates";
+const UPDATE_CHANNEL_KEY = "lockpilot.updateChannel";
+let currentVersion = "";
+let latestUpdate = null;
+let editingTimerId = null;
+let activePrompt = null;
+let preActionTicker = null;
+
+const getPreWarningInputs = () => document.querySelectorAll('input[name="prewarn"]');
+
+const formatOffset = (seconds) => {
+  const parts = [
+    [Math.floor(seconds / 3600), "h"],
+    [Math.floor((seconds % 3600) / 60), "m"],
+    [seconds % 60, "s"],
+  ].filter(([value]) => value > 0);
+  return parts.map(([value, unit]) => `${value}${unit}`).join(" ");
+};
+
+// "30s", "2m", "1h"; a bare number means minutes.
+const parseOffset = (text) => {
+  const match = /^(\d+)\s*([smh]?)$/i.exec(String(text).trim());
+  if (!match) {
+    return null;
+  }
+  const unit = { s: 1, m: 60, h: 3600 }[match[2].toLowerCase() || "m"];
+  const seconds = Number.parseInt(match[1], 10) * unit;
+  return seconds > 0 ? seconds : null;
+};
+
+const addPreWarningChip = (seconds) => {
+  const existing = [...getPreWarningInputs()].find((input) => Number.parseInt(input.value, 10) === seconds);
+  if (existing) {
+    existing.checked = true;
+    return;
+  }
+
+  if (!preWarnRow) {
+    return;
+  }
+
+  const label = document.createElement("label");
+  label.className = "prewarn-chip";
+  label.innerHTML = `<input type=\"checkbox\" name=\"prewarn\" value=\"${seconds}\" checked />${formatOffset(seconds)}`;
+  preWarnRow.insertBefore(label, customPrewarnInput);
+};
+
+const actionLabel = (action) => {
+  if (action === "lock") {
+    return "Lock screen";
+  }
+  if (action === "shutdown") {
+    return "Shut down";
+  }
+  if (action === "reboot") {
+    return "Restart";
+  }
+  if (action === "sleep") {
+    return "Sleep";
+  }
+  if (action === "hibernate") {
+    return "Hibernate";
+  }
+  if (action === "logout") {
+    return "Log out";
+  }
+  if (action === "run_command") {
+    return "Command";
+  }
+  if (action === "open") {
+    return "Open";
+  }
+  if (action === "close_apps") {
+    return "Close apps";
+  }
+  if (action === "sequence") {
+    return "Sequence";
+  }
+  return "Action";
+};
+
+const showStatus = (text, isError = false) => {
+  statusEl.textContent = text;
+  statusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const showUpdateStatus = (text, isError = false) => {
+  updateStatusEl.textContent = text;
+  updateStatusEl.style.color = isError ? "#c30e2e" : "#4f7480";
+};
+
+const selectedChannel = () => updateChannelSelect.value;
+
+const setUpdateLoading = (loading, text = "Downloading update...") => {
+  updateLoadingTextEl.textContent = text;
+  updateLoadingEl.classList.toggle("hidden", !loading);
+  installLatestBtn.disabled = loading;
+  rollbackInstallBtn.disabled = loading;
+  checkUpdatesBtn.disabled = loading;
+  updateChannelSelect.disabled = loading;
+  rollbackVersionSelect.disabled = loading;
+};
+
+const toLocalDateTimeValue = (date) => {
+  const pad = (n) => String(n).padStart(2, "0");
+  return `${date.getFullYear()}-${pad(date.getMonth() + 1)}-${pad(date.getDate())}T${pad(date.getHours())}:${pad(date.getMinutes())}:${pad(date.getSeconds())}`;
+};
+
+const setTriggerToNow = () => {
+  targetTimeInput.value = toLocalDateTimeValue(new Date());
+};
+
+const parseLocalDateTimeValue = (value) => {
+  if (!value) {
+    return null;
+  }
+  const parsed = new Date(value);
+  return Number.isNaN(parsed.getTime()) ? null : parsed;
+};
+
+const applyQuickIncrement = (minutes, fromNow = false) => {
+  const base = parseLocalDateTimeValue(targetTimeInput.value) ?? new Date();
+  const start = fromNow ? new Date() : base;
+  let next = new Date(base.getTime() + minutes * 60000);
+  if (fromNow) {
+    next = new Date(start.getTime() + minutes * 60000);
+  }
+  const minAllowed = new Date(Date.now() + 60000);
+  if (next < minAllowed) {
+    next = minAllowed;
+  }
+  targetTimeInput.value = toLocalDateTimeValue(next);
+};
+
+const toggleActionFields = () => {
+  const isPopup = actionInput.value === "popup";
+  messageWrap.classList.toggle("is-blank", !isPopup);
+  messageInput.required = false;
+  commandWrap.classList.toggle("hidden", actionInput.value !== "run_command");
+  openWrap.classList.toggle("hidden", actionInput.value !== "open");
+  closeAppsWrap.classList.toggle("hidden", actionInput.value !== "close_apps");
+  sequenceWrap.classList.toggle("hidden", actionInput.value !== "sequence");
+  if (actionInput.value === "sequence" && !sequenceStepsEl.children.length) {
+    addSequenceStep();
+  }
+};
+
+const syncActionChoices = () => {
+  actionChoiceBoxes.forEach((box) => {
+    box.classList.toggle("is-active", box.dataset.action === actionInput.value);
+  });
+};
+
+const syncRecurrenceChoices = () => {
+  recurrenceChoiceBoxes.forEach((box) => {
+    box.classList.toggle("is-active", box.dataset.recurrence === recurrencePresetInput.value);
+  });
+};
+
+const toggleRecurrence = () => {
+  const recurring = recurrencePresetInput.value !== "none";
+  const needsInterval =
+    recurrencePresetInput.value === "every_n_hours" ||
+    recurrencePresetInput.value === "every_n_minutes";
+  const needsSpecificDays = recurrencePresetInput.value === "specific_days";
+  const needsCron = recurrencePresetInput.value === "cron";
+  const needsRrule = recurrencePresetInput.value === "rrule";
+  intervalWrap.classList.toggle("hidden", !needsInterval);
+  specificDaysWrap.classList.toggle("hidden", !needsSpecificDays);
+  cronWrap.classList.toggle("hidden", !needsCron);
+  rruleWrap.classList.toggle("hidden", !needsRrule);
+  endsWrap.classList.toggle("hidden", !recurring);
+  skipWrap.classList.toggle("hidden", !recurring);
+  intervalHoursInput.required = needsInterval;
+  cronExpressionInput.required = needsCron;
+  rruleInput.required = needsRrule;
+  intervalHoursInput.max = recurrencePresetInput.value === "every_n_minutes" ? "1440" : "24";
+
+  if (!recurring) {
+    intervalWrap.classList.add("hidden");
+    specificDaysWrap.classList.add("hidden");
+    cronWrap.classList.add("hidden");
+    rruleWrap.classList.add("hidden");
+    endsWrap.classList.add("hidden");
+    skipWrap.classList.add("hidden");
+  }
+};
+
+const fmtDate = (iso) => new Date(iso).toLocaleString();
+
+const fmtRemaining = (iso) => {
+  const ms = new Date(iso).getTime() - Date.now();
+  if (ms <= 0) {
+    return "due now";
+  }
+
+  const total = Math.floor(ms / 1000);
+  const hours = Math.floor(total / 3600);
+  const minutes = Math.floor((total % 3600) / 60);
+  const seconds = total % 60;
+  return `${hours}h ${minutes}m ${seconds}s`;
+};
+
+const recurrenceLabel = (recurrence) => {
+  if (!recurrence) {
+    return "One-time";
+  }
+
+  if (recurrence.preset === "daily") {
+    return "Repeats daily";
+  }
+
+  if (recurrence.preset === "weekdays") {
+    return "Repeats weekdays";
+  }
+
+  if (recurrence.preset === "specific_days") {
+    const shortToLong = {
+      mon: "Mon",
+      tue: "Tue",
+      wed: "Wed",
+      thu: "Thu",
+      fri: "Fri",
+      sat: "Sat",
+      sun: "Sun",
+    };
+    const days = (recurrence.daysOfWeek ?? [])
+      .map((day) => shortToLong[day] ?? day)
+      .join(", ");
+    return days ? `Repeats on ${days}` : "Repeats on specific days";
+  }
+
+  if (recurrence.preset === "every_n_hours") {
+    return `Repeats every ${recurrence.intervalHours ?? "?"} hour(s)`;
+  }
+
+  if (recurrence.preset === "every_n_minutes") {
+    return `Repeats every ${recurrence.intervalMinutes ?? "?"} minute(s)`;
+  }
+
+  if (recurrence.preset === "cron") {
+    return `Repeats on cron: ${recurrence.cronExpression ?? "?"}`;
+  }
+
+  if (recurrence.preset === "rrule") {
+    return `Repeats by rule: ${recurrence.rrule ?? "?"}`;
+  }
+
+  return "Recurring";
+};
+
+const parseSkipDates = (value) => {
+  const dates = value
+    .split(/[\s,]+/)
+    .map((date) => date.trim())
+    .filter(Boolean);
+  return dates.length ? dates : null;
+};
+
+// PIN typed for locked timers; unlocked timers ignore it.
+const unlockPin = () => unlockPinInput.value || null;
+
+const attemptsError = (attempts) => {
+  const last = [...attempts].reverse().find((attempt) => !attempt.success);
+  if (!last) {
+    return "No method was attempted";
+  }
+  if (last.stderr) {
+    return `${last.method}: ${last.stderr}`;
+  }
+  return last.exitCode != null ? `${last.method} exited with status ${last.exitCode}` : `${last.method} failed`;
+};
+
+// Mirrors the backend's summary of a failed action: its last failed attempt,
+// or each failed step of a sequence, or where the sequence was stopped.
+const outcomeError = (outcome) => {
+  if (!outcome || outcome.success) {
+    return null;
+  }
+  if (!outcome.steps?.length) {
+    return attemptsError(outcome.attempts);
+  }
+  const failed = outcome.steps
+    .map((step, index) => ({ step, index }))
+    .filter(({ step }) => step.startedAt && !step.success)
+    .map(({ step, index }) => `step ${index + 1} (${step.action}): ${attemptsError(step.attempts)}`);
+  if (!failed.length) {
+    const next = outcome.steps.findIndex((step) => !step.startedAt);
+    return `stopped before step ${Math.max(next, 0) + 1}`;
+  }
+  return failed.join("; ");
+};
+
+const pausedLabel = (timer) => {
+  if (timer.status !== "paused") {
+    return null;
+  }
+  if (timer.recurrence || timer.idleTrigger) {
+    return "Paused; runs are skipped until resumed";
+  }
+  const seconds = timer.remainingSeconds ?? 0;
+  const hours = Math.floor(seconds / 3600);
+  const minutes = Math.floor((seconds % 3600) / 60);
+  return `Paused with ${hours}h ${minutes}m ${seconds % 60}s left`;
+};
+
+const endConditionLabel = (timer) => {
+  const recurrence = timer.recurrence;
+  if (!recurrence) {
+    return null;
+  }
+
+  const parts = [];
+  if (recurrence.maxOccurrences) {
+    parts.push(`Run ${timer.occurrenceCount + 1} of ${recurrence.maxOccurrences}`);
+  }
+  if (recurrence.endsAt) {
+    parts.push(`Ends ${fmtDate(recurrence.endsAt)}`);
+  }
+  if (recurrence.skipDates?.length) {
+    parts.push(`Skips ${recurrence.skipDates.length} date(s)`);
+  }
+  if (recurrence.holidayCalendar) {
+    parts.push("Skips holidays");
+  }
+  return parts.length ? parts.join(" · ") : null;
+};
+
+const optionalNumber = (input) => (input.value === "" ? null : Number(input.value));
+
+// Idle timers have no run time, so the time field is switched off.
+const toggleIdleTrigger = () => {
+  const idle = idleMinutesInput.value !== "";
+  targetTimeInput.disabled = idle;
+  setNowBtn.disabled = idle;
+};
+
+const idleLabel = (timer) => {
+  const after = `Runs after ${formatOffset(timer.idleTrigger.minutes * 60)} without input`;
+  return timer.occurrenceCount ? `${after}; last ran ${fmtDate(timer.targetTime)}` : after;
+};
+
+const buildConditions = () => {
+  const conditions = {
+    power: conditionPowerInput.value || null,
+    minIdleMinutes: optionalNumber(conditionIdleInput),
+    blockingProcesses: conditionProcessesInput.value
+      .split(",")
+      .map((name) => name.trim())
+      .filter(Boolean),
+    maxCpuPercent: optionalNumber(conditionCpuInput),
+    whenUnmet: conditionUnmetInput.value,
+    deferMinutes: conditionUnmetInput.value === "defer" ? optionalNumber(conditionDeferInput) : null,
+  };
+  const empty =
+    !conditions.power &&
+    conditions.minIdleMinutes === null &&
+    !conditions.blockingProcesses.length &&
+    conditions.maxCpuPercent === null;
+  return empty ? null : conditions;
+};
+
+const unmetLabels = {
+  skip: "else skip",
+  defer: "else defer",
+  run_anyway: "else run anyway",
+};
+
+// e.g. `Only if on AC power · idle 10m · zoom not running · else defer 10m`.
+const conditionsLabel = (conditions) => {
+  if (!conditions) {
+    return null;
+  }
+  const parts = [
+    conditions.power === "ac" ? "on AC power" : conditions.power === "battery" ? "on battery" : null,
+    conditions.minIdleMinutes ? `idle ${formatOffset(conditions.minIdleMinutes * 60)}` : null,
+    conditions.blockingProcesses.length ? `${conditions.blockingProcesses.join(", ")} not running` : null,
+    conditions.maxCpuPercent ? `CPU below ${conditions.maxCpuPercent}%` : null,
+  ].filter(Boolean);
+  const otherwise = conditions.deferMinutes
+    ? `${unmetLabels.defer} ${formatOffset(conditions.deferMinutes * 60)}`
+    : unmetLabels[conditions.whenUnmet];
+  return `Only if ${parts.join(" · ")} · ${otherwise}`;
+};
+
+const buildSnooze = () => {
+  const durationsMinutes = snoozeDurationsInput.value
+    .split(/[\s,]+/)
+    .map((value) => Number.parseInt(value, 10))
+    .filter((value) => Number.isInteger(value) && value > 0);
+  const maxCount = optionalNumber(snoozeMaxCountInput);
+  const maxTotalMinutes = optionalNumber(snoozeMaxTotalInput);
+  if (!durationsMinutes.length && maxCount === null && maxTotalMinutes === null) {
+    return null;
+  }
+  return {
+    durationsMinutes: durationsMinutes.length ? durationsMinutes : [10],
+    maxCount,
+    maxTotalMinutes,
+  };
+};
+
+const lines = (text) =>
+  text
+    .split("\n")
+    .map((line) => line.trim())
+    .filter(Boolean);
+
+const STEP_ACTIONS = [
+  "popup",
+  "lock",
+  "shutdown",
+  "reboot",
+  "sleep",
+  "hibernate",
+  "logout",
+  "run_command",
+  "open",
+  "close_apps",
+];
+
+const STEP_DETAIL_PLACEHOLDERS = {
+  popup: "Message",
+  run_command: "Command line (runs through the shell)",
+  open: "Link, file or app",
+  close_apps: "Apps, comma separated",
+};
+
+// The one setting a step's action takes, as typed in its detail field.
+const stepDetail = (step) => {
+  if (step.action === "popup") {
+    return step.message ?? "";
+  }
+  if (step.action === "run_command") {
+    return step.command?.program ?? "";
+  }
+  if (step.action === "open") {
+    return step.openTarget ?? "";
+  }
+  if (step.action === "close_apps") {
+    return (step.closeApps?.apps ?? []).join(", ");
+  }
+  return "";
+};
+
+const addSequenceStep = (step = { action: "popup", delaySeconds: 0, continueOnError: false }) => {
+  const row = document.createElement("div");
+  row.className = "sequence-step";
+
+  const action = document.createElement("select");
+  action.setAttribute("aria-label", "Step action");
+  for (const value of STEP_ACTIONS) {
+    action.append(new Option(actionLabel(value), value));
+  }
+  action.value = step.action;
+
+  const delay = document.createElement("input");
+  delay.type = "number";
+  delay.min = "0";
+  delay.placeholder = "Wait (s)";
+  delay.setAttribute("aria-label", "Seconds to wait before this step");
+  delay.value = step.delaySeconds ? String(step.delaySeconds) : "";
+
+  const detail = document.createElement("input");
+  detail.type = "text";
+  detail.setAttribute("aria-label", "Step setting");
+  detail.value = stepDetail(step);
+
+  const syncDetail = () => {
+    const placeholder = STEP_DETAIL_PLACEHOLDERS[action.value];
+    detail.placeholder = placeholder ?? "";
+    detail.disabled = !placeholder;
+  };
+  action.addEventListener("change", syncDetail);
+  syncDetail();
+
+  const toggle = document.createElement("label");
+  toggle.className = "inline-toggle";
+  const continueOnError = document.createElement("input");
+  continueOnError.type = "checkbox";
+  continueOnError.checked = Boolean(step.continueOnError);
+  toggle.append(continueOnError, "Continue on error");
+
+  const remove = document.createElement("button");
+  remove.type = "button";
+  remove.className = "secondary";
+  remove.textContent = "Remove";
+  remove.addEventListener("click", () => row.remove());
+
+  row.append(action, delay, detail, toggle, remove);
+  row.readStep = () => {
+    const value = detail.value.trim();
+    return {
+      action: action.value,
+      delaySeconds: delay.value === "" ? 0 : Number(delay.value),
+      continueOnError: continueOnError.checked,
+      message: action.value === "popup" ? value : null,
+      command: action.value === "run_command" ? { program: value, shell: true } : null,
+      openTarget: action.value === "open" ? value : null,
+      closeApps:
+        action.value === "close_apps"
+          ? { apps: value.split(",").map((app) => app.trim()).filter(Boolean), graceSeconds: null }
+          : null,
+    };
+  };
+  sequenceStepsEl.append(row);
+};
+
+const buildSteps = () => {
+  if (actionInput.value !== "sequence") {
+    return null;
+  }
+  return [...sequenceStepsEl.children].map((row) => row.readStep());
+};
+
+// A sequence as one line, e.g. `Popup → wait 60s → Close apps → Lock`.
+const sequenceLabel = (steps) =>
+  steps
+    .map((step) => {
+      const label = actionLabel(step.action);
+      return step.delaySeconds ? `wait ${formatOffset(step.delaySeconds)} → ${label}` : label;
+    })
+    .join(" → ");
+
+const buildCloseApps = () => {
+  if (actionInput.value !== "close_apps") {
+    return null;
+  }
+  return {
+    apps: lines(closeAppsInput.value),
+    graceSeconds: optionalNumber(closeGraceInput),
+  };
+};
+
+const buildCommand = () => {
+  if (actionInput.value !== "run_command") {
+    return null;
+  }
+  const env = {};
+  for (const line of lines(commandEnvInput.value)) {
+    const split = line.indexOf("=");
+    if (split > 0) {
+      env[line.slice(0, split).trim()] = line.slice(split + 1);
+    }
+  }
+  return {
+    program: commandProgramInput.value.trim(),
+    args: lines(commandArgsInput.value),
+    env,
+    workingDir: commandWorkingDirInput.value.trim() || null,
+    timeoutSeconds: optionalNumber(commandTimeoutInput),
+    shell: commandShellInput.checked,
+  };
+};
+
+const commandLabel = (command) => {
+  const parts = [command.program, ...(command.args ?? [])];
+  return `${command.shell ? "Shell" : "Command"}: ${parts.join(" ")}`;
+};
+
+const buildRecurrence = () => {
+  const recurrencePreset = recurrencePresetInput.value;
+  if (recurrencePreset === "none") {
+    return null;
+  }
+
+  const selectedSpecificDays = [...specificDayInputs]
+    .filter((input) => input.checked)
+    .map((input) => input.value);
+
+  return {
+    preset: recurrencePreset,
+    intervalHours: recurrencePreset === "every_n_hours" ? Number(intervalHoursInput.value || 0) : null,
+    intervalMinutes: recurrencePreset === "every_n_minutes" ? Number(intervalHoursInput.value || 0) : null,
+    daysOfWeek: recurrencePreset === "specific_days" ? selectedSpecificDays : null,
+    cronExpression: recurrencePreset === "cron" ? cronExpressionInput.value.trim() : null,
+    rrule: recurrencePreset === "rrule" ? rruleInput.value.trim() : null,
+    timezone: Intl.DateTimeFormat().resolvedOptions().timeZone || null,
+    endsAt: parseLocalDateTimeValue(endsAtInput.value)?.toISOString() ?? null,
+    maxOccurrences: maxOccurrencesInput.value ? Number(maxOccurrencesInput.value) : null,
+    skipDates: parseSkipDates(skipDatesInput.value),
+    holidayCalendar: holidayCalendarInput.value.trim() || null,
+  };
+};
+
+const previewRecurrence = async (listEl) => {
+  listEl.innerHTML = "";
+  const recurrence = buildRecurrence();
+  if (!recurrence) {
+    return;
+  }
+
+  const startTime = parseLocalDateTimeValue(targetTimeInput.value);
+  try {
+    const preview = await invoke("preview_recurrence", {
+      request: {
+        recurrence,
+        startTime: startTime ? startTime.toISOString() : null,
+        count: 5,
+      },
+    });
+    if (!preview.runs.length) {
+      showStatus("This schedule has no upcoming runs.", true);
+      return;
+    }
+    for (const run of preview.runs) {
+      const item = document.createElement("li");
+      item.textContent = fmtDate(run);
+      listEl.appendChild(item);
+    }
+  } catch (err) {
+    showStatus(String(err), true);
+  }
+};
+
+const renderTimers = (timers) => {
+  timersEl.innerHTML = "";
+  unlockPinInput.classList.toggle("hidden", !timers.some((timer) => timer.locked));
+
+  if (!timers.length) {
+    const empty = document.createElement("li");
+    empty.className = "empty";
+    empty.textContent = "No active timers.";
+    timersEl.appendChild(empty);
+    return;
+  }
+
+  for (const timer of timers) {
+    const item = document.createElement("li");
+    item.className = "timer-item";
+
+    const top = document.createElement("div");
+    top.className = "timer-top";
+
+    const title = document.createElement("strong");
+    title.textContent = timer.action.replace(/_/g, " ").toUpperCase();
+
+    // One-shots that have run stay listed, with their result, until removed.
+    const done = timer.status === "done";
+    const cancelBtn = document.createElement("button");
+    cancelBtn.className = "danger";
+    cancelBtn.textContent = done ? "Remove" : "Cancel";
+    cancelBtn.addEventListener("click", async () => {
+      try {
+        await invoke("cancel_timer", { id: timer.id, pin: unlockPin() });
+        if (editingTimerId === timer.id) {
+          resetForm();
+        }
+        await loadTimers();
+        showStatus(done ? "Timer removed." : "Timer canceled.");
+      } catch (err) {
+        showStatus(String(err), true);
+      }
+    });
+
+    const actions = document.createElement("div");
+    actions.className = "timer-actions";
+
+    const paused = timer.status === "paused";
+    const pauseBtn = document.createElement("button");
+    pauseBtn.className = "secondary";
+    pauseBtn.textContent = paused ? "Resume" : "Pause";
+    pauseBtn.addEventListener("click", async () => {
+      try {
+        await invoke(paused ? "resume_timer" : "pause_timer", { id: timer.id, pin: unlockPin() });
+        await loadTimers();
+        showStatus(paused ? "Timer resumed." : "Timer paused.");
+      } catch (err) {
+        showStatus(String(err), true);
+      }
+    });
+    if (!done) {
+      actions.append(pauseBtn);
+    }
+
+    const editBtn = document.createElement("button");
+    editBtn.className = "secondary";
+    editBtn.textContent = "Edit";
+    editBtn.addEventListener("click", () => startEditing(timer));
+    actions.append(editBtn);
+
+    if (timer.recurrence) {
+      const skipBtn = document.createElement("button");
+      skipBtn.className = "secondary";
+      skipBtn.textContent = "Skip Next";
+      skipBtn.addEventListener("click", async () => {
+        try {
+          await invoke("skip_next_occurrence", { id: timer.id, pin: unlockPin() });
+          await loadTimers();
+          showStatus("Next occurrence skipped.");
+        } catch (err) {
+          showStatus(String(err), true);
+        }
+      });
+      actions.append(skipBtn);
+    }
+
+    actions.append(cancelBtn);
+    top.append(title, actions);
+
+    const when = document.createElement("div");
+    when.className = "timer-meta";
+    when.textContent =
+      done
+        ? `Ran at ${fmtDate(timer.targetTime)}`
+        : pausedLabel(timer) ??
+          (timer.idleTrigger
+            ? idleLabel(timer)
+            : `Runs at ${fmtDate(timer.targetTime)} (${fmtRemaining(timer.targetTime)})`);
+
+    const recurrence = document.createElement("div");
+    recurrence.className = "timer-meta";
+    recurrence.textContent = recurrenceLabel(timer.recurrence);
+
+    item.append(top, when, recurrence);
+
+    if (timer.idleError) {
+      const idleError = document.createElement("div");
+      idleError.className = "timer-meta";
+      idleError.textContent = `Can't read idle time (${timer.idleError}); still checking`;
+      item.append(idleError);
+    }
+
+    const lastError = outcomeError(timer.lastOutcome);
+    if (lastError) {
+      const failed = document.createElement("div");
+      failed.className = "timer-meta";
+      failed.textContent = `Last run failed (${lastError})`;
+      item.append(failed);
+    }
+
+    if (timer.locked) {
+      const lock = document.createElement("div");
+      lock.className = "timer-meta";
+      lock.textContent = "Locked: cancel, edit, pause, resume, skip and snooze need the PIN";
+      item.append(lock);
+    }
+
+    const onlyIf = conditionsLabel(timer.conditions);
+    if (onlyIf) {
+      const conditions = document.createElement("div");
+      conditions.className = "timer-meta";
+      conditions.textContent = onlyIf;
+      item.append(conditions);
+    }
+
+    const endsLabel = endConditionLabel(timer);
+    if (endsLabel) {
+      const ends = document.createElement("div");
+      ends.className = "timer-meta";
+      ends.textContent = endsLabel;
+      item.append(ends);
+    }
+
+    if (timer.action === "popup" && timer.message) {
+      const msg = document.createElement("div");
+      msg.className = "timer-meta";
+      msg.textContent = `Message: ${timer.message}`;
+      item.append(msg);
+    }
+
+    if (timer.action === "open" && timer.openTarget) {
+      const target = document.createElement("div");
+      target.className = "timer-meta";
+      target.textContent = `Opens: ${timer.openTarget}`;
+      item.append(target);
+    }
+
+    if (timer.action === "close_apps" && timer.closeApps) {
+      const apps = document.createElement("div");
+      apps.className = "timer-meta";
+      apps.textContent = `Closes: ${timer.closeApps.apps.join(", ")}`;
+      item.append(apps);
+
+      // One attempt per app, such as `force-quit:steam`.
+      const failed = (timer.lastOutcome?.attempts ?? []).filter((attempt) => !attempt.success);
+      if (failed.length) {
+        const result = document.createElement("div");
+        result.className = "timer-meta";
+        result.textContent = `Last run: ${failed.map((attempt) => `${attempt.method} (${attempt.stderr ?? "failed"})`).join(", ")}`;
+        item.append(result);
+      }
+    }
+
+    if (timer.action === "sequence" && timer.steps) {
+      const steps = document.createElement("div");
+      steps.className = "timer-meta";
+      steps.textContent = `Steps: ${sequenceLabel(timer.steps)}`;
+      if (timer.sequenceRun) {
+        steps.textContent += ` (running; step ${timer.sequenceRun.nextStep + 1} of ${timer.steps.length} next)`;
+      }
+      item.append(steps);
+    }
+
+    if (timer.action === "run_command" && timer.command) {
+      const command = document.createElement("div");
+      command.className = "timer-meta";
+      command.textContent = commandLabel(timer.command);
+      item.append(command);
+
+      const result = timer.lastOutcome?.attempts?.[0];
+      if (result) {
+        const output = document.createElement("div");
+        output.className = "timer-meta";
+        const status = result.exitCode != null ? `exit ${result.exitCode}` : result.success ? "done" : "failed";
+        output.textContent = result.stdout ? `Last run: ${status} · ${result.stdout}` : `Last run: ${status}`;
+        item.append(output);
+      }
+    }
+
+    timersEl.append(item);
+  }
+};
+
+const hidePreAction = () => {
+  activePrompt = null;
+  preActionPinInput.value = "";
+  clearInterval(preActionTicker);
+  preActionModal.classList.add("hidden");
+};
+
+const resolvePreAction = async (decision, snoozeMinutes = null) => {
+  if (!activePrompt) {
+    return;
+  }
+
+  try {
+    await invoke("resolve_pre_action", {
+      request: {
+        promptId: activePrompt.promptId,
+        decision,
+        snoozeMinutes,
+        pin: preActionPinInput.value || null,
+      },
+    });
+    hidePreAction();
+    await loadTimers();
+    await loadHistory();
+  } catch (err) {
+    showStatus(String(err), true);
+  }
+};
+
+const snoozeNote = (payload) => {
+  if (payload.snoozesRemaining === 0) {
+    return "No snoozes left for this run.";
+  }
+  if (!payload.snoozeOptionsMinutes.length) {
+    return "Snoozing would pass this run's delay limit.";
+  }
+  if (payload.snoozesRemaining != null) {
+    return `${payload.snoozesRemaining} snooze(s) left.`;
+  }
+  return "";
+};
+
+const showPreAction = (payload) => {
+  activePrompt = payload;
+  preActionTitleEl.textContent = `${actionLabel(payload.action)} incoming`;
+  preActionAppsEl.textContent = payload.apps.length ? `Closes: ${payload.apps.join(", ")}` : "";
+  preActionAppsEl.classList.toggle("hidden", !payload.apps.length);
+  preActionSnoozeNoteEl.textContent = snoozeNote(payload);
+  preActionPinInput.classList.toggle("hidden", !payload.locked);
+
+  preActionButtonsEl.querySelectorAll("[data-snooze-minutes]").forEach((button) => button.remove());
+  for (const minutes of payload.snoozeOptionsMinutes) {
+    const button = document.createElement("button");
+    button.className = "secondary";
+    button.dataset.snoozeMinutes = String(minutes);
+    button.textContent = `Snooze ${formatOffset(minutes * 60)}`;
+    button.addEventListener("click", () => resolvePreAction("snooze", minutes));
+    preActionButtonsEl.insertBefore(button, preActionSkipBtn);
+  }
+
+  const deadline = Date.now() + payload.countdownSeconds * 1000;
+  const tick = () => {
+    const seconds = Math.round((deadline - Date.now()) / 1000);
+    if (seconds <= 0) {
+      hidePreAction();
+      return;
+    }
+    preActionLineEl.textContent = `${actionLabel(payload.action)} in ${formatOffset(seconds)}`;
+  };
+  clearInterval(preActionTicker);
+  tick();
+  preActionTicker = setInterval(tick, 1000);
+  preActionModal.classList.remove("hidden");
+};
+
+const loadTimers = async () => {
+  try {
+    const timers = await invoke("list_timers");
+    renderTimers(timers);
+  } catch (err) {
+    showStatus(String(err), true);
+  }
+};
+
+const historyEventLabels = {
+  executed: "Ran",
+  skipped: "Skipped",
+  ended: "Ended",
+  pin_rejected: "Wrong PIN",
+  deferred: "Deferred",
+  idle_unavailable: "Idle time unavailable",
+};
+
+// Dates are local days; the range covers the whole "to" day.
+const historyFilter = () => {
+  const dayStart = (value, offsetDays = 0) => {
+    if (!value) {
+      return null;
+    }
+    const date = new Date(`${value}T00:00:00`);
+    date.setDate(date.getDate() + offsetDays);
+    return date.toISOString();
+  };
+
+  return {
+    timerId: historyTimerInput.value.trim() || null,
+    action: historyActionSelect.value || null,
+    from: dayStart(historyFromInput.value),
+    to: dayStart(historyToInput.value, 1),
+  };
+};
+
+const renderHistory = (entries) => {
+  historyEl.innerHTML = "";
+
+  if (!entries.length) {
+    const empty = document.createElement("li");
+    empty.className = "empty";
+    empty.textContent = "No history yet.";
+    historyEl.appendChild(empty);
+    return;
+  }
+
+  for (const entry of entries) {
+    const item = document.createElement("li");
+    item.className = "timer-item";
+
+    const title = document.createElement("strong");
+    title.textContent = `${entry.action.replace(/_/g, " ").toUpperCase()} · ${historyEventLabels[entry.event] ?? entry.event}`;
+
+    const when = document.createElement("div");
+    when.className = "timer-meta";
+    when.textContent = entry.scheduledTime
+      ? `Scheduled ${fmtDate(entry.scheduledTime)} · Recorded ${fmtDate(entry.recordedAt)}`
+      : `Recorded ${fmtDate(entry.recordedAt)}`;
+    item.append(title, when);
+
+    const notes = [
+      entry.decision ? `Decision: ${entry.decision.replace(/_/g, " ")}` : null,
+      entry.error ? `Error: ${entry.error}` : null,
+      entry.detail,
+      `Timer ${entry.timerId}`,
+    ].filter(Boolean);
+    for (const note of notes) {
+      const line = document.createElement("div");
+      line.className = "timer-meta";
+      line.textContent = note;
+      item.append(line);
+    }
+
+    historyEl.append(item);
+  }
+};
+
+const loadHistory = async () => {
+  try {
+    renderHistory(await invoke("list_history", { filter: historyFilter() }));
+  } catch (err) {
+    showStatus(String(err), true);
+  }
+};
+
+const renderUpdateResult = (update) => {
+  if (!update) {
+    updateResultEl.classList.add("hidden");
+    latestUpdate = null;
+    return;
+  }
+
+  latestUpdate = update;
+  updateResultEl.classList.remove("hidden");
+  latestVersionEl.textContent = update.tag;
+  const trimmedNotes = update.notes?.trim() ?? "";
+  const notesWithoutBuildMeta = trimmedNotes
+    ? trimmedNotes.replace(/\n*\s*Build Metadata[\s\S]*$/i, "").trim()
+    : "";
+  latestNotesEl.textContent = notesWithoutBuildMeta
+    ? notesWithoutBuildMeta
+    : "What's New:\n- Backend Adjustments\n- Optimizations";
+};
+
+const loadRollbackVersions = async () => {
+  rollbackVersionSelect.innerHTML = "";
+
+  try {
+    const versions = await invoke("list_release_versions");
+    versions.forEach((version) => {
+      const option = document.createElement("option");
+      option.value = version.tag;
+      option.textContent = `${version.tag}${version.publishedAt ? ` (${new Date(version.publishedAt).toLocaleDateString()})` : ""}`;
+      rollbackVersionSelect.appendChild(option);
+    });
+  } catch (err) {
+    showUpdateStatus(`Could not load release versions: ${String(err)}`, true);
+  }
+};
+
+const checkForUpdates = async (silentWhenUpToDate = false) => {
+  if (!currentVersion) {
+    return;
+  }
+
+  const channel = selectedChannel();
+
+  try {
+    showUpdateStatus(`Checking ${channel} channel on GitHub releases...`);
+    const update = await invoke("check_channel_update", {
+      currentVersion,
+      channel,
+    });
+    renderUpdateResult(update);
+
+    if (update) {
+      showUpdateStatus(`Update available in ${channel}: ${update.tag}`);
+    } else if (!silentWhenUpToDate) {
+      showUpdateStatus(`No newer version found in ${channel}.`);
+    } else {
+      showUpdateStatus("");
+    }
+  } catch (err) {
+    showUpdateStatus(`Update check failed: ${String(err)}`, true);
+  }
+};
+
+const installChannelUpdate = async () => {
+  const channel = selectedChannel();
+
+  try {
+    setUpdateLoading(true, `Downloading ${channel} update...`);
+    await invoke("install_channel_update", { channel });
+    showUpdateStatus("Installer opened. Follow the prompts to install the update.");
+  } catch (err) {
+    showUpdateStatus(`Install failed: ${String(err)}`, true);
+  } finally {
+    setUpdateLoading(false);
+  }
+};
+
+const installTag = async (tag) => {
+  try {
+    setUpdateLoading(true, `Downloading ${tag}...`);
+    await invoke("install_release", { tag });
+    showUpdateStatus("Installer opened. Follow the prompts to install the update.");
+  } catch (err) {
+    showUpdateStatus(`Install failed: ${String(err)}`, true);
+  } finally {
+    setUpdateLoading(false);
+  }
+};
+
+const resetForm = () => {
+  const selectedAction = actionInput.value;
+  const selectedTargetTime = targetTimeInput.value;
+  form.reset();
+  actionInput.value = selectedAction;
+  targetTimeInput.value = selectedTargetTime;
+  recurrencePresetInput.value = "none";
+  intervalHoursInput.value = "2";
+  specificDayInputs.forEach((input) => {
+    input.checked = false;
+  });
+  cronExpressionInput.value = "";
+  cronPreviewEl.innerHTML = "";
+  rruleInput.value = "";
+  rrulePreviewEl.innerHTML = "";
+  endsAtInput.value = "";
+  maxOccurrencesInput.value = "";
+  skipDatesInput.value = "";
+  holidayCalendarInput.value = "";
+  snoozeDurationsInput.value = "";
+  snoozeMaxCountInput.value = "";
+  snoozeMaxTotalInput.value = "";
+  conditionPowerInput.value = "";
+  conditionIdleInput.value = "";
+  conditionCpuInput.value = "";
+  conditionProcessesInput.value = "";
+  conditionUnmetInput.value = "skip";
+  conditionDeferInput.value = "";
+  lockPinInput.value = "";
+  idleMinutesInput.value = "";
+  commandShellInput.checked = false;
+  sequenceStepsEl.innerHTML = "";
+  setEditing(null);
+  toggleIdleTrigger();
+  toggleActionFields();
+  toggleRecurrence();
+  syncActionChoices();
+  syncRecurrenceChoices();
+};
+
+const setEditing = (timerId) => {
+  editingTimerId = timerId;
+  submitBtn.textContent = timerId ? "Save Changes" : "Add Timer";
+  cancelEditBtn.classList.toggle("hidden", !timerId);
+};
+
+const startEditing = (timer) => {
+  resetForm();
+  const recurrence = timer.recurrence;
+
+  actionInput.value = timer.action;
+  targetTimeInput.value = toLocalDateTimeValue(new Date(timer.targetTime));
+  idleMinutesInput.value = timer.idleTrigger?.minutes ?? "";
+  messageInput.value = timer.message ?? "";
+  const command = timer.command;
+  commandProgramInput.value = command?.program ?? "";
+  commandArgsInput.value = (command?.args ?? []).join("\n");
+  commandEnvInput.value = Object.entries(command?.env ?? {})
+    .map(([key, value]) => `${key}=${value}`)
+    .join("\n");
+  commandWorkingDirInput.value = command?.workingDir ?? "";
+  commandTimeoutInput.value = command?.timeoutSeconds ?? "";
+  commandShellInput.checked = Boolean(command?.shell);
+  openTargetInput.value = timer.openTarget ?? "";
+  closeAppsInput.value = (timer.closeApps?.apps ?? []).join("\n");
+  sequenceStepsEl.innerHTML = "";
+  for (const step of timer.steps ?? []) {
+    addSequenceStep(step);
+  }
+  closeGraceInput.value = timer.closeApps?.graceSeconds ?? "";
+  recurrencePresetInput.value = recurrence?.preset ?? "none";
+
+  if (recurrence) {
+    const interval = recurrence.intervalHours ?? recurrence.intervalMinutes;
+    if (interval) {
+      intervalHoursInput.value = String(interval);
+    }
+    const days = recurrence.daysOfWeek ?? [];
+    specificDayInputs.forEach((input) => {
+      input.checked = days.includes(input.value);
+    });
+    cronExpressionInput.value = recurrence.cronExpression ?? "";
+    rruleInput.value = recurrence.rrule ?? "";
+    endsAtInput.value = recurrence.endsAt ? toLocalDateTimeValue(new Date(recurrence.endsAt)) : "";
+    maxOccurrencesInput.value = recurrence.maxOccurrences ? String(recurrence.maxOccurrences) : "";
+    skipDatesInput.value = (recurrence.skipDates ?? []).join(", ");
+    holidayCalendarInput.value = recurrence.holidayCalendar ?? "";
+  }
+
+  const warnings = timer.preWarningSeconds ?? (timer.preWarningMinutes ?? []).map((minutes) => minutes * 60);
+  getPreWarningInputs().forEach((input) => {
+    input.checked = warnings.includes(Number.parseInt(input.value, 10));
+  });
+  warnings.forEach(addPreWarningChip);
+
+  const snooze = timer.snooze;
+  snoozeDurationsInput.value = (snooze?.durationsMinutes ?? []).join(", ");
+  snoozeMaxCountInput.value = snooze?.maxCount ?? "";
+  snoozeMaxTotalInput.value = snooze?.maxTotalMinutes ?? "";
+
+  const conditions = timer.conditions;
+  conditionPowerInput.value = conditions?.power ?? "";
+  conditionIdleInput.value = conditions?.minIdleMinutes ?? "";
+  conditionCpuInput.value = conditions?.maxCpuPercent ?? "";
+  conditionProcessesInput.value = (conditions?.blockingProcesses ?? []).join(", ");
+  conditionUnmetInput.value = conditions?.whenUnmet ?? "skip";
+  conditionDeferInput.value = conditions?.deferMinutes ?? "";
+
+  setEditing(timer.id);
+  toggleIdleTrigger();
+  toggleActionFields();
+  toggleRecurrence();
+  syncActionChoices();
+  syncRecurrenceChoices();
+  showStatus("Editing timer. Save to apply changes.");
+};
+
+form.addEventListener("submit", async (event) => {
+  event.preventDefault();
+
+  const idleMinutes = optionalNumber(idleMinutesInput);
+  if (idleMinutes === null && !targetTimeInput.value) {
+    showStatus("Choose a valid time.", true);
+    return;
+  }
+
+  const recurrencePreset = recurrencePresetInput.value;
+  const selectedSpecificDays = [...specificDayInputs]
+    .filter((input) => input.checked)
+    .map((input) => input.value);
+  const preWarningSeconds = [...getPreWarningInputs()]
+    .filter((input) => input.checked)
+    .map((input) => Number.parseInt(input.value, 10))
+    .filter((value) => Number.isInteger(value) && value > 0);
+
+  if (recurrencePreset === "specific_days" && !selectedSpecificDays.length) {
+    showStatus("Select at least one day for Specific Days.", true);
+    return;
+  }
+
+  if (recurrencePreset === "cron" && !cronExpressionInput.value.trim()) {
+    showStatus("Enter a cron expression.", true);
+    return;
+  }
+
+  if (recurrencePreset === "rrule" && !rruleInput.value.trim()) {
+    showStatus("Enter a recurrence rule.", true);
+    return;
+  }
+
+  if (actionInput.value === "run_command" && !commandProgramInput.value.trim()) {
+    showStatus("Enter a program to run.", true);
+    return;
+  }
+
+  if (actionInput.value === "open" && !openTargetInput.value.trim()) {
+    showStatus("Enter a link, file or app to open.", true);
+    return;
+  }
+
+  if (actionInput.value === "close_apps" && !lines(closeAppsInput.value).length) {
+    showStatus("Enter at least one app to close.", true);
+    return;
+  }
+
+  if (actionInput.value === "sequence" && !sequenceStepsEl.children.length) {
+    showStatus("Add at least one step.", true);
+    return;
+  }
+
+  const recurrence = buildRecurrence();
+
+  const request = {
+    action: actionInput.value,
+    targetTime: idleMinutes === null ? new Date(targetTimeInput.value).toISOString() : new Date().toISOString(),
+    recurrence,
+    preWarningSeconds: idleMinutes === null ? preWarningSeconds : [],
+    idleTrigger: idleMinutes === null ? null : { minutes: idleMinutes },
+    snooze: buildSnooze(),
+    conditions: buildConditions(),
+    lockPin: lockPinInput.value || null,
+    message: actionInput.value === "popup" ? messageInput.value : null,
+    command: buildCommand(),
+    openTarget: actionInput.value === "open" ? openTargetInput.value.trim() : null,
+    closeApps: buildCloseApps(),
+    steps: buildSteps(),
+  };
+
+  try {
+    if (editingTimerId) {
+      await invoke("update_timer", { id: editingTimerId, request, pin: unlockPin() });
+    } else {
+      await invoke("create_timer", { request });
+    }
+    const wasEditing = Boolean(editingTimerId);
+    resetForm();
+    showStatus(wasEditing ? "Timer updated." : "Timer created.");
+    await loadTimers();
+  } catch (err) {
+    showStatus(String(err), true);
+  }
+});
+
+refreshBtn.addEventListener("click", loadTimers);
+historyRefreshBtn.addEventListener("click", loadHistory);
+[historyActionSelect, historyFromInput, historyToInput, historyTimerInput].forEach((input) => {
+  input.addEventListener("change", loadHistory);
+});
+historyClearBtn.addEventListener("click", async () => {
+  try {
+    const removed = await invoke("clear_history", { filter: historyFilter() });
+    showStatus(`Cleared ${removed} history entr${removed === 1 ? "y" : "ies"}.`);
+    await loadHistory();
+  } catch (err) {
+    showStatus(String(err), true);
+  }
+});
+preActionSkipBtn.addEventListener("click", () => resolvePreAction("cancel_action"));
+preActionRunBtn.addEventListener("click", () => resolvePreAction("run_now"));
+cancelEditBtn.addEventListener("click", () => {
+  resetForm();
+  showStatus("Edit canceled.");
+});
+actionInput.addEventListener("change", toggleActionFields);
+addStepBtn.addEventListener("click", () => addSequenceStep());
+recurrencePresetInput.addEventListener("change", toggleRecurrence);
+idleMinutesInput.addEventListener("input", toggleIdleTrigger);
+actionInput.addEventListener("change", syncActionChoices);
+recurrencePresetInput.addEventListener("change", syncRecurrenceChoices);
+
+actionChoiceBoxes.forEach((box) => {
+  box.addEventListener("click", () => {
+    const value = box.dataset.action;
+    if (!value) {
+      return;
+    }
+    actionInput.value = value;
+    toggleActionFields();
+    syncActionChoices();
+  });
+});
+
+recurrenceChoiceBoxes.forEach((box) => {
+  box.addEventListener("click", () => {
+    const value = box.dataset.recurrence;
+    if (!value) {
+      return;
+    }
+    recurrencePresetInput.value = value;
+    toggleRecurrence();
+    syncRecurrenceChoices();
+  });
+});
+
+quickChipButtons.forEach((chip) => {
+  chip.addEventListener("click", () => {
+    const minutes = Number(chip.dataset.quickMinutes);
+    if (!Number.isInteger(minutes) || minutes <= 0) {
+      return;
+    }
+    applyQuickIncrement(minutes);
+  });
+});
+
+if (quickCustomApplyBtn) {
+  quickCustomApplyBtn.addEventListener("click", () => {
+    const minutes = Number.parseInt(String(quickCustomInput?.value || ""), 10);
+    if (!Number.isInteger(minutes) || minutes <= 0) {
+      showStatus("Custom increment must be a positive number of minutes.", true);
+      return;
+    }
+    applyQuickIncrement(minutes);
+  });
+}
+
+if (prewarnCustomApplyBtn) {
+  prewarnCustomApplyBtn.addEventListener("click", () => {
+    const seconds = parseOffset(customPrewarnInput?.value || "");
+    if (!seconds) {
+      showStatus("Pre warning must look like 30s, 2m or 1h.", true);
+      return;
+    }
+
+    addPreWarningChip(seconds);
+  });
+}
+
+cronPreviewBtn.addEventListener("click", () => previewRecurrence(cronPreviewEl));
+rrulePreviewBtn.addEventListener("click", () => previewRecurrence(rrulePreviewEl));
+
+if (setNowBtn) {
+  setNowBtn.addEventListener("click", () => {
+    setTriggerToNow();
+  });
+}
+
+checkUpdatesBtn.addEventListener("click", () => checkForUpdates(false));
+installLatestBtn.addEventListener("click", installChannelUpdate);
+
+rollbackInstallBtn.addEventListener("click", async () => {
+  const selectedTag = rollbackVersionSelect.value;
+  if (!selectedTag) {
+    showUpdateStatus("Pick a version to install.", true);
+    return;
+  }
+
+  await installTag(selectedTag);
+});
+
+autoCheckUpdatesInput.addEventListener("change", () => {
+  localStorage.setItem(AUTO_UPDATE_KEY, autoCheckUpdatesInput.checked ? "1" : "0");
+});
+
+updateChannelSelect.addEventListener("change", () => {
+  localStorage.setItem(UPDATE_CHANNEL_KEY, selectedChannel());
+  renderUpdateResult(null);
+});
+
+const initialize = async () => {
+  setTriggerToNow();
+  toggleActionFields();
+  toggleRecurrence();
+  syncActionChoices();
+  syncRecurrenceChoices();
+  await listen("pre_action_warning", (event) => showPreAction(event.payload));
+  await listen("action_outcome", async (event) => {
+    const { action, outcome } = event.payload;
+    const error = outcomeError(outcome);
+    if (error) {
+      showStatus(`${actionLabel(action)} failed: ${error}`, true);
+    }
+    await loadHistory();
+  });
+  await loadTimers();
+  setInterval(loadTimers, 1000);
+  await loadHistory();
+
+  currentVersion = await getVersion();
+  currentVersionEl.textContent = currentVersion;
+
+  const savedChannel = localStorage.getItem(UPDATE_CHANNEL_KEY);
+  updateChannelSelect.value = savedChannel === "dev" ? "dev" : "main";
+
+  const autoCheckSetting = localStorage.getItem(AUTO_UPDATE_KEY);
+  autoCheckUpdatesInput.checked = autoCheckSetting !== "0";
+
+  await loadRollbackVersions();
+
+  if (autoCheckUpdatesInput.checked) {
+    await checkForUpdates(true);
+  }
+};
+
+initialize().catch((err) => {
+  showStatus(`Initialization failed: ${String(err)}`, true);
+});
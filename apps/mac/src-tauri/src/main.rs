@@ -1,484 +1,195 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::{
-    collections::HashMap,
-    fs,
-    path::{Path, PathBuf},
-    process::Command,
-    sync::mpsc,
-    sync::{Arc, Mutex},
-    thread,
-    time::Duration,
+use std::{path::PathBuf, process::Command, sync::Arc, thread};
+
+use lockpilot_core::{
+    close::{self, AppCloser},
+    model::{
+        ActionAttempt, ActionOutcome, ActionOutcomePayload, CloseAppsSpec, CreateTimerRequest, HistoryEntry,
+        HistoryFilter, PowerSource, PreActionWarningPayload, PreviewRecurrenceRequest, RecurrencePreview,
+        ResolvePreActionRequest, TimerInfo,
+    },
+    open::OpenTargetKind,
+    prewarning, recurrence,
+    updater::{self, ReleaseVersion, UpdateChannel, UpdateInfo},
+    ActionBackend, HistoryStore, IdleSource, LockStore, PreActionPrompter, Scheduler, SystemProbe, TimerStore,
 };
-
-use chrono::{DateTime, Datelike, Duration as ChronoDuration, TimeZone, Utc, Weekday};
-use reqwest::blocking::Client;
-use semver::Version;
-use serde::{Deserialize, Serialize};
 use tauri::{Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
-use uuid::Uuid;
 
-const GITHUB_OWNER: &str = "maxacode";
-const GITHUB_REPO: &str = "LockPilot-Mac-Win";
+const INSTALLER_EXTENSIONS: &[&str] = &[".dmg"];
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-enum TimerAction {
-    Popup,
-    Lock,
-    Shutdown,
-    Reboot,
-}
+// ─── Tauri commands ───────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-enum UpdateChannel {
-    Main,
-    Dev,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-enum RecurrencePreset {
-    Daily,
-    Weekdays,
-    SpecificDays,
-    EveryNHours,
-    EveryNMinutes,
+#[tauri::command]
+fn list_timers(state: State<'_, Scheduler>) -> Result<Vec<TimerInfo>, String> {
+    state.list_timers()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct RecurrenceConfig {
-    preset: RecurrencePreset,
-    interval_hours: Option<u32>,
-    interval_minutes: Option<u32>,
-    days_of_week: Option<Vec<String>>,
+#[tauri::command]
+fn cancel_timer(id: String, pin: Option<String>, state: State<'_, Scheduler>) -> Result<bool, String> {
+    state.cancel_timer(&id, pin.as_deref())
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct TimerInfo {
+#[tauri::command]
+fn skip_next_occurrence(
     id: String,
-    action: TimerAction,
-    target_time: DateTime<Utc>,
-    recurrence: Option<RecurrenceConfig>,
-    pre_warning_minutes: Option<Vec<u32>>,
-    message: Option<String>,
-    created_at: DateTime<Utc>,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct CreateTimerRequest {
-    action: TimerAction,
-    target_time: String,
-    recurrence: Option<RecurrenceConfig>,
-    pre_warning_minutes: Option<Vec<u32>>,
-    message: Option<String>,
-}
-
-struct TimerEntry {
-    info: TimerInfo,
-    cancel_tx: mpsc::Sender<()>,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "snake_case")]
-enum PreActionDecision {
-    RunNow,
-    Snooze10,
-    CancelAction,
-    ContinueScheduled,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct ResolvePreActionRequest {
-    prompt_id: String,
-    decision: PreActionDecision,
-}
-
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct PreActionWarningPayload {
-    prompt_id: String,
-    timer_id: String,
-    action: TimerAction,
-    warning_minutes: u32,
-    countdown_seconds: u32,
-    snooze_minutes: u32,
+    pin: Option<String>,
+    state: State<'_, Scheduler>,
+) -> Result<bool, String> {
+    state.skip_next_occurrence(&id, pin.as_deref())
 }
 
-#[derive(Clone)]
-struct TimerStore {
-    inner: Arc<Mutex<HashMap<String, TimerEntry>>>,
-    storage_path: Arc<PathBuf>,
+#[tauri::command]
+fn pause_timer(id: String, pin: Option<String>, state: State<'_, Scheduler>) -> Result<bool, String> {
+    state.pause_timer(&id, pin.as_deref())
 }
 
-#[derive(Clone)]
-struct PreActionStore {
-    inner: Arc<Mutex<HashMap<String, mpsc::Sender<PreActionDecision>>>>,
+#[tauri::command]
+fn resume_timer(id: String, pin: Option<String>, state: State<'_, Scheduler>) -> Result<bool, String> {
+    state.resume_timer(&id, pin.as_deref())
 }
 
-impl PreActionStore {
-    fn new() -> Self {
-        Self {
-            inner: Arc::new(Mutex::new(HashMap::new())),
-        }
-    }
+#[tauri::command]
+fn resolve_pre_action(
+    request: ResolvePreActionRequest,
+    state: State<'_, Scheduler>,
+) -> Result<bool, String> {
+    state.resolve_pre_action(request)
 }
 
-impl TimerStore {
-    fn new(storage_path: PathBuf) -> Self {
-        Self {
-            inner: Arc::new(Mutex::new(HashMap::new())),
-            storage_path: Arc::new(storage_path),
-        }
-    }
-
-    fn persist(&self) -> Result<(), String> {
-        let locked = self
-            .inner
-            .lock()
-            .map_err(|_| "Failed to lock timer store".to_string())?;
-
-        let mut timers: Vec<TimerInfo> = locked.values().map(|entry| entry.info.clone()).collect();
-        timers.sort_by_key(|timer| timer.target_time);
-        drop(locked);
-
-        if let Some(parent) = self.storage_path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|err| format!("Failed to create timer storage directory: {err}"))?;
-        }
-
-        let data = serde_json::to_string_pretty(&PersistedTimers { timers })
-            .map_err(|err| format!("Failed to encode timer data: {err}"))?;
-        fs::write(self.storage_path.as_ref(), data)
-            .map_err(|err| format!("Failed to write timer data: {err}"))?;
-        Ok(())
-    }
-
-    fn load_persisted_infos(&self) -> Result<Vec<TimerInfo>, String> {
-        if !self.storage_path.exists() {
-            return Ok(Vec::new());
-        }
-
-        let raw = fs::read_to_string(self.storage_path.as_ref())
-            .map_err(|err| format!("Failed to read timer data: {err}"))?;
-        let persisted = serde_json::from_str::<PersistedTimers>(&raw)
-            .map_err(|err| format!("Failed to parse timer data: {err}"))?;
-        Ok(persisted.timers)
-    }
+#[tauri::command]
+fn create_timer(request: CreateTimerRequest, state: State<'_, Scheduler>) -> Result<TimerInfo, String> {
+    state.create_timer(request)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct PersistedTimers {
-    timers: Vec<TimerInfo>,
+#[tauri::command]
+fn update_timer(
+    id: String,
+    request: CreateTimerRequest,
+    pin: Option<String>,
+    state: State<'_, Scheduler>,
+) -> Result<TimerInfo, String> {
+    state.update_timer(&id, request, pin.as_deref())
 }
 
-#[derive(Debug, Deserialize, Clone)]
-struct GithubAsset {
-    name: String,
-    browser_download_url: String,
+#[tauri::command]
+fn list_history(filter: Option<HistoryFilter>, state: State<'_, Scheduler>) -> Result<Vec<HistoryEntry>, String> {
+    state.list_history(&filter.unwrap_or_default())
 }
 
-#[derive(Debug, Deserialize, Clone)]
-struct GithubRelease {
-    tag_name: String,
-    name: Option<String>,
-    body: Option<String>,
-    draft: bool,
-    prerelease: bool,
-    published_at: Option<String>,
-    assets: Vec<GithubAsset>,
+#[tauri::command]
+fn clear_history(filter: Option<HistoryFilter>, state: State<'_, Scheduler>) -> Result<usize, String> {
+    state.clear_history(&filter.unwrap_or_default())
 }
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct ReleaseVersion {
-    tag: String,
-    name: String,
-    published_at: Option<String>,
+#[tauri::command]
+fn preview_recurrence(request: PreviewRecurrenceRequest) -> Result<RecurrencePreview, String> {
+    recurrence::preview_recurrence(request)
 }
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct UpdateInfo {
-    tag: String,
-    name: String,
-    notes: Option<String>,
-    published_at: Option<String>,
-}
+// ─── Update / release commands ────────────────────────────────────
 
 #[tauri::command]
-fn list_timers(state: State<'_, TimerStore>) -> Result<Vec<TimerInfo>, String> {
-    let store = state
-        .inner
-        .lock()
-        .map_err(|_| "Failed to lock timer store".to_string())?;
-
-    let mut timers: Vec<TimerInfo> = store.values().map(|entry| entry.info.clone()).collect();
-    timers.sort_by_key(|timer| timer.target_time);
-
-    Ok(timers)
+fn list_release_versions() -> Result<Vec<ReleaseVersion>, String> {
+    updater::list_release_versions(INSTALLER_EXTENSIONS)
 }
 
 #[tauri::command]
-fn cancel_timer(id: String, state: State<'_, TimerStore>) -> Result<bool, String> {
-    let mut store = state
-        .inner
-        .lock()
-        .map_err(|_| "Failed to lock timer store".to_string())?;
-
-    if let Some(entry) = store.remove(&id) {
-        let _ = entry.cancel_tx.send(());
-        drop(store);
-        state.persist()?;
-        Ok(true)
-    } else {
-        Ok(false)
-    }
+fn check_channel_update(
+    current_version: String,
+    channel: UpdateChannel,
+) -> Result<Option<UpdateInfo>, String> {
+    updater::check_channel_update(&current_version, &channel, INSTALLER_EXTENSIONS)
 }
 
 #[tauri::command]
-fn resolve_pre_action(
-    request: ResolvePreActionRequest,
-    state: State<'_, PreActionStore>,
-) -> Result<bool, String> {
-    let sender = {
-        let mut pending = state
-            .inner
-            .lock()
-            .map_err(|_| "Failed to lock pre-action store".to_string())?;
-        pending.remove(&request.prompt_id)
-    };
+fn install_channel_update(channel: UpdateChannel) -> Result<String, String> {
+    let installer = updater::download_channel_installer(&channel, INSTALLER_EXTENSIONS)?;
+    open_installer(&installer.path)?;
 
-    if let Some(tx) = sender {
-        let _ = tx.send(request.decision);
-        Ok(true)
-    } else {
-        Ok(false)
-    }
+    Ok(format!(
+        "Opened {} channel installer {} from {}",
+        updater::channel_name(&channel),
+        installer.tag,
+        installer.path.display()
+    ))
 }
 
 #[tauri::command]
-fn create_timer(
-    app: tauri::AppHandle,
-    request: CreateTimerRequest,
-    state: State<'_, TimerStore>,
-    pre_action_state: State<'_, PreActionStore>,
-) -> Result<TimerInfo, String> {
-    let target = DateTime::parse_from_rfc3339(&request.target_time)
-        .map_err(|_| "Invalid date/time format".to_string())?
-        .with_timezone(&Utc);
-
-    let now = Utc::now();
-    if target <= now {
-        return Err("Selected time must be in the future".to_string());
-    }
-
-    validate_recurrence(request.recurrence.as_ref())?;
-    let pre_warning_minutes = normalize_pre_warning_minutes(request.pre_warning_minutes.as_ref())?;
-
-    let id = Uuid::new_v4().to_string();
-    let recurrence = request.recurrence.clone();
-    let info = TimerInfo {
-        id: id.clone(),
-        action: request.action,
-        target_time: target,
-        recurrence: recurrence.clone(),
-        pre_warning_minutes: pre_warning_minutes.clone(),
-        message: request.message.map(|msg| msg.trim().to_string()),
-        created_at: now,
-    };
-
-    let (cancel_tx, cancel_rx) = mpsc::channel();
-
-    {
-        let mut store = state
-            .inner
-            .lock()
-            .map_err(|_| "Failed to lock timer store".to_string())?;
-
-        store.insert(
-            id.clone(),
-            TimerEntry {
-                info: info.clone(),
-                cancel_tx,
-            },
-        );
-    }
-
-    state.persist()?;
-    schedule_timer_thread(
-        app.clone(),
-        pre_action_state.inner.clone(),
-        state.inner.clone(),
-        state.storage_path.as_ref(),
-        id.clone(),
-        target,
-        info.clone(),
-        recurrence,
-        cancel_rx,
-    );
+fn install_release(tag: String) -> Result<String, String> {
+    let installer = updater::download_release_installer(&tag, INSTALLER_EXTENSIONS)?;
+    open_installer(&installer.path)?;
 
-    Ok(info)
+    Ok(format!(
+        "Opened installer for {} from {}",
+        installer.tag,
+        installer.path.display()
+    ))
 }
 
-fn schedule_timer_thread(
-    app: tauri::AppHandle,
-    pre_action_store: Arc<Mutex<HashMap<String, mpsc::Sender<PreActionDecision>>>>,
-    store: Arc<Mutex<HashMap<String, TimerEntry>>>,
-    storage_path: &Path,
-    id: String,
-    initial_target: DateTime<Utc>,
-    task_info: TimerInfo,
-    recurrence: Option<RecurrenceConfig>,
-    cancel_rx: mpsc::Receiver<()>,
-) {
-    let storage_path = storage_path.to_path_buf();
-    thread::spawn(move || {
-        let mut next_run = initial_target;
-        let warning_minutes = normalize_pre_warning_minutes(task_info.pre_warning_minutes.as_ref())
-            .ok()
-            .flatten()
-            .unwrap_or_default();
-        'timer_loop: loop {
-            let mut should_execute_action = true;
-            if should_show_pre_action_warning(&task_info.action) && !warning_minutes.is_empty() {
-                if let Some(minutes) = warning_minutes.iter().max().copied() {
-                    let warning_time = next_run - ChronoDuration::minutes(minutes as i64);
-                    let now = Utc::now();
-                    if warning_time > now {
-                        let wait = match (warning_time - now).to_std() {
-                            Ok(duration) => duration,
-                            Err(_) => Duration::from_secs(0),
-                        };
-                        if cancel_rx.recv_timeout(wait).is_ok() {
-                            close_pre_action_window(&app, &id);
-                            return;
-                        }
-                    }
-
-                    let decision = request_pre_action_decision(
-                        &app,
-                        &pre_action_store,
-                        &id,
-                        &task_info.action,
-                        minutes,
-                    );
-                    match decision {
-                        PreActionDecision::RunNow => {
-                            close_pre_action_window(&app, &id);
-                            run_action(&task_info.action, task_info.message.as_deref());
-                            should_execute_action = false;
-                        }
-                        PreActionDecision::Snooze10 => {
-                            close_pre_action_window(&app, &id);
-                            next_run = Utc::now() + ChronoDuration::minutes(10);
-                            if let Ok(mut locked) = store.lock() {
-                                if let Some(entry) = locked.get_mut(&id) {
-                                    entry.info.target_time = next_run;
-                                } else {
-                                    return;
-                                }
-                            }
-                            let _ = persist_inner_store(&store, &storage_path);
-                            continue 'timer_loop;
-                        }
-                        PreActionDecision::CancelAction => {
-                            close_pre_action_window(&app, &id);
-                            should_execute_action = false;
-                        }
-                        PreActionDecision::ContinueScheduled => {
-                            close_pre_action_window(&app, &id);
-                        }
-                    }
-                }
-            }
+fn open_installer(path: &std::path::Path) -> Result<(), String> {
+    Command::new("/usr/bin/open")
+        .arg(path)
+        .spawn()
+        .map_err(|err| format!("Failed to open installer DMG: {err}"))?;
+    Ok(())
+}
 
-            if should_execute_action {
-                let wait = match (next_run - Utc::now()).to_std() {
-                    Ok(duration) => duration,
-                    Err(_) => Duration::from_secs(0),
-                };
-                if cancel_rx.recv_timeout(wait).is_ok() {
-                    close_pre_action_window(&app, &id);
-                    break;
-                }
-                close_pre_action_window(&app, &id);
-                run_action(&task_info.action, task_info.message.as_deref());
-            }
+// ─── Pre-warning window ───────────────────────────────────────────
 
-            let Some(recurrence_cfg) = recurrence.as_ref() else {
-                if let Ok(mut locked) = store.lock() {
-                    locked.remove(&id);
-                }
-                let _ = persist_inner_store(&store, &storage_path);
-                break;
-            };
+struct TauriPrompter {
+    app: tauri::AppHandle,
+}
 
-            let computed_next = compute_next_run(next_run, recurrence_cfg);
-            let Some(updated_next) = computed_next else {
-                if let Ok(mut locked) = store.lock() {
-                    locked.remove(&id);
-                }
-                let _ = persist_inner_store(&store, &storage_path);
-                break;
-            };
-            next_run = updated_next;
+impl PreActionPrompter for TauriPrompter {
+    fn open(&self, payload: &PreActionWarningPayload) -> Result<(), String> {
+        open_pre_action_window(&self.app, payload);
+        self.app
+            .emit("pre_action_warning", payload.clone())
+            .map_err(|err| format!("Failed to emit pre-action warning: {err}"))
+    }
 
-            if let Ok(mut locked) = store.lock() {
-                if let Some(entry) = locked.get_mut(&id) {
-                    entry.info.target_time = next_run;
-                } else {
-                    break;
-                }
-            }
-            let _ = persist_inner_store(&store, &storage_path);
+    fn close(&self, timer_id: &str) {
+        let label = pre_action_window_label(timer_id);
+        if let Some(window) = self.app.get_webview_window(&label) {
+            let _ = window.close();
         }
-    });
-}
+    }
 
-fn should_show_pre_action_warning(action: &TimerAction) -> bool {
-    matches!(
-        action,
-        TimerAction::Lock | TimerAction::Shutdown | TimerAction::Reboot | TimerAction::Popup
-    )
+    fn action_finished(&self, payload: &ActionOutcomePayload) {
+        let _ = self.app.emit("action_outcome", payload.clone());
+    }
 }
 
 fn pre_action_window_label(timer_id: &str) -> String {
     format!("prewarning-{timer_id}")
 }
 
-fn open_pre_action_window(
-    app: &tauri::AppHandle,
-    timer_id: &str,
-    action: &TimerAction,
-    warning_minutes: u32,
-    countdown_seconds: u32,
-) {
-    let label = pre_action_window_label(timer_id);
+fn open_pre_action_window(app: &tauri::AppHandle, payload: &PreActionWarningPayload) {
+    let label = pre_action_window_label(&payload.timer_id);
+    let action_key = payload.action.key();
+    let (warning_seconds, countdown_seconds) = (payload.warning_seconds, payload.countdown_seconds);
+    let apps = prewarning::encode_app_list(&payload.apps);
     if let Some(existing) = app.get_webview_window(&label) {
+        // A later warning for the same run: refresh the open window.
+        let _ = existing.eval(format!(
+            "window.applyWarning(\"{action_key}\", {warning_seconds}, {countdown_seconds}, \
+             decodeURIComponent(\"{apps}\"))"
+        ));
         let _ = existing.set_focus();
         let _ = existing.show();
         return;
     }
 
-    let action_key = match action {
-        TimerAction::Lock => "lock",
-        TimerAction::Shutdown => "shutdown",
-        TimerAction::Reboot => "reboot",
-        TimerAction::Popup => "popup",
-    };
     let url = format!(
-        "prewarning.html?action={action_key}&warning={warning_minutes}&seconds={countdown_seconds}"
+        "prewarning.html?action={action_key}&warning={warning_seconds}&seconds={countdown_seconds}&apps={apps}"
     );
+    // Room for the list of apps a CloseApps run will close.
+    let height = 250.0 + 22.0 * payload.apps.len().min(6) as f64;
 
     let _ = WebviewWindowBuilder::new(app, &label, WebviewUrl::App(url.into()))
         .title("LockPilot - Pre Warning")
-        .inner_size(420.0, 250.0)
+        .inner_size(420.0, height)
         .resizable(false)
         .maximizable(false)
         .minimizable(false)
@@ -488,582 +199,312 @@ fn open_pre_action_window(
         .build();
 }
 
-fn close_pre_action_window(app: &tauri::AppHandle, timer_id: &str) {
-    let label = pre_action_window_label(timer_id);
-    if let Some(window) = app.get_webview_window(&label) {
-        let _ = window.close();
-    }
-}
+// ─── macOS system state ───────────────────────────────────────────
 
-fn request_pre_action_decision(
-    app: &tauri::AppHandle,
-    pre_action_store: &Arc<Mutex<HashMap<String, mpsc::Sender<PreActionDecision>>>>,
-    timer_id: &str,
-    action: &TimerAction,
-    warning_minutes: u32,
-) -> PreActionDecision {
-    let prompt_id = Uuid::new_v4().to_string();
-    let (tx, rx) = mpsc::channel::<PreActionDecision>();
+/// Reads the machine state timers' conditions check from `pmset`, `ioreg`
+/// and `ps`.
+struct MacSystemProbe;
 
-    if let Ok(mut pending) = pre_action_store.lock() {
-        pending.insert(prompt_id.clone(), tx);
-    } else {
-        return PreActionDecision::ContinueScheduled;
+impl SystemProbe for MacSystemProbe {
+    // The first line of `pmset -g batt` names the source, such as
+    // `Now drawing from 'Battery Power'`.
+    fn power_source(&self) -> Result<PowerSource, String> {
+        let batt = command_stdout("/usr/bin/pmset", &["-g", "batt"])?;
+        Ok(if batt.contains("'Battery Power'") {
+            PowerSource::Battery
+        } else {
+            PowerSource::Ac
+        })
     }
 
-    let countdown_seconds = warning_minutes.saturating_mul(60).max(1);
-    open_pre_action_window(app, timer_id, action, warning_minutes, countdown_seconds);
-
-    let payload = PreActionWarningPayload {
-        prompt_id: prompt_id.clone(),
-        timer_id: timer_id.to_string(),
-        action: action.clone(),
-        warning_minutes,
-        countdown_seconds,
-        snooze_minutes: 10,
-    };
-
-    if app.emit("pre_action_warning", payload).is_err() {
-        if let Ok(mut pending) = pre_action_store.lock() {
-            pending.remove(&prompt_id);
-        }
-        return PreActionDecision::ContinueScheduled;
+    fn is_running(&self, name: &str) -> Result<bool, String> {
+        MacAppCloser.running(name).map(|pids| !pids.is_empty())
     }
 
-    match rx.recv_timeout(Duration::from_secs(countdown_seconds as u64)) {
-        Ok(decision) => decision,
-        Err(_) => {
-            if let Ok(mut pending) = pre_action_store.lock() {
-                pending.remove(&prompt_id);
-            }
-            PreActionDecision::ContinueScheduled
-        }
+    // `ps` reports each process's share of one core, so the sum is spread
+    // over every core.
+    fn cpu_percent(&self) -> Result<f32, String> {
+        let ps = command_stdout("/bin/ps", &["-A", "-o", "%cpu="])?;
+        let busy: f32 = ps.lines().filter_map(|line| line.trim().parse::<f32>().ok()).sum();
+        let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+        Ok((busy / cores as f32).min(100.0))
     }
 }
 
-fn normalize_pre_warning_minutes(values: Option<&Vec<u32>>) -> Result<Option<Vec<u32>>, String> {
-    let Some(values) = values else {
-        return Ok(None);
-    };
-
-    let mut normalized: Vec<u32> = values
-        .iter()
-        .copied()
-        .filter(|value| matches!(value, 1 | 5 | 10))
-        .collect();
-
-    normalized.sort_unstable();
-    normalized.dedup();
-
-    if normalized.len() != values.len() {
-        return Err("Pre-warning options must be any of: 1, 5, 10 minutes.".to_string());
+impl IdleSource for MacSystemProbe {
+    // IOHIDSystem's `HIDIdleTime` is in nanoseconds.
+    fn idle_seconds(&self) -> Result<u64, String> {
+        let ioreg = command_stdout("/usr/sbin/ioreg", &["-c", "IOHIDSystem", "-d", "4"])?;
+        ioreg
+            .lines()
+            .find_map(|line| line.split_once("\"HIDIdleTime\" = ").map(|(_, nanos)| nanos.trim().to_string()))
+            .and_then(|nanos| nanos.parse::<u64>().ok())
+            .map(|nanos| nanos / 1_000_000_000)
+            .ok_or_else(|| "ioreg reports no HIDIdleTime".to_string())
     }
-
-    Ok(Some(normalized))
 }
 
-#[tauri::command]
-fn list_release_versions() -> Result<Vec<ReleaseVersion>, String> {
-    let mut releases = rollback_releases(fetch_releases()?);
-    releases.sort_by(release_version_desc);
-
-    Ok(releases
-        .into_iter()
-        .map(|release| ReleaseVersion {
-            tag: release.tag_name.clone(),
-            name: release.name.unwrap_or_else(|| release.tag_name.clone()),
-            published_at: release.published_at,
-        })
-        .collect())
+fn command_stdout(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| format!("Failed to run {program}: {err}"))?;
+    if !output.status.success() {
+        return Err(format!("{program} exited with status {}", output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
 }
 
-#[tauri::command]
-fn check_channel_update(
-    current_version: String,
-    channel: UpdateChannel,
-) -> Result<Option<UpdateInfo>, String> {
-    let current = normalize_version(&current_version)
-        .ok_or_else(|| format!("Invalid current version: {current_version}"))?;
+// ─── macOS system actions ─────────────────────────────────────────
 
-    let mut releases = releases_for_channel(fetch_releases()?, &channel);
-    releases.sort_by(release_version_desc);
-
-    let update = match channel {
-        UpdateChannel::Dev | UpdateChannel::Main => releases.into_iter().find(|release| {
-            normalize_version(&release.tag_name)
-                .map(|version| version > current)
-                .unwrap_or(false)
-        }),
-    };
+struct MacActionBackend;
 
-    Ok(update.map(|release| UpdateInfo {
-        tag: release.tag_name.clone(),
-        name: release.name.unwrap_or_else(|| release.tag_name.clone()),
-        notes: release.body,
-        published_at: release.published_at,
-    }))
-}
+impl ActionBackend for MacActionBackend {
+    // Modern macOS fallback chain for locking: the Ctrl+Cmd+Q lock
+    // shortcut, then the screen saver, then display sleep.
+    fn lock(&self) -> ActionOutcome {
+        ActionOutcome::first_success(&[
+            &|| {
+                run_osascript(
+                    "osascript:lock-shortcut",
+                    "tell application \"System Events\" to keystroke \"q\" using {control down, command down}",
+                )
+            },
+            &|| {
+                run_osascript(
+                    "osascript:screen-saver",
+                    "tell application \"System Events\" to start current screen saver",
+                )
+            },
+            &|| {
+                ActionAttempt::from_output(
+                    "pmset:displaysleepnow",
+                    Command::new("/usr/bin/pmset").arg("displaysleepnow").output(),
+                )
+            },
+        ])
+    }
 
-#[tauri::command]
-fn install_channel_update(channel: UpdateChannel) -> Result<String, String> {
-    let mut releases = releases_for_channel(fetch_releases()?, &channel);
-    releases.sort_by(release_version_desc);
-    let release = releases
-        .into_iter()
-        .next()
-        .ok_or_else(|| format!("No releases found for {} channel", channel_name(&channel)))?;
+    fn shutdown(&self) -> ActionOutcome {
+        ActionOutcome::from_attempts(vec![run_osascript(
+            "osascript:shut-down",
+            "tell application \"System Events\" to shut down",
+        )])
+    }
 
-    let dmg_asset = pick_dmg_asset(&release.assets)
-        .ok_or_else(|| format!("No DMG asset found for release {}", release.tag_name))?;
+    fn reboot(&self) -> ActionOutcome {
+        ActionOutcome::from_attempts(vec![run_osascript(
+            "osascript:restart",
+            "tell application \"System Events\" to restart",
+        )])
+    }
 
-    let local_dmg = download_asset_to_temp(&dmg_asset.browser_download_url, &release.tag_name)?;
-    Command::new("/usr/bin/open")
-        .arg(&local_dmg)
-        .spawn()
-        .map_err(|err| format!("Failed to open installer DMG: {err}"))?;
+    fn sleep(&self) -> ActionOutcome {
+        ActionOutcome::from_attempts(vec![ActionAttempt::from_output(
+            "pmset:sleepnow",
+            Command::new("/usr/bin/pmset").arg("sleepnow").output(),
+        )])
+    }
 
-    Ok(format!(
-        "Opened {} channel installer {} from {}",
-        channel_name(&channel),
-        release.tag_name,
-        local_dmg.display()
-    ))
-}
+    // macOS has no separate hibernate command: sleeping hibernates only
+    // when `hibernatemode` is 25, so check that before sleeping.
+    fn hibernate(&self) -> ActionOutcome {
+        let mut attempts = vec![check_hibernate_mode()];
+        if attempts[0].success {
+            attempts.push(ActionAttempt::from_output(
+                "pmset:sleepnow",
+                Command::new("/usr/bin/pmset").arg("sleepnow").output(),
+            ));
+        }
+        ActionOutcome::from_attempts(attempts)
+    }
 
-#[tauri::command]
-fn install_release(tag: String) -> Result<String, String> {
-    let releases = rollback_releases(fetch_releases()?);
-    let release = releases
-        .into_iter()
-        .find(|release| tags_match(&release.tag_name, &tag))
-        .ok_or_else(|| format!("Release not found for tag: {tag}"))?;
+    // Apple event sent to loginwindow: logs out without the confirmation
+    // dialog of System Events' `log out`, which the pre-warning replaces.
+    fn logout(&self) -> ActionOutcome {
+        ActionOutcome::from_attempts(vec![run_osascript(
+            "osascript:log-out",
+            "tell application \"loginwindow\" to «event aevtrlgo»",
+        )])
+    }
 
-    let dmg_asset = pick_dmg_asset(&release.assets)
-        .ok_or_else(|| format!("No DMG asset found for release {}", release.tag_name))?;
+    fn popup(&self, message: &str) -> ActionOutcome {
+        let escaped = message.replace('"', "\\\"");
+        let script = format!(
+            "display dialog \"{}\" with title \"LockPilot\" buttons {{\"OK\"}} default button \"OK\"",
+            escaped
+        );
+        ActionOutcome::from_attempts(vec![run_osascript("osascript:dialog", &script)])
+    }
 
-    let local_dmg = download_asset_to_temp(&dmg_asset.browser_download_url, &release.tag_name)?;
-    Command::new("/usr/bin/open")
-        .arg(&local_dmg)
-        .spawn()
-        .map_err(|err| format!("Failed to open installer DMG: {err}"))?;
+    // Links and files go to their default app; apps launch by bundle id
+    // (`com.tinyspeck.slackmacgap`) or by name (`Slack`).
+    fn open(&self, target: &str, kind: OpenTargetKind) -> ActionOutcome {
+        let (method, args) = match kind {
+            OpenTargetKind::App if is_bundle_id(target) => ("open -b", vec!["-b", target]),
+            OpenTargetKind::App => ("open -a", vec!["-a", target]),
+            OpenTargetKind::Url | OpenTargetKind::File => ("open", vec![target]),
+        };
+        ActionOutcome::from_attempts(vec![ActionAttempt::from_output(
+            method,
+            Command::new("/usr/bin/open").args(args).output(),
+        )])
+    }
 
-    Ok(format!(
-        "Opened installer for {} from {}",
-        release.tag_name,
-        local_dmg.display()
-    ))
+    fn close_apps(&self, spec: &CloseAppsSpec) -> ActionOutcome {
+        close::close_apps(&MacAppCloser, spec)
+    }
 }
 
-fn run_action(action: &TimerAction, message: Option<&str>) {
-    match action {
-        TimerAction::Popup => {
-            let text = message
-                .map(str::trim)
-                .filter(|msg| !msg.is_empty())
-                .unwrap_or("LockPilot timer reached.");
-            let escaped = text.replace('"', "\\\"");
-            let script = format!(
-                "display dialog \"{}\" with title \"LockPilot\" buttons {{\"OK\"}} default button \"OK\"",
-                escaped
-            );
-            let _ = run_osascript(&script);
-        }
-        TimerAction::Lock => {
-            // Modern macOS fallback chain for locking:
-            // 1) trigger Ctrl+Cmd+Q lock shortcut
-            // 2) start screen saver
-            // 3) force display sleep
-            let locked = run_osascript(
-                "tell application \"System Events\" to keystroke \"q\" using {control down, command down}",
-            )
-            .is_ok()
-                || run_osascript("tell application \"System Events\" to start current screen saver")
-                    .is_ok();
+/// Closes apps by bundle id or process name. Quitting goes through Apple
+/// events so apps can ask to save; SIGKILL ends the ones that don't quit.
+struct MacAppCloser;
 
-            if !locked {
-                let _ = Command::new("/usr/bin/pmset").arg("displaysleepnow").spawn();
-            }
-        }
-        TimerAction::Shutdown => {
-            let _ = run_osascript("tell application \"System Events\" to shut down");
+impl AppCloser for MacAppCloser {
+    fn running(&self, app: &str) -> Result<Vec<u32>, String> {
+        if !is_bundle_id(app) {
+            return pgrep(app);
         }
-        TimerAction::Reboot => {
-            let _ = run_osascript("tell application \"System Events\" to restart");
+        let script = format!(
+            "tell application \"System Events\" to get unix id of every process \
+             whose bundle identifier is \"{app}\""
+        );
+        let output = Command::new("/usr/bin/osascript")
+            .arg("-e")
+            .arg(script)
+            .output()
+            .map_err(|err| format!("Failed to run osascript: {err}"))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
         }
+        // A comma-separated list such as `412, 977`, or nothing.
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split(',')
+            .filter_map(|pid| pid.trim().parse().ok())
+            .collect())
     }
-}
-
-fn validate_recurrence(recurrence: Option<&RecurrenceConfig>) -> Result<(), String> {
-    let Some(recurrence) = recurrence else {
-        return Ok(());
-    };
 
-    match recurrence.preset {
-        RecurrencePreset::Daily | RecurrencePreset::Weekdays => Ok(()),
-        RecurrencePreset::SpecificDays => {
-            let Some(days) = recurrence.days_of_week.as_ref() else {
-                return Err("Specific Days requires at least one day.".to_string());
-            };
-            if days.is_empty() {
-                return Err("Specific Days requires at least one day.".to_string());
-            }
-            if days.len() > 7 {
-                return Err("Specific Days can include at most 7 days.".to_string());
-            }
-            if days.iter().any(|day| parse_weekday(day).is_none()) {
-                return Err("Specific Days contains an invalid weekday.".to_string());
-            }
+    fn quit(&self, app: &str, _pids: &[u32]) -> Result<(), String> {
+        let script = if is_bundle_id(app) {
+            format!("tell application id \"{app}\" to quit")
+        } else {
+            format!("tell application \"{app}\" to quit")
+        };
+        let attempt = run_osascript("osascript:quit", &script);
+        if attempt.success {
             Ok(())
-        }
-        RecurrencePreset::EveryNHours => {
-            let Some(hours) = recurrence.interval_hours else {
-                return Err("Every N Hours requires an interval.".to_string());
-            };
-            if (1..=24).contains(&hours) {
-                Ok(())
-            } else {
-                Err("Interval hours must be between 1 and 24.".to_string())
-            }
-        }
-        RecurrencePreset::EveryNMinutes => {
-            let Some(minutes) = recurrence.interval_minutes else {
-                return Err("Every N Minutes requires an interval.".to_string());
-            };
-            if (1..=1440).contains(&minutes) {
-                Ok(())
-            } else {
-                Err("Interval minutes must be between 1 and 1440.".to_string())
-            }
+        } else {
+            Err(attempt.stderr.unwrap_or_else(|| "osascript failed".to_string()))
         }
     }
-}
 
-fn compute_next_run(current_target: DateTime<Utc>, recurrence: &RecurrenceConfig) -> Option<DateTime<Utc>> {
-    match recurrence.preset {
-        RecurrencePreset::Daily => {
-            let mut next = current_target + ChronoDuration::days(1);
-            while next <= Utc::now() {
-                next += ChronoDuration::days(1);
-            }
-            Some(next)
-        }
-        RecurrencePreset::EveryNHours => {
-            let interval = recurrence.interval_hours?;
-            let mut next = current_target + ChronoDuration::hours(interval as i64);
-            while next <= Utc::now() {
-                next += ChronoDuration::hours(interval as i64);
-            }
-            Some(next)
-        }
-        RecurrencePreset::EveryNMinutes => {
-            let interval = recurrence.interval_minutes?;
-            let mut next = current_target + ChronoDuration::minutes(interval as i64);
-            while next <= Utc::now() {
-                next += ChronoDuration::minutes(interval as i64);
-            }
-            Some(next)
-        }
-        RecurrencePreset::Weekdays => {
-            let time = current_target.time();
-            let mut date = current_target.date_naive() + ChronoDuration::days(1);
+    fn kill(&self, pids: &[u32]) -> Result<(), String> {
+        send_signal("-KILL", pids)
+    }
+}
 
-            for _ in 0..14 {
-                let weekday = date.weekday();
-                if weekday != Weekday::Sat && weekday != Weekday::Sun {
-                    let candidate = Utc.from_utc_datetime(&date.and_time(time));
-                    if candidate > Utc::now() {
-                        return Some(candidate);
-                    }
-                }
-                date += ChronoDuration::days(1);
-            }
-            None
-        }
-        RecurrencePreset::SpecificDays => {
-            let allowed_days = recurrence
-                .days_of_week
-                .as_ref()?
-                .iter()
-                .filter_map(|day| parse_weekday(day))
-                .collect::<Vec<_>>();
-            if allowed_days.is_empty() {
-                return None;
-            }
+/// Whether `target` names an app by bundle id (`com.tinyspeck.slackmacgap`)
+/// rather than by name (`Slack`).
+fn is_bundle_id(target: &str) -> bool {
+    target.contains('.') && !target.contains(' ') && !target.ends_with(".app")
+}
 
-            let time = current_target.time();
-            let mut date = current_target.date_naive() + ChronoDuration::days(1);
-            for _ in 0..14 {
-                if allowed_days.contains(&date.weekday()) {
-                    let candidate = Utc.from_utc_datetime(&date.and_time(time));
-                    if candidate > Utc::now() {
-                        return Some(candidate);
-                    }
-                }
-                date += ChronoDuration::days(1);
-            }
-            None
-        }
-    }
+fn run_osascript(method: &str, script: &str) -> ActionAttempt {
+    ActionAttempt::from_output(
+        method,
+        Command::new("/usr/bin/osascript").arg("-e").arg(script).output(),
+    )
 }
 
-fn parse_weekday(input: &str) -> Option<Weekday> {
-    match input.trim().to_ascii_lowercase().as_str() {
-        "mon" | "monday" => Some(Weekday::Mon),
-        "tue" | "tues" | "tuesday" => Some(Weekday::Tue),
-        "wed" | "wednesday" => Some(Weekday::Wed),
-        "thu" | "thur" | "thurs" | "thursday" => Some(Weekday::Thu),
-        "fri" | "friday" => Some(Weekday::Fri),
-        "sat" | "saturday" => Some(Weekday::Sat),
-        "sun" | "sunday" => Some(Weekday::Sun),
-        _ => None,
+/// Ids of processes named exactly `name`.
+fn pgrep(name: &str) -> Result<Vec<u32>, String> {
+    let output = Command::new("/usr/bin/pgrep")
+        .args(["-x", name])
+        .output()
+        .map_err(|err| format!("Failed to run pgrep: {err}"))?;
+    // pgrep exits with 1 when nothing matches.
+    if !output.status.success() && output.status.code() != Some(1) {
+        return Err(format!("pgrep exited with status {}", output.status));
     }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect())
 }
 
-fn run_osascript(script: &str) -> Result<(), String> {
-    let output = Command::new("/usr/bin/osascript")
-        .arg("-e")
-        .arg(script)
+fn send_signal(signal: &str, pids: &[u32]) -> Result<(), String> {
+    let output = Command::new("/bin/kill")
+        .arg(signal)
+        .args(pids.iter().map(u32::to_string))
         .output()
-        .map_err(|err| format!("Failed to run osascript: {err}"))?;
-
+        .map_err(|err| format!("Failed to run kill: {err}"))?;
     if output.status.success() {
         Ok(())
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
     }
 }
 
-fn persist_inner_store(store: &Arc<Mutex<HashMap<String, TimerEntry>>>, storage_path: &Path) -> Result<(), String> {
-    let locked = store
-        .lock()
-        .map_err(|_| "Failed to lock timer store".to_string())?;
-    let mut timers: Vec<TimerInfo> = locked.values().map(|entry| entry.info.clone()).collect();
-    timers.sort_by_key(|timer| timer.target_time);
-    drop(locked);
-
-    if let Some(parent) = storage_path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|err| format!("Failed to create timer storage directory: {err}"))?;
-    }
-
-    let data = serde_json::to_string_pretty(&PersistedTimers { timers })
-        .map_err(|err| format!("Failed to encode timer data: {err}"))?;
-    fs::write(storage_path, data).map_err(|err| format!("Failed to write timer data: {err}"))?;
-    Ok(())
-}
-
-fn restore_timers(
-    store: &TimerStore,
-    app: &tauri::AppHandle,
-    pre_action_store: &PreActionStore,
-) -> Result<(), String> {
-    let restored = store.load_persisted_infos()?;
-    if restored.is_empty() {
-        return Ok(());
-    }
-
-    let now = Utc::now();
-    for mut info in restored {
-        if info.target_time <= now {
-            if let Some(recurrence) = info.recurrence.as_ref() {
-                let mut next = info.target_time;
-                while next <= now {
-                    let Some(updated) = compute_next_run(next, recurrence) else {
-                        next = now;
-                        break;
-                    };
-                    next = updated;
-                }
-                if next <= now {
-                    continue;
-                }
-                info.target_time = next;
-            } else {
-                continue;
-            }
-        }
-
-        let (cancel_tx, cancel_rx) = mpsc::channel();
-        {
-            let mut locked = store
-                .inner
-                .lock()
-                .map_err(|_| "Failed to lock timer store".to_string())?;
-            locked.insert(
-                info.id.clone(),
-                TimerEntry {
-                    info: info.clone(),
-                    cancel_tx,
-                },
-            );
-        }
-
-        schedule_timer_thread(
-            app.clone(),
-            pre_action_store.inner.clone(),
-            store.inner.clone(),
-            store.storage_path.as_ref(),
-            info.id.clone(),
-            info.target_time,
-            info.clone(),
-            info.recurrence.clone(),
-            cancel_rx,
-        );
-    }
-
-    store.persist()?;
-    Ok(())
+fn check_hibernate_mode() -> ActionAttempt {
+    let method = "pmset:hibernatemode";
+    let output = match Command::new("/usr/bin/pmset").arg("-g").output() {
+        Ok(output) if output.status.success() => output,
+        result => return ActionAttempt::from_output(method, result),
+    };
+    let mode = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("hibernatemode").map(|mode| mode.trim().to_string()));
+    let result = match mode.as_deref() {
+        Some("25") => Ok(()),
+        Some(mode) => Err(format!(
+            "Hibernate is not available: hibernatemode is {mode}; enable it with `sudo pmset -a hibernatemode 25`"
+        )),
+        None => Err("Hibernate is not available: pmset reports no hibernatemode".to_string()),
+    };
+    ActionAttempt::from_result(method, result)
 }
 
-fn timer_storage_path(app: &tauri::AppHandle) -> PathBuf {
+fn data_file_path(app: &tauri::AppHandle, file_name: &str) -> PathBuf {
     let base = app
         .path()
         .app_data_dir()
         .unwrap_or_else(|_| std::env::temp_dir().join("lockpilot"));
-    base.join("timers.json")
+    base.join(file_name)
 }
 
-fn fetch_releases() -> Result<Vec<GithubRelease>, String> {
-    let client = Client::builder()
-        .user_agent("LockPilot-Updater")
-        .build()
-        .map_err(|err| format!("Failed to build HTTP client: {err}"))?;
-
-    let url = format!(
-        "https://api.github.com/repos/{}/{}/releases?per_page=100",
-        GITHUB_OWNER, GITHUB_REPO
-    );
-
-    let response = client
-        .get(url)
-        .send()
-        .map_err(|err| format!("Failed to fetch GitHub releases: {err}"))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "GitHub releases API returned status {}",
-            response.status()
-        ));
-    }
-
-    response
-        .json::<Vec<GithubRelease>>()
-        .map_err(|err| format!("Failed to parse GitHub releases: {err}"))
-}
-
-fn normalize_version(version: &str) -> Option<Version> {
-    Version::parse(version.trim().trim_start_matches('v')).ok()
-}
-
-fn release_version_desc(a: &GithubRelease, b: &GithubRelease) -> std::cmp::Ordering {
-    let av = normalize_version(&a.tag_name);
-    let bv = normalize_version(&b.tag_name);
-    bv.cmp(&av)
-}
-
-fn rollback_releases(releases: Vec<GithubRelease>) -> Vec<GithubRelease> {
-    releases
-        .into_iter()
-        .filter(|release| !release.draft)
-        .filter(|release| normalize_version(&release.tag_name).is_some())
-        .filter(|release| has_supported_asset(release))
-        .collect()
-}
-
-fn releases_for_channel(releases: Vec<GithubRelease>, channel: &UpdateChannel) -> Vec<GithubRelease> {
-    let base = releases
-        .into_iter()
-        .filter(|release| !release.draft)
-        .filter(|release| normalize_version(&release.tag_name).is_some())
-        .filter(|release| has_supported_asset(release));
-
-    match channel {
-        UpdateChannel::Main => base.filter(|release| !release.prerelease).collect(),
-        UpdateChannel::Dev => base.filter(|release| release.prerelease).collect(),
-    }
-}
-
-fn channel_name(channel: &UpdateChannel) -> &'static str {
-    match channel {
-        UpdateChannel::Main => "main",
-        UpdateChannel::Dev => "dev",
-    }
-}
-
-fn tags_match(a: &str, b: &str) -> bool {
-    a.trim() == b.trim() || a.trim_start_matches('v') == b.trim_start_matches('v')
-}
-
-fn has_supported_asset(release: &GithubRelease) -> bool {
-    pick_dmg_asset(&release.assets).is_some()
-}
-
-fn pick_dmg_asset(assets: &[GithubAsset]) -> Option<GithubAsset> {
-    let arch = std::env::consts::ARCH;
-    let dmg_assets: Vec<GithubAsset> = assets
-        .iter()
-        .filter(|asset| asset.name.to_lowercase().ends_with(".dmg"))
-        .cloned()
-        .collect();
-
-    let arch_match = match arch {
-        "aarch64" => dmg_assets
-            .iter()
-            .find(|asset| asset.name.contains("aarch64") || asset.name.contains("arm64"))
-            .cloned(),
-        "x86_64" => dmg_assets
-            .iter()
-            .find(|asset| asset.name.contains("x86_64") || asset.name.contains("amd64"))
-            .cloned(),
-        _ => None,
-    };
-
-    arch_match.or_else(|| dmg_assets.into_iter().next())
-}
-
-fn download_asset_to_temp(url: &str, tag: &str) -> Result<PathBuf, String> {
-    let client = Client::builder()
-        .user_agent("LockPilot-Updater")
-        .build()
-        .map_err(|err| format!("Failed to build HTTP client: {err}"))?;
-    let response = client
-        .get(url)
-        .send()
-        .map_err(|err| format!("Failed to download release asset: {err}"))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "Release asset download failed with status {}",
-            response.status()
-        ));
-    }
-
-    let bytes = response
-        .bytes()
-        .map_err(|err| format!("Failed to read release asset body: {err}"))?;
-    let safe_tag = tag.replace('/', "-");
-    let path = std::env::temp_dir().join(format!("LockPilot-{safe_tag}.dmg"));
-    fs::write(&path, bytes).map_err(|err| format!("Failed to write installer DMG: {err}"))?;
-    Ok(path)
-}
+// ─── Entry point ──────────────────────────────────────────────────
 
 fn main() {
     tauri::Builder::default()
         .setup(|app| {
-            let store = TimerStore::new(timer_storage_path(app.handle()));
-            let pre_action_store = PreActionStore::new();
-            if let Err(err) = restore_timers(&store, app.handle(), &pre_action_store) {
+            let scheduler = Scheduler::start(
+                TimerStore::new(data_file_path(app.handle(), "timers.json")),
+                HistoryStore::new(data_file_path(app.handle(), "history.jsonl")),
+                LockStore::new(data_file_path(app.handle(), "locks.json"))?,
+                Arc::new(MacActionBackend),
+                Arc::new(MacSystemProbe),
+                Arc::new(TauriPrompter {
+                    app: app.handle().clone(),
+                }),
+            );
+            if let Err(err) = scheduler.restore_timers() {
                 eprintln!("Failed to restore timers: {err}");
             }
-            app.manage(store);
-            app.manage(pre_action_store);
+            app.manage(scheduler);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             create_timer,
+            update_timer,
             list_timers,
             cancel_timer,
+            skip_next_occurrence,
+            pause_timer,
+            resume_timer,
             resolve_pre_action,
+            preview_recurrence,
+            list_history,
+            clear_history,
             list_release_versions,
             check_channel_update,
             install_channel_update,
@@ -1,10 +1,12 @@
 const { invoke } = window.__TAURI__.core;
 const { getVersion } = window.__TAURI__.app;
+const { listen } = window.__TAURI__.event;
 
 const form = document.getElementById("timer-form");
 const actionInput = document.getElementById("action");
 const targetTimeInput = document.getElementById("target-time");
 const setNowBtn = document.getElementById("set-now");
+const idleMinutesInput = document.getElementById("idle-minutes");
 const recurrencePresetInput = document.getElementById("recurrence-preset");
 const actionChoiceBoxes = document.querySelectorAll(".choice-box[data-action]");
 const recurrenceChoiceBoxes = document.querySelectorAll(".choice-box[data-recurrence]");
@@ -18,11 +20,69 @@ const intervalWrap = document.getElementById("interval-wrap");
 const intervalHoursInput = document.getElementById("interval-hours");
 const specificDaysWrap = document.getElementById("specific-days-wrap");
 const specificDayInputs = document.querySelectorAll('input[name="specific-day"]');
+const cronWrap = document.getElementById("cron-wrap");
+const cronExpressionInput = document.getElementById("cron-expression");
+const cronPreviewBtn = document.getElementById("cron-preview-btn");
+const cronPreviewEl = document.getElementById("cron-preview");
+const rruleWrap = document.getElementById("rrule-wrap");
+const rruleInput = document.getElementById("rrule-input");
+const rrulePreviewBtn = document.getElementById("rrule-preview-btn");
+const rrulePreviewEl = document.getElementById("rrule-preview");
+const endsWrap = document.getElementById("ends-wrap");
+const endsAtInput = document.getElementById("ends-at");
+const maxOccurrencesInput = document.getElementById("max-occurrences");
+const skipWrap = document.getElementById("skip-wrap");
+const skipDatesInput = document.getElementById("skip-dates");
+const holidayCalendarInput = document.getElementById("holiday-calendar");
+const snoozeDurationsInput = document.getElementById("snooze-durations");
+const snoozeMaxCountInput = document.getElementById("snooze-max-count");
+const snoozeMaxTotalInput = document.getElementById("snooze-max-total");
+const conditionPowerInput = document.getElementById("condition-power");
+const conditionIdleInput = document.getElementById("condition-idle");
+const conditionCpuInput = document.getElementById("condition-cpu");
+const conditionProcessesInput = document.getElementById("condition-processes");
+const conditionUnmetInput = document.getElementById("condition-unmet");
+const conditionDeferInput = document.getElementById("condition-defer");
+const lockPinInput = document.getElementById("lock-pin");
 const messageWrap = document.getElementById("message-wrap");
 const messageInput = document.getElementById("message");
+const openWrap = document.getElementById("open-wrap");
+const openTargetInput = document.getElementById("open-target");
+const closeAppsWrap = document.getElementById("close-apps-wrap");
+const closeAppsInput = document.getElementById("close-apps");
+const closeGraceInput = document.getElementById("close-grace");
+const sequenceWrap = document.getElementById("sequence-wrap");
+const sequenceStepsEl = document.getElementById("sequence-steps");
+const addStepBtn = document.getElementById("add-step");
+const commandWrap = document.getElementById("command-wrap");
+const commandProgramInput = document.getElementById("command-program");
+const commandArgsInput = document.getElementById("command-args");
+const commandEnvInput = document.getElementById("command-env");
+const commandWorkingDirInput = document.getElementById("command-working-dir");
+const commandTimeoutInput = document.getElementById("command-timeout");
+const commandShellInput = document.getElementById("command-shell");
+const submitBtn = form.querySelector('button[type="submit"]');
+const cancelEditBtn = document.getElementById("cancel-edit");
 const timersEl = document.getElementById("timers");
 const statusEl = document.getElementById("status");
 const refreshBtn = document.getElementById("refresh");
+const unlockPinInput = document.getElementById("unlock-pin");
+const historyEl = document.getElementById("history");
+const historyActionSelect = document.getElementById("history-action");
+const historyFromInput = document.getElementById("history-from");
+const historyToInput = document.getElementById("history-to");
+const historyTimerInput = document.getElementById("history-timer");
+const historyRefreshBtn = document.getElementById("history-refresh");
+const historyClearBtn = document.getElementById("history-clear");
+const preActionModal = document.getElementById("pre-action-modal");
+const preActionTitleEl = document.getElementById("pre-action-title");
+const preActionLineEl = document.getElementById("pre-action-line");
+const preActionAppsEl = document.getElementById("pre-action-apps");
+const preActionSnoozeNoteEl = document.getElementById("pre-action-snooze-note");
+const preActionPinInput = document.getElementById("pre-action-pin");
+const preActionButtonsEl = document.getElementById("pre-action-buttons");
+const preActionSkipBtn = document.getElementById("pre-action-skip");
+const preActionRunBtn = document.getElementById("pre-action-run");
 
 const checkUpdatesBtn = document.getElementById("check-updates");
 const autoCheckUpdatesInput = document.getElementById("auto-check-updates");
@@ -42,9 +102,49 @@ const AUTO_UPDATE_KEY = "lockpilot.autoCheckUpdates";
 const UPDATE_CHANNEL_KEY = "lockpilot.updateChannel";
 let currentVersion = "";
 let latestUpdate = null;
+let editingTimerId = null;
+let activePrompt = null;
+let preActionTicker = null;
 
 const getPreWarningInputs = () => document.querySelectorAll('input[name="prewarn"]');
 
+const formatOffset = (seconds) => {
+  const parts = [
+    [Math.floor(seconds / 3600), "h"],
+    [Math.floor((seconds % 3600) / 60), "m"],
+    [seconds % 60, "s"],
+  ].filter(([value]) => value > 0);
+  return parts.map(([value, unit]) => `${value}${unit}`).join(" ");
+};
+
+// "30s", "2m", "1h"; a bare number means minutes.
+const parseOffset = (text) => {
+  const match = /^(\d+)\s*([smh]?)$/i.exec(String(text).trim());
+  if (!match) {
+    return null;
+  }
+  const unit = { s: 1, m: 60, h: 3600 }[match[2].toLowerCase() || "m"];
+  const seconds = Number.parseInt(match[1], 10) * unit;
+  return seconds > 0 ? seconds : null;
+};
+
+const addPreWarningChip = (seconds) => {
+  const existing = [...getPreWarningInputs()].find((input) => Number.parseInt(input.value, 10) === seconds);
+  if (existing) {
+    existing.checked = true;
+    return;
+  }
+
+  if (!preWarnRow) {
+    return;
+  }
+
+  const label = document.createElement("label");
+  label.className = "prewarn-chip";
+  label.innerHTML = `<input type=\"checkbox\" name=\"prewarn\" value=\"${seconds}\" checked />${formatOffset(seconds)}`;
+  preWarnRow.insertBefore(label, customPrewarnInput);
+};
+
 const actionLabel = (action) => {
   if (action === "lock") {
     return "Lock screen";
@@ -55,6 +155,27 @@ const actionLabel = (action) => {
   if (action === "reboot") {
     return "Restart";
   }
+  if (action === "sleep") {
+    return "Sleep";
+  }
+  if (action === "hibernate") {
+    return "Hibernate";
+  }
+  if (action === "logout") {
+    return "Log out";
+  }
+  if (action === "run_command") {
+    return "Command";
+  }
+  if (action === "open") {
+    return "Open";
+  }
+  if (action === "close_apps") {
+    return "Close apps";
+  }
+  if (action === "sequence") {
+    return "Sequence";
+  }
   return "Action";
 };
 
@@ -111,10 +232,17 @@ const applyQuickIncrement = (minutes, fromNow = false) => {
   targetTimeInput.value = toLocalDateTimeValue(next);
 };
 
-const toggleMessage = () => {
+const toggleActionFields = () => {
   const isPopup = actionInput.value === "popup";
   messageWrap.classList.toggle("is-blank", !isPopup);
   messageInput.required = false;
+  commandWrap.classList.toggle("hidden", actionInput.value !== "run_command");
+  openWrap.classList.toggle("hidden", actionInput.value !== "open");
+  closeAppsWrap.classList.toggle("hidden", actionInput.value !== "close_apps");
+  sequenceWrap.classList.toggle("hidden", actionInput.value !== "sequence");
+  if (actionInput.value === "sequence" && !sequenceStepsEl.children.length) {
+    addSequenceStep();
+  }
 };
 
 const syncActionChoices = () => {
@@ -135,14 +263,26 @@ const toggleRecurrence = () => {
     recurrencePresetInput.value === "every_n_hours" ||
     recurrencePresetInput.value === "every_n_minutes";
   const needsSpecificDays = recurrencePresetInput.value === "specific_days";
+  const needsCron = recurrencePresetInput.value === "cron";
+  const needsRrule = recurrencePresetInput.value === "rrule";
   intervalWrap.classList.toggle("hidden", !needsInterval);
   specificDaysWrap.classList.toggle("hidden", !needsSpecificDays);
+  cronWrap.classList.toggle("hidden", !needsCron);
+  rruleWrap.classList.toggle("hidden", !needsRrule);
+  endsWrap.classList.toggle("hidden", !recurring);
+  skipWrap.classList.toggle("hidden", !recurring);
   intervalHoursInput.required = needsInterval;
+  cronExpressionInput.required = needsCron;
+  rruleInput.required = needsRrule;
   intervalHoursInput.max = recurrencePresetInput.value === "every_n_minutes" ? "1440" : "24";
 
   if (!recurring) {
     intervalWrap.classList.add("hidden");
     specificDaysWrap.classList.add("hidden");
+    cronWrap.classList.add("hidden");
+    rruleWrap.classList.add("hidden");
+    endsWrap.classList.add("hidden");
+    skipWrap.classList.add("hidden");
   }
 };
 
@@ -198,11 +338,384 @@ const recurrenceLabel = (recurrence) => {
     return `Repeats every ${recurrence.intervalMinutes ?? "?"} minute(s)`;
   }
 
+  if (recurrence.preset === "cron") {
+    return `Repeats on cron: ${recurrence.cronExpression ?? "?"}`;
+  }
+
+  if (recurrence.preset === "rrule") {
+    return `Repeats by rule: ${recurrence.rrule ?? "?"}`;
+  }
+
   return "Recurring";
 };
 
+const parseSkipDates = (value) => {
+  const dates = value
+    .split(/[\s,]+/)
+    .map((date) => date.trim())
+    .filter(Boolean);
+  return dates.length ? dates : null;
+};
+
+// PIN typed for locked timers; unlocked timers ignore it.
+const unlockPin = () => unlockPinInput.value || null;
+
+const attemptsError = (attempts) => {
+  const last = [...attempts].reverse().find((attempt) => !attempt.success);
+  if (!last) {
+    return "No method was attempted";
+  }
+  if (last.stderr) {
+    return `${last.method}: ${last.stderr}`;
+  }
+  return last.exitCode != null ? `${last.method} exited with status ${last.exitCode}` : `${last.method} failed`;
+};
+
+// Mirrors the backend's summary of a failed action: its last failed attempt,
+// or each failed step of a sequence, or where the sequence was stopped.
+const outcomeError = (outcome) => {
+  if (!outcome || outcome.success) {
+    return null;
+  }
+  if (!outcome.steps?.length) {
+    return attemptsError(outcome.attempts);
+  }
+  const failed = outcome.steps
+    .map((step, index) => ({ step, index }))
+    .filter(({ step }) => step.startedAt && !step.success)
+    .map(({ step, index }) => `step ${index + 1} (${step.action}): ${attemptsError(step.attempts)}`);
+  if (!failed.length) {
+    const next = outcome.steps.findIndex((step) => !step.startedAt);
+    return `stopped before step ${Math.max(next, 0) + 1}`;
+  }
+  return failed.join("; ");
+};
+
+const pausedLabel = (timer) => {
+  if (timer.status !== "paused") {
+    return null;
+  }
+  if (timer.recurrence || timer.idleTrigger) {
+    return "Paused; runs are skipped until resumed";
+  }
+  const seconds = timer.remainingSeconds ?? 0;
+  const hours = Math.floor(seconds / 3600);
+  const minutes = Math.floor((seconds % 3600) / 60);
+  return `Paused with ${hours}h ${minutes}m ${seconds % 60}s left`;
+};
+
+const endConditionLabel = (timer) => {
+  const recurrence = timer.recurrence;
+  if (!recurrence) {
+    return null;
+  }
+
+  const parts = [];
+  if (recurrence.maxOccurrences) {
+    parts.push(`Run ${timer.occurrenceCount + 1} of ${recurrence.maxOccurrences}`);
+  }
+  if (recurrence.endsAt) {
+    parts.push(`Ends ${fmtDate(recurrence.endsAt)}`);
+  }
+  if (recurrence.skipDates?.length) {
+    parts.push(`Skips ${recurrence.skipDates.length} date(s)`);
+  }
+  if (recurrence.holidayCalendar) {
+    parts.push("Skips holidays");
+  }
+  return parts.length ? parts.join(" · ") : null;
+};
+
+const optionalNumber = (input) => (input.value === "" ? null : Number(input.value));
+
+// Idle timers have no run time, so the time field is switched off.
+const toggleIdleTrigger = () => {
+  const idle = idleMinutesInput.value !== "";
+  targetTimeInput.disabled = idle;
+  setNowBtn.disabled = idle;
+};
+
+const idleLabel = (timer) => {
+  const after = `Runs after ${formatOffset(timer.idleTrigger.minutes * 60)} without input`;
+  return timer.occurrenceCount ? `${after}; last ran ${fmtDate(timer.targetTime)}` : after;
+};
+
+const buildConditions = () => {
+  const conditions = {
+    power: conditionPowerInput.value || null,
+    minIdleMinutes: optionalNumber(conditionIdleInput),
+    blockingProcesses: conditionProcessesInput.value
+      .split(",")
+      .map((name) => name.trim())
+      .filter(Boolean),
+    maxCpuPercent: optionalNumber(conditionCpuInput),
+    whenUnmet: conditionUnmetInput.value,
+    deferMinutes: conditionUnmetInput.value === "defer" ? optionalNumber(conditionDeferInput) : null,
+  };
+  const empty =
+    !conditions.power &&
+    conditions.minIdleMinutes === null &&
+    !conditions.blockingProcesses.length &&
+    conditions.maxCpuPercent === null;
+  return empty ? null : conditions;
+};
+
+const unmetLabels = {
+  skip: "else skip",
+  defer: "else defer",
+  run_anyway: "else run anyway",
+};
+
+// e.g. `Only if on AC power · idle 10m · zoom not running · else defer 10m`.
+const conditionsLabel = (conditions) => {
+  if (!conditions) {
+    return null;
+  }
+  const parts = [
+    conditions.power === "ac" ? "on AC power" : conditions.power === "battery" ? "on battery" : null,
+    conditions.minIdleMinutes ? `idle ${formatOffset(conditions.minIdleMinutes * 60)}` : null,
+    conditions.blockingProcesses.length ? `${conditions.blockingProcesses.join(", ")} not running` : null,
+    conditions.maxCpuPercent ? `CPU below ${conditions.maxCpuPercent}%` : null,
+  ].filter(Boolean);
+  const otherwise = conditions.deferMinutes
+    ? `${unmetLabels.defer} ${formatOffset(conditions.deferMinutes * 60)}`
+    : unmetLabels[conditions.whenUnmet];
+  return `Only if ${parts.join(" · ")} · ${otherwise}`;
+};
+
+const buildSnooze = () => {
+  const durationsMinutes = snoozeDurationsInput.value
+    .split(/[\s,]+/)
+    .map((value) => Number.parseInt(value, 10))
+    .filter((value) => Number.isInteger(value) && value > 0);
+  const maxCount = optionalNumber(snoozeMaxCountInput);
+  const maxTotalMinutes = optionalNumber(snoozeMaxTotalInput);
+  if (!durationsMinutes.length && maxCount === null && maxTotalMinutes === null) {
+    return null;
+  }
+  return {
+    durationsMinutes: durationsMinutes.length ? durationsMinutes : [10],
+    maxCount,
+    maxTotalMinutes,
+  };
+};
+
+const lines = (text) =>
+  text
+    .split("\n")
+    .map((line) => line.trim())
+    .filter(Boolean);
+
+const STEP_ACTIONS = [
+  "popup",
+  "lock",
+  "shutdown",
+  "reboot",
+  "sleep",
+  "hibernate",
+  "logout",
+  "run_command",
+  "open",
+  "close_apps",
+];
+
+const STEP_DETAIL_PLACEHOLDERS = {
+  popup: "Message",
+  run_command: "Command line (runs through the shell)",
+  open: "Link, file or app",
+  close_apps: "Apps, comma separated",
+};
+
+// The one setting a step's action takes, as typed in its detail field.
+const stepDetail = (step) => {
+  if (step.action === "popup") {
+    return step.message ?? "";
+  }
+  if (step.action === "run_command") {
+    return step.command?.program ?? "";
+  }
+  if (step.action === "open") {
+    return step.openTarget ?? "";
+  }
+  if (step.action === "close_apps") {
+    return (step.closeApps?.apps ?? []).join(", ");
+  }
+  return "";
+};
+
+const addSequenceStep = (step = { action: "popup", delaySeconds: 0, continueOnError: false }) => {
+  const row = document.createElement("div");
+  row.className = "sequence-step";
+
+  const action = document.createElement("select");
+  action.setAttribute("aria-label", "Step action");
+  for (const value of STEP_ACTIONS) {
+    action.append(new Option(actionLabel(value), value));
+  }
+  action.value = step.action;
+
+  const delay = document.createElement("input");
+  delay.type = "number";
+  delay.min = "0";
+  delay.placeholder = "Wait (s)";
+  delay.setAttribute("aria-label", "Seconds to wait before this step");
+  delay.value = step.delaySeconds ? String(step.delaySeconds) : "";
+
+  const detail = document.createElement("input");
+  detail.type = "text";
+  detail.setAttribute("aria-label", "Step setting");
+  detail.value = stepDetail(step);
+
+  const syncDetail = () => {
+    const placeholder = STEP_DETAIL_PLACEHOLDERS[action.value];
+    detail.placeholder = placeholder ?? "";
+    detail.disabled = !placeholder;
+  };
+  action.addEventListener("change", syncDetail);
+  syncDetail();
+
+  const toggle = document.createElement("label");
+  toggle.className = "inline-toggle";
+  const continueOnError = document.createElement("input");
+  continueOnError.type = "checkbox";
+  continueOnError.checked = Boolean(step.continueOnError);
+  toggle.append(continueOnError, "Continue on error");
+
+  const remove = document.createElement("button");
+  remove.type = "button";
+  remove.className = "secondary";
+  remove.textContent = "Remove";
+  remove.addEventListener("click", () => row.remove());
+
+  row.append(action, delay, detail, toggle, remove);
+  row.readStep = () => {
+    const value = detail.value.trim();
+    return {
+      action: action.value,
+      delaySeconds: delay.value === "" ? 0 : Number(delay.value),
+      continueOnError: continueOnError.checked,
+      message: action.value === "popup" ? value : null,
+      command: action.value === "run_command" ? { program: value, shell: true } : null,
+      openTarget: action.value === "open" ? value : null,
+      closeApps:
+        action.value === "close_apps"
+          ? { apps: value.split(",").map((app) => app.trim()).filter(Boolean), graceSeconds: null }
+          : null,
+    };
+  };
+  sequenceStepsEl.append(row);
+};
+
+const buildSteps = () => {
+  if (actionInput.value !== "sequence") {
+    return null;
+  }
+  return [...sequenceStepsEl.children].map((row) => row.readStep());
+};
+
+// A sequence as one line, e.g. `Popup → wait 60s → Close apps → Lock`.
+const sequenceLabel = (steps) =>
+  steps
+    .map((step) => {
+      const label = actionLabel(step.action);
+      return step.delaySeconds ? `wait ${formatOffset(step.delaySeconds)} → ${label}` : label;
+    })
+    .join(" → ");
+
+const buildCloseApps = () => {
+  if (actionInput.value !== "close_apps") {
+    return null;
+  }
+  return {
+    apps: lines(closeAppsInput.value),
+    graceSeconds: optionalNumber(closeGraceInput),
+  };
+};
+
+const buildCommand = () => {
+  if (actionInput.value !== "run_command") {
+    return null;
+  }
+  const env = {};
+  for (const line of lines(commandEnvInput.value)) {
+    const split = line.indexOf("=");
+    if (split > 0) {
+      env[line.slice(0, split).trim()] = line.slice(split + 1);
+    }
+  }
+  return {
+    program: commandProgramInput.value.trim(),
+    args: lines(commandArgsInput.value),
+    env,
+    workingDir: commandWorkingDirInput.value.trim() || null,
+    timeoutSeconds: optionalNumber(commandTimeoutInput),
+    shell: commandShellInput.checked,
+  };
+};
+
+const commandLabel = (command) => {
+  const parts = [command.program, ...(command.args ?? [])];
+  return `${command.shell ? "Shell" : "Command"}: ${parts.join(" ")}`;
+};
+
+const buildRecurrence = () => {
+  const recurrencePreset = recurrencePresetInput.value;
+  if (recurrencePreset === "none") {
+    return null;
+  }
+
+  const selectedSpecificDays = [...specificDayInputs]
+    .filter((input) => input.checked)
+    .map((input) => input.value);
+
+  return {
+    preset: recurrencePreset,
+    intervalHours: recurrencePreset === "every_n_hours" ? Number(intervalHoursInput.value || 0) : null,
+    intervalMinutes: recurrencePreset === "every_n_minutes" ? Number(intervalHoursInput.value || 0) : null,
+    daysOfWeek: recurrencePreset === "specific_days" ? selectedSpecificDays : null,
+    cronExpression: recurrencePreset === "cron" ? cronExpressionInput.value.trim() : null,
+    rrule: recurrencePreset === "rrule" ? rruleInput.value.trim() : null,
+    timezone: Intl.DateTimeFormat().resolvedOptions().timeZone || null,
+    endsAt: parseLocalDateTimeValue(endsAtInput.value)?.toISOString() ?? null,
+    maxOccurrences: maxOccurrencesInput.value ? Number(maxOccurrencesInput.value) : null,
+    skipDates: parseSkipDates(skipDatesInput.value),
+    holidayCalendar: holidayCalendarInput.value.trim() || null,
+  };
+};
+
+const previewRecurrence = async (listEl) => {
+  listEl.innerHTML = "";
+  const recurrence = buildRecurrence();
+  if (!recurrence) {
+    return;
+  }
+
+  const startTime = parseLocalDateTimeValue(targetTimeInput.value);
+  try {
+    const preview = await invoke("preview_recurrence", {
+      request: {
+        recurrence,
+        startTime: startTime ? startTime.toISOString() : null,
+        count: 5,
+      },
+    });
+    if (!preview.runs.length) {
+      showStatus("This schedule has no upcoming runs.", true);
+      return;
+    }
+    for (const run of preview.runs) {
+      const item = document.createElement("li");
+      item.textContent = fmtDate(run);
+      listEl.appendChild(item);
+    }
+  } catch (err) {
+    showStatus(String(err), true);
+  }
+};
+
 const renderTimers = (timers) => {
   timersEl.innerHTML = "";
+  unlockPinInput.classList.toggle("hidden", !timers.some((timer) => timer.locked));
 
   if (!timers.length) {
     const empty = document.createElement("li");
@@ -220,26 +733,80 @@ const renderTimers = (timers) => {
     top.className = "timer-top";
 
     const title = document.createElement("strong");
-    title.textContent = timer.action.toUpperCase();
+    title.textContent = timer.action.replace(/_/g, " ").toUpperCase();
 
+    // One-shots that have run stay listed, with their result, until removed.
+    const done = timer.status === "done";
     const cancelBtn = document.createElement("button");
     cancelBtn.className = "danger";
-    cancelBtn.textContent = "Cancel";
+    cancelBtn.textContent = done ? "Remove" : "Cancel";
     cancelBtn.addEventListener("click", async () => {
       try {
-        await invoke("cancel_timer", { id: timer.id });
+        await invoke("cancel_timer", { id: timer.id, pin: unlockPin() });
+        if (editingTimerId === timer.id) {
+          resetForm();
+        }
         await loadTimers();
-        showStatus("Timer canceled.");
+        showStatus(done ? "Timer removed." : "Timer canceled.");
       } catch (err) {
         showStatus(String(err), true);
       }
     });
 
-    top.append(title, cancelBtn);
+    const actions = document.createElement("div");
+    actions.className = "timer-actions";
+
+    const paused = timer.status === "paused";
+    const pauseBtn = document.createElement("button");
+    pauseBtn.className = "secondary";
+    pauseBtn.textContent = paused ? "Resume" : "Pause";
+    pauseBtn.addEventListener("click", async () => {
+      try {
+        await invoke(paused ? "resume_timer" : "pause_timer", { id: timer.id, pin: unlockPin() });
+        await loadTimers();
+        showStatus(paused ? "Timer resumed." : "Timer paused.");
+      } catch (err) {
+        showStatus(String(err), true);
+      }
+    });
+    if (!done) {
+      actions.append(pauseBtn);
+    }
+
+    const editBtn = document.createElement("button");
+    editBtn.className = "secondary";
+    editBtn.textContent = "Edit";
+    editBtn.addEventListener("click", () => startEditing(timer));
+    actions.append(editBtn);
+
+    if (timer.recurrence) {
+      const skipBtn = document.createElement("button");
+      skipBtn.className = "secondary";
+      skipBtn.textContent = "Skip Next";
+      skipBtn.addEventListener("click", async () => {
+        try {
+          await invoke("skip_next_occurrence", { id: timer.id, pin: unlockPin() });
+          await loadTimers();
+          showStatus("Next occurrence skipped.");
+        } catch (err) {
+          showStatus(String(err), true);
+        }
+      });
+      actions.append(skipBtn);
+    }
+
+    actions.append(cancelBtn);
+    top.append(title, actions);
 
     const when = document.createElement("div");
     when.className = "timer-meta";
-    when.textContent = `Runs at ${fmtDate(timer.targetTime)} (${fmtRemaining(timer.targetTime)})`;
+    when.textContent =
+      done
+        ? `Ran at ${fmtDate(timer.targetTime)}`
+        : pausedLabel(timer) ??
+          (timer.idleTrigger
+            ? idleLabel(timer)
+            : `Runs at ${fmtDate(timer.targetTime)} (${fmtRemaining(timer.targetTime)})`);
 
     const recurrence = document.createElement("div");
     recurrence.className = "timer-meta";
@@ -247,6 +814,44 @@ const renderTimers = (timers) => {
 
     item.append(top, when, recurrence);
 
+    if (timer.idleError) {
+      const idleError = document.createElement("div");
+      idleError.className = "timer-meta";
+      idleError.textContent = `Can't read idle time (${timer.idleError}); still checking`;
+      item.append(idleError);
+    }
+
+    const lastError = outcomeError(timer.lastOutcome);
+    if (lastError) {
+      const failed = document.createElement("div");
+      failed.className = "timer-meta";
+      failed.textContent = `Last run failed (${lastError})`;
+      item.append(failed);
+    }
+
+    if (timer.locked) {
+      const lock = document.createElement("div");
+      lock.className = "timer-meta";
+      lock.textContent = "Locked: cancel, edit, pause, resume, skip and snooze need the PIN";
+      item.append(lock);
+    }
+
+    const onlyIf = conditionsLabel(timer.conditions);
+    if (onlyIf) {
+      const conditions = document.createElement("div");
+      conditions.className = "timer-meta";
+      conditions.textContent = onlyIf;
+      item.append(conditions);
+    }
+
+    const endsLabel = endConditionLabel(timer);
+    if (endsLabel) {
+      const ends = document.createElement("div");
+      ends.className = "timer-meta";
+      ends.textContent = endsLabel;
+      item.append(ends);
+    }
+
     if (timer.action === "popup" && timer.message) {
       const msg = document.createElement("div");
       msg.className = "timer-meta";
@@ -254,10 +859,134 @@ const renderTimers = (timers) => {
       item.append(msg);
     }
 
+    if (timer.action === "open" && timer.openTarget) {
+      const target = document.createElement("div");
+      target.className = "timer-meta";
+      target.textContent = `Opens: ${timer.openTarget}`;
+      item.append(target);
+    }
+
+    if (timer.action === "close_apps" && timer.closeApps) {
+      const apps = document.createElement("div");
+      apps.className = "timer-meta";
+      apps.textContent = `Closes: ${timer.closeApps.apps.join(", ")}`;
+      item.append(apps);
+
+      // One attempt per app, such as `force-quit:steam`.
+      const failed = (timer.lastOutcome?.attempts ?? []).filter((attempt) => !attempt.success);
+      if (failed.length) {
+        const result = document.createElement("div");
+        result.className = "timer-meta";
+        result.textContent = `Last run: ${failed.map((attempt) => `${attempt.method} (${attempt.stderr ?? "failed"})`).join(", ")}`;
+        item.append(result);
+      }
+    }
+
+    if (timer.action === "sequence" && timer.steps) {
+      const steps = document.createElement("div");
+      steps.className = "timer-meta";
+      steps.textContent = `Steps: ${sequenceLabel(timer.steps)}`;
+      if (timer.sequenceRun) {
+        steps.textContent += ` (running; step ${timer.sequenceRun.nextStep + 1} of ${timer.steps.length} next)`;
+      }
+      item.append(steps);
+    }
+
+    if (timer.action === "run_command" && timer.command) {
+      const command = document.createElement("div");
+      command.className = "timer-meta";
+      command.textContent = commandLabel(timer.command);
+      item.append(command);
+
+      const result = timer.lastOutcome?.attempts?.[0];
+      if (result) {
+        const output = document.createElement("div");
+        output.className = "timer-meta";
+        const status = result.exitCode != null ? `exit ${result.exitCode}` : result.success ? "done" : "failed";
+        output.textContent = result.stdout ? `Last run: ${status} · ${result.stdout}` : `Last run: ${status}`;
+        item.append(output);
+      }
+    }
+
     timersEl.append(item);
   }
 };
 
+const hidePreAction = () => {
+  activePrompt = null;
+  preActionPinInput.value = "";
+  clearInterval(preActionTicker);
+  preActionModal.classList.add("hidden");
+};
+
+const resolvePreAction = async (decision, snoozeMinutes = null) => {
+  if (!activePrompt) {
+    return;
+  }
+
+  try {
+    await invoke("resolve_pre_action", {
+      request: {
+        promptId: activePrompt.promptId,
+        decision,
+        snoozeMinutes,
+        pin: preActionPinInput.value || null,
+      },
+    });
+    hidePreAction();
+    await loadTimers();
+    await loadHistory();
+  } catch (err) {
+    showStatus(String(err), true);
+  }
+};
+
+const snoozeNote = (payload) => {
+  if (payload.snoozesRemaining === 0) {
+    return "No snoozes left for this run.";
+  }
+  if (!payload.snoozeOptionsMinutes.length) {
+    return "Snoozing would pass this run's delay limit.";
+  }
+  if (payload.snoozesRemaining != null) {
+    return `${payload.snoozesRemaining} snooze(s) left.`;
+  }
+  return "";
+};
+
+const showPreAction = (payload) => {
+  activePrompt = payload;
+  preActionTitleEl.textContent = `${actionLabel(payload.action)} incoming`;
+  preActionAppsEl.textContent = payload.apps.length ? `Closes: ${payload.apps.join(", ")}` : "";
+  preActionAppsEl.classList.toggle("hidden", !payload.apps.length);
+  preActionSnoozeNoteEl.textContent = snoozeNote(payload);
+  preActionPinInput.classList.toggle("hidden", !payload.locked);
+
+  preActionButtonsEl.querySelectorAll("[data-snooze-minutes]").forEach((button) => button.remove());
+  for (const minutes of payload.snoozeOptionsMinutes) {
+    const button = document.createElement("button");
+    button.className = "secondary";
+    button.dataset.snoozeMinutes = String(minutes);
+    button.textContent = `Snooze ${formatOffset(minutes * 60)}`;
+    button.addEventListener("click", () => resolvePreAction("snooze", minutes));
+    preActionButtonsEl.insertBefore(button, preActionSkipBtn);
+  }
+
+  const deadline = Date.now() + payload.countdownSeconds * 1000;
+  const tick = () => {
+    const seconds = Math.round((deadline - Date.now()) / 1000);
+    if (seconds <= 0) {
+      hidePreAction();
+      return;
+    }
+    preActionLineEl.textContent = `${actionLabel(payload.action)} in ${formatOffset(seconds)}`;
+  };
+  clearInterval(preActionTicker);
+  tick();
+  preActionTicker = setInterval(tick, 1000);
+  preActionModal.classList.remove("hidden");
+};
+
 const loadTimers = async () => {
   try {
     const timers = await invoke("list_timers");
@@ -267,6 +996,84 @@ const loadTimers = async () => {
   }
 };
 
+const historyEventLabels = {
+  executed: "Ran",
+  skipped: "Skipped",
+  ended: "Ended",
+  pin_rejected: "Wrong PIN",
+  deferred: "Deferred",
+  idle_unavailable: "Idle time unavailable",
+};
+
+// Dates are local days; the range covers the whole "to" day.
+const historyFilter = () => {
+  const dayStart = (value, offsetDays = 0) => {
+    if (!value) {
+      return null;
+    }
+    const date = new Date(`${value}T00:00:00`);
+    date.setDate(date.getDate() + offsetDays);
+    return date.toISOString();
+  };
+
+  return {
+    timerId: historyTimerInput.value.trim() || null,
+    action: historyActionSelect.value || null,
+    from: dayStart(historyFromInput.value),
+    to: dayStart(historyToInput.value, 1),
+  };
+};
+
+const renderHistory = (entries) => {
+  historyEl.innerHTML = "";
+
+  if (!entries.length) {
+    const empty = document.createElement("li");
+    empty.className = "empty";
+    empty.textContent = "No history yet.";
+    historyEl.appendChild(empty);
+    return;
+  }
+
+  for (const entry of entries) {
+    const item = document.createElement("li");
+    item.className = "timer-item";
+
+    const title = document.createElement("strong");
+    title.textContent = `${entry.action.replace(/_/g, " ").toUpperCase()} · ${historyEventLabels[entry.event] ?? entry.event}`;
+
+    const when = document.createElement("div");
+    when.className = "timer-meta";
+    when.textContent = entry.scheduledTime
+      ? `Scheduled ${fmtDate(entry.scheduledTime)} · Recorded ${fmtDate(entry.recordedAt)}`
+      : `Recorded ${fmtDate(entry.recordedAt)}`;
+    item.append(title, when);
+
+    const notes = [
+      entry.decision ? `Decision: ${entry.decision.replace(/_/g, " ")}` : null,
+      entry.error ? `Error: ${entry.error}` : null,
+      entry.detail,
+      `Timer ${entry.timerId}`,
+    ].filter(Boolean);
+    for (const note of notes) {
+      const line = document.createElement("div");
+      line.className = "timer-meta";
+      line.textContent = note;
+      item.append(line);
+    }
+
+    historyEl.append(item);
+  }
+};
+
+const loadHistory = async () => {
+  try {
+    renderHistory(await invoke("list_history", { filter: historyFilter() }));
+  } catch (err) {
+    showStatus(String(err), true);
+  }
+};
+
 const renderUpdateResult = (update) => {
   if (!update) {
     updateResultEl.classList.add("hidden");
@@ -355,10 +1162,128 @@ const installTag = async (tag) => {
   }
 };
 
+const resetForm = () => {
+  const selectedAction = actionInput.value;
+  const selectedTargetTime = targetTimeInput.value;
+  form.reset();
+  actionInput.value = selectedAction;
+  targetTimeInput.value = selectedTargetTime;
+  recurrencePresetInput.value = "none";
+  intervalHoursInput.value = "2";
+  specificDayInputs.forEach((input) => {
+    input.checked = false;
+  });
+  cronExpressionInput.value = "";
+  cronPreviewEl.innerHTML = "";
+  rruleInput.value = "";
+  rrulePreviewEl.innerHTML = "";
+  endsAtInput.value = "";
+  maxOccurrencesInput.value = "";
+  skipDatesInput.value = "";
+  holidayCalendarInput.value = "";
+  snoozeDurationsInput.value = "";
+  snoozeMaxCountInput.value = "";
+  snoozeMaxTotalInput.value = "";
+  conditionPowerInput.value = "";
+  conditionIdleInput.value = "";
+  conditionCpuInput.value = "";
+  conditionProcessesInput.value = "";
+  conditionUnmetInput.value = "skip";
+  conditionDeferInput.value = "";
+  lockPinInput.value = "";
+  idleMinutesInput.value = "";
+  commandShellInput.checked = false;
+  sequenceStepsEl.innerHTML = "";
+  setEditing(null);
+  toggleIdleTrigger();
+  toggleActionFields();
+  toggleRecurrence();
+  syncActionChoices();
+  syncRecurrenceChoices();
+};
+
+const setEditing = (timerId) => {
+  editingTimerId = timerId;
+  submitBtn.textContent = timerId ? "Save Changes" : "Add Timer";
+  cancelEditBtn.classList.toggle("hidden", !timerId);
+};
+
+const startEditing = (timer) => {
+  resetForm();
+  const recurrence = timer.recurrence;
+
+  actionInput.value = timer.action;
+  targetTimeInput.value = toLocalDateTimeValue(new Date(timer.targetTime));
+  idleMinutesInput.value = timer.idleTrigger?.minutes ?? "";
+  messageInput.value = timer.message ?? "";
+  const command = timer.command;
+  commandProgramInput.value = command?.program ?? "";
+  commandArgsInput.value = (command?.args ?? []).join("\n");
+  commandEnvInput.value = Object.entries(command?.env ?? {})
+    .map(([key, value]) => `${key}=${value}`)
+    .join("\n");
+  commandWorkingDirInput.value = command?.workingDir ?? "";
+  commandTimeoutInput.value = command?.timeoutSeconds ?? "";
+  commandShellInput.checked = Boolean(command?.shell);
+  openTargetInput.value = timer.openTarget ?? "";
+  closeAppsInput.value = (timer.closeApps?.apps ?? []).join("\n");
+  sequenceStepsEl.innerHTML = "";
+  for (const step of timer.steps ?? []) {
+    addSequenceStep(step);
+  }
+  closeGraceInput.value = timer.closeApps?.graceSeconds ?? "";
+  recurrencePresetInput.value = recurrence?.preset ?? "none";
+
+  if (recurrence) {
+    const interval = recurrence.intervalHours ?? recurrence.intervalMinutes;
+    if (interval) {
+      intervalHoursInput.value = String(interval);
+    }
+    const days = recurrence.daysOfWeek ?? [];
+    specificDayInputs.forEach((input) => {
+      input.checked = days.includes(input.value);
+    });
+    cronExpressionInput.value = recurrence.cronExpression ?? "";
+    rruleInput.value = recurrence.rrule ?? "";
+    endsAtInput.value = recurrence.endsAt ? toLocalDateTimeValue(new Date(recurrence.endsAt)) : "";
+    maxOccurrencesInput.value = recurrence.maxOccurrences ? String(recurrence.maxOccurrences) : "";
+    skipDatesInput.value = (recurrence.skipDates ?? []).join(", ");
+    holidayCalendarInput.value = recurrence.holidayCalendar ?? "";
+  }
+
+  const warnings = timer.preWarningSeconds ?? (timer.preWarningMinutes ?? []).map((minutes) => minutes * 60);
+  getPreWarningInputs().forEach((input) => {
+    input.checked = warnings.includes(Number.parseInt(input.value, 10));
+  });
+  warnings.forEach(addPreWarningChip);
+
+  const snooze = timer.snooze;
+  snoozeDurationsInput.value = (snooze?.durationsMinutes ?? []).join(", ");
+  snoozeMaxCountInput.value = snooze?.maxCount ?? "";
+  snoozeMaxTotalInput.value = snooze?.maxTotalMinutes ?? "";
+
+  const conditions = timer.conditions;
+  conditionPowerInput.value = conditions?.power ?? "";
+  conditionIdleInput.value = conditions?.minIdleMinutes ?? "";
+  conditionCpuInput.value = conditions?.maxCpuPercent ?? "";
+  conditionProcessesInput.value = (conditions?.blockingProcesses ?? []).join(", ");
+  conditionUnmetInput.value = conditions?.whenUnmet ?? "skip";
+  conditionDeferInput.value = conditions?.deferMinutes ?? "";
+
+  setEditing(timer.id);
+  toggleIdleTrigger();
+  toggleActionFields();
+  toggleRecurrence();
+  syncActionChoices();
+  syncRecurrenceChoices();
+  showStatus("Editing timer. Save to apply changes.");
+};
+
 form.addEventListener("submit", async (event) => {
   event.preventDefault();
 
-  if (!targetTimeInput.value) {
+  const idleMinutes = optionalNumber(idleMinutesInput);
+  if (idleMinutes === null && !targetTimeInput.value) {
     showStatus("Choose a valid time.", true);
     return;
   }
@@ -367,7 +1292,7 @@ form.addEventListener("submit", async (event) => {
   const selectedSpecificDays = [...specificDayInputs]
     .filter((input) => input.checked)
     .map((input) => input.value);
-  const preWarningMinutes = [...getPreWarningInputs()]
+  const preWarningSeconds = [...getPreWarningInputs()]
     .filter((input) => input.checked)
     .map((input) => Number.parseInt(input.value, 10))
     .filter((value) => Number.isInteger(value) && value > 0);
@@ -377,41 +1302,63 @@ form.addEventListener("submit", async (event) => {
     return;
   }
 
-  let recurrence = null;
-  if (recurrencePreset !== "none") {
-    recurrence = {
-      preset: recurrencePreset,
-      intervalHours: recurrencePreset === "every_n_hours" ? Number(intervalHoursInput.value || 0) : null,
-      intervalMinutes: recurrencePreset === "every_n_minutes" ? Number(intervalHoursInput.value || 0) : null,
-      daysOfWeek: recurrencePreset === "specific_days" ? selectedSpecificDays : null,
-    };
+  if (recurrencePreset === "cron" && !cronExpressionInput.value.trim()) {
+    showStatus("Enter a cron expression.", true);
+    return;
   }
 
+  if (recurrencePreset === "rrule" && !rruleInput.value.trim()) {
+    showStatus("Enter a recurrence rule.", true);
+    return;
+  }
+
+  if (actionInput.value === "run_command" && !commandProgramInput.value.trim()) {
+    showStatus("Enter a program to run.", true);
+    return;
+  }
+
+  if (actionInput.value === "open" && !openTargetInput.value.trim()) {
+    showStatus("Enter a link, file or app to open.", true);
+    return;
+  }
+
+  if (actionInput.value === "close_apps" && !lines(closeAppsInput.value).length) {
+    showStatus("Enter at least one app to close.", true);
+    return;
+  }
+
+  if (actionInput.value === "sequence" && !sequenceStepsEl.children.length) {
+    showStatus("Add at least one step.", true);
+    return;
+  }
+
+  const recurrence = buildRecurrence();
+
   const request = {
     action: actionInput.value,
-    targetTime: new Date(targetTimeInput.value).toISOString(),
+    targetTime: idleMinutes === null ? new Date(targetTimeInput.value).toISOString() : new Date().toISOString(),
     recurrence,
-    preWarningMinutes,
+    preWarningSeconds: idleMinutes === null ? preWarningSeconds : [],
+    idleTrigger: idleMinutes === null ? null : { minutes: idleMinutes },
+    snooze: buildSnooze(),
+    conditions: buildConditions(),
+    lockPin: lockPinInput.value || null,
     message: actionInput.value === "popup" ? messageInput.value : null,
+    command: buildCommand(),
+    openTarget: actionInput.value === "open" ? openTargetInput.value.trim() : null,
+    closeApps: buildCloseApps(),
+    steps: buildSteps(),
   };
 
   try {
-    const selectedAction = actionInput.value;
-    const selectedTargetTime = targetTimeInput.value;
-    await invoke("create_timer", { request });
-    form.reset();
-    actionInput.value = selectedAction;
-    targetTimeInput.value = selectedTargetTime;
-    recurrencePresetInput.value = "none";
-    intervalHoursInput.value = "2";
-    specificDayInputs.forEach((input) => {
-      input.checked = false;
-    });
-    toggleMessage();
-    toggleRecurrence();
-    syncActionChoices();
-    syncRecurrenceChoices();
-    showStatus("Timer created.");
+    if (editingTimerId) {
+      await invoke("update_timer", { id: editingTimerId, request, pin: unlockPin() });
+    } else {
+      await invoke("create_timer", { request });
+    }
+    const wasEditing = Boolean(editingTimerId);
+    resetForm();
+    showStatus(wasEditing ? "Timer updated." : "Timer created.");
     await loadTimers();
   } catch (err) {
     showStatus(String(err), true);
@@ -419,8 +1366,29 @@ form.addEventListener("submit", async (event) => {
 });
 
 refreshBtn.addEventListener("click", loadTimers);
-actionInput.addEventListener("change", toggleMessage);
+historyRefreshBtn.addEventListener("click", loadHistory);
+[historyActionSelect, historyFromInput, historyToInput, historyTimerInput].forEach((input) => {
+  input.addEventListener("change", loadHistory);
+});
+historyClearBtn.addEventListener("click", async () => {
+  try {
+    const removed = await invoke("clear_history", { filter: historyFilter() });
+    showStatus(`Cleared ${removed} history entr${removed === 1 ? "y" : "ies"}.`);
+    await loadHistory();
+  } catch (err) {
+    showStatus(String(err), true);
+  }
+});
+preActionSkipBtn.addEventListener("click", () => resolvePreAction("cancel_action"));
+preActionRunBtn.addEventListener("click", () => resolvePreAction("run_now"));
+cancelEditBtn.addEventListener("click", () => {
+  resetForm();
+  showStatus("Edit canceled.");
+});
+actionInput.addEventListener("change", toggleActionFields);
+addStepBtn.addEventListener("click", () => addSequenceStep());
 recurrencePresetInput.addEventListener("change", toggleRecurrence);
+idleMinutesInput.addEventListener("input", toggleIdleTrigger);
 actionInput.addEventListener("change", syncActionChoices);
 recurrencePresetInput.addEventListener("change", syncRecurrenceChoices);
 
@@ -431,7 +1399,7 @@ actionChoiceBoxes.forEach((box) => {
       return;
     }
     actionInput.value = value;
-    toggleMessage();
+    toggleActionFields();
     syncActionChoices();
   });
 });
@@ -471,29 +1439,19 @@ if (quickCustomApplyBtn) {
 
 if (prewarnCustomApplyBtn) {
   prewarnCustomApplyBtn.addEventListener("click", () => {
-    const minutes = Number.parseInt(String(customPrewarnInput?.value || ""), 10);
-    if (!Number.isInteger(minutes) || minutes <= 0) {
-      showStatus("Pre warning custom minutes must be a positive number.", true);
+    const seconds = parseOffset(customPrewarnInput?.value || "");
+    if (!seconds) {
+      showStatus("Pre warning must look like 30s, 2m or 1h.", true);
       return;
     }
 
-    const existing = [...getPreWarningInputs()].find((input) => Number.parseInt(input.value, 10) === minutes);
-    if (existing) {
-      existing.checked = true;
-      return;
-    }
-
-    if (!preWarnRow) {
-      return;
-    }
-
-    const label = document.createElement("label");
-    label.className = "prewarn-chip";
-    label.innerHTML = `<input type=\"checkbox\" name=\"prewarn\" value=\"${minutes}\" checked />${minutes}m`;
-    preWarnRow.insertBefore(label, customPrewarnInput);
+    addPreWarningChip(seconds);
   });
 }
 
+cronPreviewBtn.addEventListener("click", () => previewRecurrence(cronPreviewEl));
+rrulePreviewBtn.addEventListener("click", () => previewRecurrence(rrulePreviewEl));
+
 if (setNowBtn) {
   setNowBtn.addEventListener("click", () => {
     setTriggerToNow();
@@ -524,12 +1482,22 @@ updateChannelSelect.addEventListener("change", () => {
 
 const initialize = async () => {
   setTriggerToNow();
-  toggleMessage();
+  toggleActionFields();
   toggleRecurrence();
   syncActionChoices();
   syncRecurrenceChoices();
+  await listen("pre_action_warning", (event) => showPreAction(event.payload));
+  await listen("action_outcome", async (event) => {
+    const { action, outcome } = event.payload;
+    const error = outcomeError(outcome);
+    if (error) {
+      showStatus(`${actionLabel(action)} failed: ${error}`, true);
+    }
+    await loadHistory();
+  });
   await loadTimers();
   setInterval(loadTimers, 1000);
+  await loadHistory();
 
   currentVersion = await getVersion();
   currentVersionEl.textContent = currentVersion;
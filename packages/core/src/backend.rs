@@ -0,0 +1,403 @@
+use std::{io, process::Output, sync::Mutex};
+
+use crate::{
+    command,
+    model::{
+        ActionAttempt, ActionOutcome, ActionStep, CloseAppsSpec, CommandSpec, StepOutcome,
+        TimerAction, TimerInfo,
+    },
+    open::{open_target_kind, OpenTargetKind},
+};
+
+/// Popup text used when a timer has no message.
+pub const DEFAULT_POPUP_MESSAGE: &str = "LockPilot timer reached.";
+
+/// Platform hooks that perform timers' system actions. Each reports every
+/// method it tried.
+pub trait ActionBackend: Send + Sync {
+    fn lock(&self) -> ActionOutcome;
+    fn shutdown(&self) -> ActionOutcome;
+    fn reboot(&self) -> ActionOutcome;
+    fn sleep(&self) -> ActionOutcome;
+    /// Fails, saying why, when the machine can't hibernate.
+    fn hibernate(&self) -> ActionOutcome;
+    /// Sign the current user out.
+    fn logout(&self) -> ActionOutcome;
+    fn popup(&self, message: &str) -> ActionOutcome;
+    /// Open a link or file with its default app, or launch an app.
+    fn open(&self, target: &str, kind: OpenTargetKind) -> ActionOutcome;
+    /// Close the listed apps, reporting one attempt per app; see
+    /// [`crate::close::close_apps`].
+    fn close_apps(&self, spec: &CloseAppsSpec) -> ActionOutcome;
+
+    /// Run a user's command. Commands run the same way everywhere, so
+    /// platforms keep this default.
+    fn run_command(&self, command: &CommandSpec) -> ActionOutcome {
+        ActionOutcome::from_attempts(vec![command::run_command(command)])
+    }
+}
+
+/// Run a single-action timer's action on `backend`. Sequences are run a
+/// step at a time by the scheduler; see [`crate::sequence`].
+pub fn run_action(backend: &dyn ActionBackend, info: &TimerInfo) -> ActionOutcome {
+    run_step(
+        backend,
+        &ActionStep {
+            action: info.action.clone(),
+            delay_seconds: 0,
+            continue_on_error: false,
+            message: info.message.clone(),
+            command: info.command.clone(),
+            open_target: info.open_target.clone(),
+            close_apps: info.close_apps.clone(),
+        },
+    )
+}
+
+/// Run a single action, from a timer or one step of a sequence.
+pub fn run_step(backend: &dyn ActionBackend, step: &ActionStep) -> ActionOutcome {
+    match step.action {
+        TimerAction::Popup => backend.popup(
+            step.message
+                .as_deref()
+                .map(str::trim)
+                .filter(|msg| !msg.is_empty())
+                .unwrap_or(DEFAULT_POPUP_MESSAGE),
+        ),
+        TimerAction::Lock => backend.lock(),
+        TimerAction::Shutdown => backend.shutdown(),
+        TimerAction::Reboot => backend.reboot(),
+        TimerAction::Sleep => backend.sleep(),
+        TimerAction::Hibernate => backend.hibernate(),
+        TimerAction::Logout => backend.logout(),
+        TimerAction::RunCommand => match &step.command {
+            Some(command) => backend.run_command(command),
+            None => ActionOutcome::from_attempts(vec![ActionAttempt::from_result(
+                "command",
+                Err("Timer has no command to run".to_string()),
+            )]),
+        },
+        TimerAction::Open => match &step.open_target {
+            Some(target) => backend.open(target, open_target_kind(target)),
+            None => ActionOutcome::from_attempts(vec![ActionAttempt::from_result(
+                "open",
+                Err("Timer has nothing to open".to_string()),
+            )]),
+        },
+        TimerAction::CloseApps => match &step.close_apps {
+            Some(spec) => backend.close_apps(spec),
+            None => ActionOutcome::from_attempts(vec![ActionAttempt::from_result(
+                "close",
+                Err("Timer has no apps to close".to_string()),
+            )]),
+        },
+        TimerAction::Sequence => ActionOutcome::from_attempts(vec![ActionAttempt::from_result(
+            "sequence",
+            Err("A sequence can't run as a single action".to_string()),
+        )]),
+    }
+}
+
+/// A call made to a [`RecordingBackend`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordedCall {
+    Lock,
+    Shutdown,
+    Reboot,
+    Sleep,
+    Hibernate,
+    Logout,
+    Popup(String),
+    RunCommand(CommandSpec),
+    Open(String),
+    CloseApps(Vec<String>),
+}
+
+/// Backend that only records what it was asked to do, so scheduling can be
+/// exercised without touching the machine.
+#[derive(Default)]
+pub struct RecordingBackend {
+    calls: Mutex<Vec<RecordedCall>>,
+    /// When set, every call fails with this error.
+    failure: Mutex<Option<String>>,
+}
+
+impl RecordingBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.calls
+            .lock()
+            .map(|calls| calls.clone())
+            .unwrap_or_default()
+    }
+
+    /// Make later calls fail with `error`, or succeed again with `None`.
+    pub fn set_failure(&self, error: Option<&str>) {
+        if let Ok(mut failure) = self.failure.lock() {
+            *failure = error.map(str::to_string);
+        }
+    }
+
+    fn record(&self, call: RecordedCall) -> ActionOutcome {
+        let method = format!("recording:{call:?}");
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push(call);
+        }
+        let failure = self.failure.lock().ok().and_then(|failure| failure.clone());
+        ActionOutcome::from_attempts(vec![ActionAttempt::from_result(
+            &method,
+            failure.map_or(Ok(()), Err),
+        )])
+    }
+}
+
+impl ActionBackend for RecordingBackend {
+    fn lock(&self) -> ActionOutcome {
+        self.record(RecordedCall::Lock)
+    }
+
+    fn shutdown(&self) -> ActionOutcome {
+        self.record(RecordedCall::Shutdown)
+    }
+
+    fn reboot(&self) -> ActionOutcome {
+        self.record(RecordedCall::Reboot)
+    }
+
+    fn sleep(&self) -> ActionOutcome {
+        self.record(RecordedCall::Sleep)
+    }
+
+    fn hibernate(&self) -> ActionOutcome {
+        self.record(RecordedCall::Hibernate)
+    }
+
+    fn logout(&self) -> ActionOutcome {
+        self.record(RecordedCall::Logout)
+    }
+
+    fn popup(&self, message: &str) -> ActionOutcome {
+        self.record(RecordedCall::Popup(message.to_string()))
+    }
+
+    fn open(&self, target: &str, _kind: OpenTargetKind) -> ActionOutcome {
+        self.record(RecordedCall::Open(target.to_string()))
+    }
+
+    fn close_apps(&self, spec: &CloseAppsSpec) -> ActionOutcome {
+        self.record(RecordedCall::CloseApps(spec.apps.clone()))
+    }
+
+    fn run_command(&self, command: &CommandSpec) -> ActionOutcome {
+        self.record(RecordedCall::RunCommand(command.clone()))
+    }
+}
+
+impl ActionAttempt {
+    /// Record of a command that ran (or failed to start) as `method`.
+    pub fn from_output(method: &str, result: io::Result<Output>) -> Self {
+        match result {
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                Self {
+                    method: method.to_string(),
+                    success: output.status.success(),
+                    exit_code: output.status.code(),
+                    stderr: (!stderr.is_empty()).then_some(stderr),
+                    stdout: None,
+                }
+            }
+            Err(err) => Self::from_result(method, Err(format!("Failed to start: {err}"))),
+        }
+    }
+
+    /// Record of an API call made as `method`.
+    pub fn from_result(method: &str, result: Result<(), String>) -> Self {
+        Self {
+            method: method.to_string(),
+            success: result.is_ok(),
+            exit_code: None,
+            stderr: result.err(),
+            stdout: None,
+        }
+    }
+}
+
+impl ActionOutcome {
+    /// Try `methods` in order, stopping at the first that succeeds.
+    pub fn first_success(methods: &[&dyn Fn() -> ActionAttempt]) -> Self {
+        let mut attempts = Vec::new();
+        for method in methods {
+            let attempt = method();
+            let success = attempt.success;
+            attempts.push(attempt);
+            if success {
+                break;
+            }
+        }
+        Self::from_attempts(attempts)
+    }
+
+    /// Outcome of independent steps, which succeeds only if every step did.
+    pub fn all(attempts: Vec<ActionAttempt>) -> Self {
+        Self {
+            success: attempts.iter().all(|attempt| attempt.success),
+            attempts,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn from_attempts(attempts: Vec<ActionAttempt>) -> Self {
+        Self {
+            success: attempts.last().is_some_and(|attempt| attempt.success),
+            attempts,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Outcome of a sequence, which succeeds only if every step ran and
+    /// succeeded.
+    pub fn from_steps(steps: Vec<StepOutcome>) -> Self {
+        Self {
+            success: steps.iter().all(|step| step.success),
+            attempts: Vec::new(),
+            steps,
+        }
+    }
+
+    /// Why the action failed, from its last failed attempt (for sequences,
+    /// from each step that failed, or where it was stopped); `None` on
+    /// success.
+    pub fn error(&self) -> Option<String> {
+        if self.success {
+            return None;
+        }
+        if self.steps.is_empty() {
+            return Some(describe_failure(&self.attempts));
+        }
+        let failed: Vec<String> = self
+            .steps
+            .iter()
+            .enumerate()
+            .filter(|(_, step)| step.started_at.is_some() && !step.success)
+            .map(|(index, step)| {
+                format!(
+                    "step {} ({}): {}",
+                    index + 1,
+                    step.action.key(),
+                    describe_failure(&step.attempts)
+                )
+            })
+            .collect();
+        if failed.is_empty() {
+            // Stopped early without a failed step.
+            let next = self
+                .steps
+                .iter()
+                .position(|step| step.started_at.is_none())
+                .unwrap_or(0);
+            return Some(format!("stopped before step {}", next + 1));
+        }
+        Some(failed.join("; "))
+    }
+}
+
+fn describe_failure(attempts: &[ActionAttempt]) -> String {
+    let Some(last) = attempts.iter().rev().find(|attempt| !attempt.success) else {
+        return "No method was attempted".to_string();
+    };
+    match (&last.stderr, last.exit_code) {
+        (Some(stderr), _) => format!("{}: {stderr}", last.method),
+        (None, Some(code)) => format!("{} exited with status {code}", last.method),
+        (None, None) => format!("{} failed", last.method),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(method: &str, success: bool) -> ActionAttempt {
+        ActionAttempt {
+            method: method.to_string(),
+            success,
+            exit_code: Some(if success { 0 } else { 1 }),
+            stderr: (!success).then(|| "denied".to_string()),
+            stdout: None,
+        }
+    }
+
+    fn timer(action: TimerAction, message: Option<&str>) -> TimerInfo {
+        TimerInfo {
+            message: message.map(str::to_string),
+            ..TimerInfo::fixture(action, chrono::Utc::now())
+        }
+    }
+
+    #[test]
+    fn fallbacks_stop_at_the_first_success() {
+        let outcome = ActionOutcome::first_success(&[
+            &|| attempt("shortcut", false),
+            &|| attempt("screen-saver", true),
+            &|| panic!("not reached"),
+        ]);
+        assert!(outcome.success);
+        assert_eq!(outcome.attempts.len(), 2);
+        assert_eq!(outcome.error(), None);
+    }
+
+    #[test]
+    fn failure_reports_the_last_attempt() {
+        let outcome = ActionOutcome::first_success(&[&|| attempt("shortcut", false), &|| {
+            attempt("pmset", false)
+        }]);
+        assert!(!outcome.success);
+        assert_eq!(outcome.error(), Some("pmset: denied".to_string()));
+
+        let missing =
+            ActionAttempt::from_output("osascript", Err(io::Error::from(io::ErrorKind::NotFound)));
+        assert!(!missing.success);
+        assert!(missing.stderr.unwrap().starts_with("Failed to start"));
+    }
+
+    #[test]
+    fn dispatches_actions_to_backend_methods() {
+        let backend = RecordingBackend::new();
+        assert!(run_action(&backend, &timer(TimerAction::Lock, None)).success);
+        run_action(&backend, &timer(TimerAction::Popup, Some("  ")));
+        run_action(&backend, &timer(TimerAction::Popup, Some("Stand up")));
+        run_action(&backend, &timer(TimerAction::Sleep, None));
+        let mut standup = timer(TimerAction::Open, None);
+        standup.open_target = Some("https://meet.example.com/standup".to_string());
+        run_action(&backend, &standup);
+        let missing = run_action(&backend, &timer(TimerAction::RunCommand, None));
+        assert_eq!(
+            missing.error(),
+            Some("command: Timer has no command to run".to_string())
+        );
+        let nothing_to_close = run_action(&backend, &timer(TimerAction::CloseApps, None));
+        assert_eq!(
+            nothing_to_close.error(),
+            Some("close: Timer has no apps to close".to_string())
+        );
+
+        backend.set_failure(Some("denied"));
+        let failed = run_action(&backend, &timer(TimerAction::Reboot, None));
+        assert_eq!(failed.error(), Some("recording:Reboot: denied".to_string()));
+
+        assert_eq!(
+            backend.calls(),
+            vec![
+                RecordedCall::Lock,
+                RecordedCall::Popup(DEFAULT_POPUP_MESSAGE.to_string()),
+                RecordedCall::Popup("Stand up".to_string()),
+                RecordedCall::Sleep,
+                RecordedCall::Open("https://meet.example.com/standup".to_string()),
+                RecordedCall::Reboot,
+            ]
+        );
+    }
+}
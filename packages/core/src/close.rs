@@ -0,0 +1,231 @@
+//! `CloseApps` timers: ask each app to quit, give them a grace period, then
+//! force-kill whatever is still running.
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::model::{ActionAttempt, ActionOutcome, CloseAppsSpec, TimerInfo};
+
+/// Grace period used when a timer doesn't set one.
+pub const DEFAULT_CLOSE_GRACE_SECONDS: u32 = 10;
+pub const MAX_CLOSE_GRACE_SECONDS: u32 = 5 * 60;
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Platform hooks for finding and stopping an app's processes. Apps are
+/// named by process name, or by bundle id where the platform has them.
+pub trait AppCloser {
+    /// Ids of the app's running processes.
+    fn running(&self, app: &str) -> Result<Vec<u32>, String>;
+    /// Ask the app to quit, letting it save and clean up.
+    fn quit(&self, app: &str, pids: &[u32]) -> Result<(), String>;
+    fn kill(&self, pids: &[u32]) -> Result<(), String>;
+}
+
+/// Trim and de-duplicate the requested apps, filling in the default grace
+/// period.
+pub fn normalize_close_apps(spec: Option<CloseAppsSpec>) -> Result<CloseAppsSpec, String> {
+    let mut spec = spec.unwrap_or_default();
+    let mut apps: Vec<String> = Vec::new();
+    for app in spec
+        .apps
+        .iter()
+        .map(|app| app.trim())
+        .filter(|app| !app.is_empty())
+    {
+        if !apps.iter().any(|seen| seen.eq_ignore_ascii_case(app)) {
+            apps.push(app.to_string());
+        }
+    }
+    if apps.is_empty() {
+        return Err("Close apps needs at least one app.".to_string());
+    }
+    if apps
+        .iter()
+        .any(|app| app.chars().any(|ch| ch.is_control() || ch == '"'))
+    {
+        return Err("App names must not contain quotes or control characters.".to_string());
+    }
+    spec.apps = apps;
+
+    let grace = spec.grace_seconds.unwrap_or(DEFAULT_CLOSE_GRACE_SECONDS);
+    if grace > MAX_CLOSE_GRACE_SECONDS {
+        return Err(format!(
+            "Grace period must be at most {MAX_CLOSE_GRACE_SECONDS} seconds."
+        ));
+    }
+    spec.grace_seconds = Some(grace);
+    Ok(spec)
+}
+
+/// Apps a run of the timer will close, including those closed by steps of a
+/// sequence, for the pre-warning to list.
+pub fn apps_to_close(info: &TimerInfo) -> Vec<String> {
+    let steps = info
+        .steps
+        .iter()
+        .flatten()
+        .filter_map(|step| step.close_apps.as_ref());
+    let mut apps: Vec<String> = Vec::new();
+    for app in info
+        .close_apps
+        .iter()
+        .chain(steps)
+        .flat_map(|spec| &spec.apps)
+    {
+        if !apps.contains(app) {
+            apps.push(app.clone());
+        }
+    }
+    apps
+}
+
+/// Close every app in `spec`, reporting one attempt per app: `quit:` when
+/// it closed on request, `force-quit:` when it had to be killed, and
+/// `not-running:` when there was nothing to close.
+pub fn close_apps(closer: &dyn AppCloser, spec: &CloseAppsSpec) -> ActionOutcome {
+    let mut results: Vec<Option<ActionAttempt>> = Vec::new();
+    let mut quitting = Vec::new();
+    for (index, app) in spec.apps.iter().enumerate() {
+        results.push(match closer.running(app) {
+            Ok(pids) if pids.is_empty() => Some(ActionAttempt::from_result(
+                &format!("not-running:{app}"),
+                Ok(()),
+            )),
+            Ok(pids) => {
+                // A refused request still gets the force-kill below.
+                let _ = closer.quit(app, &pids);
+                quitting.push(index);
+                None
+            }
+            Err(err) => Some(ActionAttempt::from_result(&format!("quit:{app}"), Err(err))),
+        });
+    }
+
+    let grace =
+        Duration::from_secs(spec.grace_seconds.unwrap_or(DEFAULT_CLOSE_GRACE_SECONDS) as u64);
+    let deadline = Instant::now() + grace;
+    loop {
+        quitting.retain(|&index| {
+            let app = &spec.apps[index];
+            match closer.running(app) {
+                Ok(pids) if !pids.is_empty() => true,
+                result => {
+                    let result = result.map(|_| ());
+                    results[index] =
+                        Some(ActionAttempt::from_result(&format!("quit:{app}"), result));
+                    false
+                }
+            }
+        });
+        if quitting.is_empty() || Instant::now() >= deadline {
+            break;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    for index in quitting {
+        let app = &spec.apps[index];
+        let result = closer
+            .running(app)
+            .and_then(|pids| closer.kill(&pids))
+            .and_then(|_| match closer.running(app) {
+                Ok(pids) if !pids.is_empty() => {
+                    Err(format!("Still running after kill (pids {pids:?})"))
+                }
+                result => result.map(|_| ()),
+            });
+        results[index] = Some(ActionAttempt::from_result(
+            &format!("force-quit:{app}"),
+            result,
+        ));
+    }
+
+    ActionOutcome::all(results.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use super::*;
+
+    /// Apps by name with their pids; `stubborn` ones ignore quit requests.
+    struct FakeCloser {
+        running: Mutex<HashMap<String, Vec<u32>>>,
+        stubborn: Vec<&'static str>,
+    }
+
+    impl AppCloser for FakeCloser {
+        fn running(&self, app: &str) -> Result<Vec<u32>, String> {
+            Ok(self
+                .running
+                .lock()
+                .unwrap()
+                .get(app)
+                .cloned()
+                .unwrap_or_default())
+        }
+
+        fn quit(&self, app: &str, _pids: &[u32]) -> Result<(), String> {
+            if !self.stubborn.contains(&app) {
+                self.running.lock().unwrap().remove(app);
+            }
+            Ok(())
+        }
+
+        fn kill(&self, pids: &[u32]) -> Result<(), String> {
+            self.running
+                .lock()
+                .unwrap()
+                .retain(|_, running| !running.iter().any(|pid| pids.contains(pid)));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn normalizes_the_app_list() {
+        let spec = normalize_close_apps(Some(CloseAppsSpec {
+            apps: vec![
+                " Slack ".to_string(),
+                "slack".to_string(),
+                "".to_string(),
+                "steam".to_string(),
+            ],
+            grace_seconds: None,
+        }))
+        .unwrap();
+        assert_eq!(spec.apps, vec!["Slack", "steam"]);
+        assert_eq!(spec.grace_seconds, Some(DEFAULT_CLOSE_GRACE_SECONDS));
+        assert!(normalize_close_apps(None).is_err());
+    }
+
+    #[test]
+    fn quits_then_force_kills_and_reports_each_app() {
+        let closer = FakeCloser {
+            running: Mutex::new(HashMap::from([
+                ("Slack".to_string(), vec![10]),
+                ("steam".to_string(), vec![20, 21]),
+            ])),
+            stubborn: vec!["steam"],
+        };
+        let spec = CloseAppsSpec {
+            apps: vec!["Slack".to_string(), "Code".to_string(), "steam".to_string()],
+            grace_seconds: Some(0),
+        };
+
+        let outcome = close_apps(&closer, &spec);
+        let methods: Vec<&str> = outcome
+            .attempts
+            .iter()
+            .map(|attempt| attempt.method.as_str())
+            .collect();
+        assert_eq!(
+            methods,
+            vec!["quit:Slack", "not-running:Code", "force-quit:steam"]
+        );
+        assert!(outcome.success);
+        assert!(closer.running.lock().unwrap().is_empty());
+    }
+}
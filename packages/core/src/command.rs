@@ -0,0 +1,349 @@
+//! `RunCommand` timers: checking the requested program and running it with
+//! a timeout, keeping its exit code and output.
+
+use std::{
+    io::Read,
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::model::{ActionAttempt, CommandSpec};
+
+/// Timeout used when a command doesn't set one.
+pub const DEFAULT_COMMAND_TIMEOUT_SECONDS: u32 = 5 * 60;
+pub const MAX_COMMAND_TIMEOUT_SECONDS: u32 = 24 * 60 * 60;
+/// Output kept from each stream; longer output keeps its end.
+const MAX_OUTPUT_BYTES: usize = 4 * 1024;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How long to wait for output after the command exits, in case a child
+/// process it left behind still holds the pipes open.
+const OUTPUT_GRACE: Duration = Duration::from_secs(1);
+
+/// Trim and check a requested command, filling in the default timeout.
+pub fn normalize_command(command: Option<CommandSpec>) -> Result<CommandSpec, String> {
+    let mut command = command.ok_or_else(|| "Run command needs a program to run.".to_string())?;
+
+    command.program = command.program.trim().to_string();
+    if command.program.is_empty() {
+        return Err("Program must not be empty.".to_string());
+    }
+    if command.program.contains('\0') || command.args.iter().any(|arg| arg.contains('\0')) {
+        return Err("Program and arguments must not contain NUL characters.".to_string());
+    }
+    if command.shell && !command.args.is_empty() {
+        return Err("Shell commands take their arguments in the command line.".to_string());
+    }
+    if let Some(key) = command
+        .env
+        .keys()
+        .find(|key| key.is_empty() || key.contains('=') || key.contains('\0'))
+    {
+        return Err(format!("Invalid environment variable name: {key:?}"));
+    }
+
+    command.working_dir = command
+        .working_dir
+        .map(|dir| dir.trim().to_string())
+        .filter(|dir| !dir.is_empty());
+    if let Some(dir) = &command.working_dir {
+        if !Path::new(dir).is_dir() {
+            return Err(format!("Working directory does not exist: {dir}"));
+        }
+    }
+
+    let timeout = command
+        .timeout_seconds
+        .unwrap_or(DEFAULT_COMMAND_TIMEOUT_SECONDS);
+    if timeout == 0 || timeout > MAX_COMMAND_TIMEOUT_SECONDS {
+        return Err(format!(
+            "Command timeout must be between 1 and {MAX_COMMAND_TIMEOUT_SECONDS} seconds."
+        ));
+    }
+    command.timeout_seconds = Some(timeout);
+    Ok(command)
+}
+
+/// Run `command`, killing it and everything it started once its timeout
+/// passes.
+pub fn run_command(command: &CommandSpec) -> ActionAttempt {
+    let method = format!("command:{}", command.program);
+    let mut child = match build(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) => {
+            return ActionAttempt::from_result(&method, Err(format!("Failed to start: {err}")))
+        }
+    };
+
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+    let timeout = command
+        .timeout_seconds
+        .unwrap_or(DEFAULT_COMMAND_TIMEOUT_SECONDS);
+    let result = wait_with_timeout(&mut child, Duration::from_secs(timeout as u64));
+    let stdout = stdout.recv_timeout(OUTPUT_GRACE).ok().flatten();
+    let stderr = stderr.recv_timeout(OUTPUT_GRACE).ok().flatten();
+    match result {
+        Ok(status) => ActionAttempt {
+            method,
+            success: status.success(),
+            exit_code: status.code(),
+            stderr,
+            stdout,
+        },
+        // Keep what a killed command printed; it's often why it hung.
+        Err(err) => ActionAttempt {
+            method,
+            success: false,
+            exit_code: None,
+            stderr: Some(match stderr {
+                Some(output) => format!("{err}\n{output}"),
+                None => err,
+            }),
+            stdout,
+        },
+    }
+}
+
+fn build(command: &CommandSpec) -> Command {
+    let mut process = if command.shell {
+        shell_command(&command.program)
+    } else {
+        let mut process = Command::new(&command.program);
+        process.args(&command.args);
+        process
+    };
+    process.envs(&command.env);
+    if let Some(dir) = &command.working_dir {
+        process.current_dir(dir);
+    }
+    own_process_group(&mut process);
+    process
+}
+
+/// Start the command as the leader of a new process group, so a timeout can
+/// kill whatever it started along with it.
+#[cfg(unix)]
+fn own_process_group(process: &mut Command) {
+    use std::os::unix::process::CommandExt;
+
+    process.process_group(0);
+}
+
+/// `taskkill /T` follows the process tree, so no group is needed.
+#[cfg(not(unix))]
+fn own_process_group(_process: &mut Command) {}
+
+#[cfg(unix)]
+fn kill_tree(child: &mut Child) {
+    // A negative pid signals the whole group the child leads.
+    if let Ok(pid) = i32::try_from(child.id()) {
+        unsafe {
+            libc::kill(-pid, libc::SIGKILL);
+        }
+    }
+    let _ = child.kill();
+}
+
+#[cfg(windows)]
+fn kill_tree(child: &mut Child) {
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID"])
+        .arg(child.id().to_string())
+        .output();
+    let _ = child.kill();
+}
+
+#[cfg(not(any(unix, windows)))]
+fn kill_tree(child: &mut Child) {
+    let _ = child.kill();
+}
+
+#[cfg(windows)]
+fn shell_command(line: &str) -> Command {
+    let mut process = Command::new("cmd");
+    process.args(["/C", line]);
+    process
+}
+
+#[cfg(not(windows))]
+fn shell_command(line: &str) -> Command {
+    let mut process = Command::new("sh");
+    process.args(["-c", line]);
+    process
+}
+
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Duration,
+) -> Result<std::process::ExitStatus, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok(status),
+            Ok(None) if Instant::now() >= deadline => {
+                kill_tree(child);
+                let _ = child.wait();
+                return Err(format!("Timed out after {} seconds", timeout.as_secs()));
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(err) => return Err(format!("Failed to wait for command: {err}")),
+        }
+    }
+}
+
+/// Read a pipe to the end on its own thread, sending back its tail.
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> mpsc::Receiver<Option<String>> {
+    let (tx, rx) = mpsc::channel();
+    if let Some(mut pipe) = pipe {
+        thread::spawn(move || {
+            let mut bytes = Vec::new();
+            let _ = pipe.read_to_end(&mut bytes);
+            let _ = tx.send(output_tail(&bytes));
+        });
+    }
+    rx
+}
+
+fn output_tail(bytes: &[u8]) -> Option<String> {
+    let start = bytes.len().saturating_sub(MAX_OUTPUT_BYTES);
+    let text = String::from_utf8_lossy(&bytes[start..]).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(program: &str) -> CommandSpec {
+        CommandSpec {
+            program: program.to_string(),
+            args: Vec::new(),
+            env: Default::default(),
+            working_dir: None,
+            timeout_seconds: None,
+            shell: false,
+        }
+    }
+
+    #[test]
+    fn validates_requested_commands() {
+        let normalized = normalize_command(Some(spec("  backup.sh "))).unwrap();
+        assert_eq!(normalized.program, "backup.sh");
+        assert_eq!(
+            normalized.timeout_seconds,
+            Some(DEFAULT_COMMAND_TIMEOUT_SECONDS)
+        );
+
+        assert!(normalize_command(None).is_err());
+        assert!(normalize_command(Some(spec(" "))).is_err());
+
+        let mut shell_with_args = spec("echo hi");
+        shell_with_args.shell = true;
+        shell_with_args.args = vec!["there".to_string()];
+        assert!(normalize_command(Some(shell_with_args)).is_err());
+
+        let mut bad_env = spec("true");
+        bad_env.env.insert("A=B".to_string(), "c".to_string());
+        assert!(normalize_command(Some(bad_env)).is_err());
+
+        let mut missing_dir = spec("true");
+        missing_dir.working_dir = Some("/definitely/not/here".to_string());
+        assert!(normalize_command(Some(missing_dir)).is_err());
+
+        let mut no_timeout = spec("true");
+        no_timeout.timeout_seconds = Some(0);
+        assert!(normalize_command(Some(no_timeout)).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn captures_exit_code_and_output() {
+        let dir = std::env::temp_dir();
+        let mut command = spec("printf \"$GREETING from $(pwd)\"; echo oops >&2; exit 3");
+        command.shell = true;
+        command
+            .env
+            .insert("GREETING".to_string(), "hello".to_string());
+        command.working_dir = Some(dir.to_string_lossy().to_string());
+
+        let attempt = run_command(&command);
+        assert!(!attempt.success);
+        assert_eq!(attempt.exit_code, Some(3));
+        assert_eq!(attempt.stderr.as_deref(), Some("oops"));
+        assert!(attempt.stdout.unwrap().starts_with("hello from /"));
+
+        let mut direct = spec("echo");
+        direct.args = vec!["$HOME".to_string(), "a b".to_string()];
+        assert_eq!(run_command(&direct).stdout.as_deref(), Some("$HOME a b"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kills_commands_that_time_out() {
+        let mut command = spec("sleep");
+        command.args = vec!["5".to_string()];
+        command.timeout_seconds = Some(1);
+
+        let started = Instant::now();
+        let attempt = run_command(&command);
+        assert!(started.elapsed() < Duration::from_secs(4));
+        assert!(!attempt.success);
+        assert_eq!(attempt.stderr.as_deref(), Some("Timed out after 1 seconds"));
+        assert!(run_command(&spec("no-such-program-lockpilot"))
+            .stderr
+            .unwrap()
+            .starts_with("Failed to start"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timed_out_commands_keep_their_output() {
+        let mut command = spec("echo started; echo waiting >&2; sleep 5");
+        command.shell = true;
+        command.timeout_seconds = Some(1);
+
+        let attempt = run_command(&command);
+        assert!(!attempt.success);
+        assert_eq!(attempt.exit_code, None);
+        assert_eq!(attempt.stdout.as_deref(), Some("started"));
+        assert_eq!(
+            attempt.stderr.as_deref(),
+            Some("Timed out after 1 seconds\nwaiting")
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn timeouts_also_kill_what_a_shell_command_started() {
+        let pid_file =
+            std::env::temp_dir().join(format!("lockpilot-command-{}", uuid::Uuid::new_v4()));
+        let mut command = spec(&format!(
+            "sleep 30 & echo $! > '{}'; wait",
+            pid_file.display()
+        ));
+        command.shell = true;
+        command.timeout_seconds = Some(1);
+
+        assert!(!run_command(&command).success);
+        let pid = std::fs::read_to_string(&pid_file)
+            .unwrap()
+            .trim()
+            .to_string();
+        let _ = std::fs::remove_file(&pid_file);
+        // Gone, or a zombie waiting to be reaped by init.
+        thread::sleep(Duration::from_millis(100));
+        let state = std::fs::read_to_string(format!("/proc/{pid}/stat")).unwrap_or_default();
+        assert!(
+            state.is_empty() || state.contains(") Z "),
+            "sleep {pid} still running: {state}"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Conditions checked just before a scheduled run, such as "only on AC
+//! power" or "not while a game is running", and what to do when one of
+//! them isn't met.
+
+use std::sync::Mutex;
+
+use crate::{
+    idle::IdleSource,
+    model::{PowerSource, TimerConditions, UnmetPolicy},
+    prewarning::format_offset,
+};
+
+/// Delay used by `Defer` when a timer doesn't set one.
+pub const DEFAULT_DEFER_MINUTES: u32 = 10;
+pub const MAX_CONDITION_MINUTES: u32 = 24 * 60;
+
+/// Platform hooks that report the machine's state for conditions and idle
+/// triggers.
+pub trait SystemProbe: IdleSource {
+    fn power_source(&self) -> Result<PowerSource, String>;
+    /// Whether a process named `name` is running.
+    fn is_running(&self, name: &str) -> Result<bool, String>;
+    /// Overall CPU use, from 0 to 100.
+    fn cpu_percent(&self) -> Result<f32, String>;
+}
+
+/// Drop empty conditions and check the limits of the rest; `None` when
+/// nothing is left to check.
+pub fn normalize_conditions(
+    conditions: Option<TimerConditions>,
+) -> Result<Option<TimerConditions>, String> {
+    let Some(mut conditions) = conditions else {
+        return Ok(None);
+    };
+
+    let mut processes: Vec<String> = Vec::new();
+    for name in conditions
+        .blocking_processes
+        .iter()
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+    {
+        if !processes.iter().any(|seen| seen.eq_ignore_ascii_case(name)) {
+            processes.push(name.to_string());
+        }
+    }
+    conditions.blocking_processes = processes;
+
+    if conditions
+        .min_idle_minutes
+        .is_some_and(|minutes| minutes == 0 || minutes > MAX_CONDITION_MINUTES)
+    {
+        return Err(format!(
+            "Idle time must be between 1 and {MAX_CONDITION_MINUTES} minutes."
+        ));
+    }
+    if conditions
+        .max_cpu_percent
+        .is_some_and(|percent| percent == 0 || percent > 100)
+    {
+        return Err("CPU limit must be between 1 and 100%.".to_string());
+    }
+    conditions.defer_minutes = match conditions.when_unmet {
+        UnmetPolicy::Defer => {
+            let minutes = conditions.defer_minutes.unwrap_or(DEFAULT_DEFER_MINUTES);
+            if minutes == 0 || minutes > MAX_CONDITION_MINUTES {
+                return Err(format!(
+                    "Defer time must be between 1 and {MAX_CONDITION_MINUTES} minutes."
+                ));
+            }
+            Some(minutes)
+        }
+        UnmetPolicy::Skip | UnmetPolicy::RunAnyway => None,
+    };
+
+    let empty = conditions.power.is_none()
+        && conditions.min_idle_minutes.is_none()
+        && conditions.blocking_processes.is_empty()
+        && conditions.max_cpu_percent.is_none();
+    Ok((!empty).then_some(conditions))
+}
+
+/// Why the run shouldn't go ahead, one reason per unmet condition; empty
+/// when every condition holds. A condition that can't be checked counts as
+/// unmet.
+pub fn unmet_conditions(probe: &dyn SystemProbe, conditions: &TimerConditions) -> Vec<String> {
+    let mut reasons = Vec::new();
+
+    if let Some(wanted) = conditions.power {
+        match probe.power_source() {
+            Ok(source) if source == wanted => {}
+            Ok(source) => reasons.push(format!(
+                "On {}, not {}",
+                power_label(source),
+                power_label(wanted)
+            )),
+            Err(err) => reasons.push(format!("Couldn't check the power source: {err}")),
+        }
+    }
+
+    if let Some(minutes) = conditions.min_idle_minutes {
+        match probe.idle_seconds() {
+            Ok(idle) if idle >= minutes as u64 * 60 => {}
+            Ok(idle) => reasons.push(format!(
+                "Idle for {}, not {}",
+                format_offset(idle.min(u32::MAX as u64) as u32),
+                format_offset(minutes * 60)
+            )),
+            Err(err) => reasons.push(format!("Couldn't check idle time: {err}")),
+        }
+    }
+
+    for name in &conditions.blocking_processes {
+        match probe.is_running(name) {
+            Ok(false) => {}
+            Ok(true) => reasons.push(format!("{name} is running")),
+            Err(err) => reasons.push(format!("Couldn't check for {name}: {err}")),
+        }
+    }
+
+    if let Some(limit) = conditions.max_cpu_percent {
+        match probe.cpu_percent() {
+            Ok(percent) if percent <= limit as f32 => {}
+            Ok(percent) => reasons.push(format!("CPU at {percent:.0}%, above {limit}%")),
+            Err(err) => reasons.push(format!("Couldn't check CPU use: {err}")),
+        }
+    }
+
+    reasons
+}
+
+fn power_label(source: PowerSource) -> &'static str {
+    match source {
+        PowerSource::Ac => "AC power",
+        PowerSource::Battery => "battery",
+    }
+}
+
+/// Machine state reported by a [`StaticProbe`].
+#[derive(Debug, Clone)]
+pub struct SystemState {
+    pub power: PowerSource,
+    pub idle_seconds: u64,
+    pub running: Vec<String>,
+    pub cpu_percent: f32,
+}
+
+impl Default for SystemState {
+    fn default() -> Self {
+        Self {
+            power: PowerSource::Ac,
+            idle_seconds: 0,
+            running: Vec::new(),
+            cpu_percent: 0.0,
+        }
+    }
+}
+
+/// Probe that reports whatever state it was given, so conditions can be
+/// exercised without looking at the machine.
+#[derive(Default)]
+pub struct StaticProbe {
+    state: Mutex<SystemState>,
+}
+
+impl StaticProbe {
+    pub fn new(state: SystemState) -> Self {
+        Self {
+            state: Mutex::new(state),
+        }
+    }
+
+    pub fn set(&self, state: SystemState) {
+        if let Ok(mut current) = self.state.lock() {
+            *current = state;
+        }
+    }
+
+    fn state(&self) -> Result<SystemState, String> {
+        self.state
+            .lock()
+            .map(|state| state.clone())
+            .map_err(|_| "Failed to lock probe state".to_string())
+    }
+}
+
+impl SystemProbe for StaticProbe {
+    fn power_source(&self) -> Result<PowerSource, String> {
+        Ok(self.state()?.power)
+    }
+
+    fn is_running(&self, name: &str) -> Result<bool, String> {
+        Ok(self
+            .state()?
+            .running
+            .iter()
+            .any(|running| running.eq_ignore_ascii_case(name)))
+    }
+
+    fn cpu_percent(&self) -> Result<f32, String> {
+        Ok(self.state()?.cpu_percent)
+    }
+}
+
+impl IdleSource for StaticProbe {
+    fn idle_seconds(&self) -> Result<u64, String> {
+        Ok(self.state()?.idle_seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conditions() -> TimerConditions {
+        TimerConditions {
+            power: Some(PowerSource::Ac),
+            min_idle_minutes: Some(10),
+            blocking_processes: vec![
+                " steam ".to_string(),
+                "Steam".to_string(),
+                "zoom".to_string(),
+            ],
+            max_cpu_percent: Some(50),
+            when_unmet: UnmetPolicy::Defer,
+            defer_minutes: None,
+        }
+    }
+
+    #[test]
+    fn normalizes_and_validates_conditions() {
+        let normalized = normalize_conditions(Some(conditions())).unwrap().unwrap();
+        assert_eq!(normalized.blocking_processes, vec!["steam", "zoom"]);
+        assert_eq!(normalized.defer_minutes, Some(DEFAULT_DEFER_MINUTES));
+
+        let nothing = TimerConditions {
+            blocking_processes: vec![" ".to_string()],
+            ..TimerConditions::default()
+        };
+        assert_eq!(normalize_conditions(Some(nothing)), Ok(None));
+        let too_busy = TimerConditions {
+            max_cpu_percent: Some(0),
+            ..conditions()
+        };
+        assert!(normalize_conditions(Some(too_busy)).is_err());
+    }
+
+    #[test]
+    fn reports_each_unmet_condition() {
+        let conditions = normalize_conditions(Some(conditions())).unwrap().unwrap();
+        let probe = StaticProbe::new(SystemState {
+            power: PowerSource::Ac,
+            idle_seconds: 15 * 60,
+            running: Vec::new(),
+            cpu_percent: 12.0,
+        });
+        assert!(unmet_conditions(&probe, &conditions).is_empty());
+
+        probe.set(SystemState {
+            power: PowerSource::Battery,
+            idle_seconds: 120,
+            running: vec!["zoom".to_string()],
+            cpu_percent: 87.4,
+        });
+        assert_eq!(
+            unmet_conditions(&probe, &conditions),
+            vec![
+                "On battery, not AC power",
+                "Idle for 2m, not 10m",
+                "zoom is running",
+                "CPU at 87%, above 50%",
+            ]
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Five-field cron expressions: `minute hour day-of-month month day-of-week`.
+//!
+//! Fields accept `*`, numbers, `a-b` ranges, `/n` steps, comma lists and
+//! three-letter month/weekday names; day-of-week takes 0-7 with both 0 and 7
+//! meaning Sunday. As in Vixie cron, when both day fields are restricted a day
+//! matches if either one does. Several expressions can be joined with `;` and
+//! the earliest match wins. Times are wall-clock times in the recurrence's
+//! zone and go through the same DST rules as the day-based presets.
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+use crate::recurrence::resolve_local;
+
+/// How far ahead to look before deciding an expression never matches; covers
+/// leap-day schedules.
+const SEARCH_DAYS: u32 = 366 * 8;
+
+const MONTH_NAMES: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    specs: Vec<CronSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CronSpec {
+    minutes: Field,
+    hours: Field,
+    days_of_month: Field,
+    months: Field,
+    days_of_week: Field,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+/// Bit set of the values a field allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Field(u64);
+
+impl Field {
+    fn contains(self, value: u32) -> bool {
+        value < 64 && self.0 & (1 << value) != 0
+    }
+
+    fn values(self) -> impl Iterator<Item = u32> {
+        (0..64).filter(move |value| self.contains(*value))
+    }
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let specs = expression
+            .split(';')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .map(CronSpec::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if specs.is_empty() {
+            return Err("Cron expression is empty.".to_string());
+        }
+        Ok(Self { specs })
+    }
+
+    /// First run strictly after `after`, or `None` if nothing matches.
+    pub fn next_after(&self, tz: &Tz, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.specs
+            .iter()
+            .filter_map(|spec| spec.next_after(tz, after))
+            .min()
+    }
+}
+
+impl CronSpec {
+    fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.to_ascii_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            "@weekly" => "0 0 * * 0".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@hourly" => "0 * * * *".to_string(),
+            _ => expression.to_string(),
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields.as_slice() else {
+            return Err(format!(
+                "Cron expression \"{expression}\" must have 5 fields: minute hour day-of-month month day-of-week."
+            ));
+        };
+
+        let mut days_of_week = parse_field(day_of_week, "day-of-week", 0, 7, WEEKDAY_NAMES)?;
+        if days_of_week.contains(7) {
+            days_of_week = Field((days_of_week.0 | 1) & !(1 << 7));
+        }
+
+        Ok(Self {
+            minutes: parse_field(minute, "minute", 0, 59, &[])?,
+            hours: parse_field(hour, "hour", 0, 23, &[])?,
+            days_of_month: parse_field(day_of_month, "day-of-month", 1, 31, &[])?,
+            months: parse_field(month, "month", 1, 12, MONTH_NAMES)?,
+            days_of_week,
+            any_day_of_month: day_of_month.starts_with('*'),
+            any_day_of_week: day_of_week.starts_with('*'),
+        })
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if !self.months.contains(date.month()) {
+            return false;
+        }
+        let day_of_month = self.days_of_month.contains(date.day());
+        let day_of_week = self
+            .days_of_week
+            .contains(date.weekday().num_days_from_sunday());
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (true, true) => true,
+            (true, false) => day_of_week,
+            (false, true) => day_of_month,
+            (false, false) => day_of_month || day_of_week,
+        }
+    }
+
+    fn next_after(&self, tz: &Tz, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut date = after.with_timezone(tz).date_naive();
+        for _ in 0..SEARCH_DAYS {
+            if self.matches_day(date) {
+                // DST shifts can reorder wall-clock times, so take the earliest.
+                let earliest = self
+                    .hours
+                    .values()
+                    .flat_map(|hour| self.minutes.values().map(move |minute| (hour, minute)))
+                    .filter_map(|(hour, minute)| date.and_hms_opt(hour, minute, 0))
+                    .map(|local| resolve_local(tz, local))
+                    .filter(|candidate| *candidate > after)
+                    .min();
+                if earliest.is_some() {
+                    return earliest;
+                }
+            }
+            date += ChronoDuration::days(1);
+        }
+        None
+    }
+}
+
+fn parse_field(
+    text: &str,
+    label: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> Result<Field, String> {
+    let mut bits = 0u64;
+    for part in text.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("Invalid step \"{step}\" in cron {label} field."))?;
+                (range, Some(step))
+            }
+            None => (part, None),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, label, min, max, names)?,
+                parse_value(end, label, min, max, names)?,
+            )
+        } else {
+            let value = parse_value(range, label, min, max, names)?;
+            // "5/15" means every 15 starting at 5.
+            (value, if step.is_some() { max } else { value })
+        };
+
+        if start > end {
+            return Err(format!("Invalid range \"{range}\" in cron {label} field."));
+        }
+
+        for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(Field(bits))
+}
+
+fn parse_value(text: &str, label: &str, min: u32, max: u32, names: &[&str]) -> Result<u32, String> {
+    let lower = text.to_ascii_lowercase();
+    let value = match names.iter().position(|name| *name == lower) {
+        Some(index) => index as u32 + min,
+        None => lower
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid value \"{text}\" in cron {label} field."))?,
+    };
+
+    if (min..=max).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!(
+            "Cron {label} value {value} is out of range ({min}-{max})."
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Weekday};
+
+    const NEW_YORK: Tz = chrono_tz::America::New_York;
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        NEW_YORK
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn runs(expression: &str, from: DateTime<Utc>, count: usize) -> Vec<DateTime<Utc>> {
+        let schedule = CronSchedule::parse(expression).unwrap();
+        let mut after = from;
+        (0..count)
+            .map(|_| {
+                after = schedule.next_after(&NEW_YORK, after).unwrap();
+                after
+            })
+            .collect()
+    }
+
+    #[test]
+    fn joined_expressions_take_the_earliest_match() {
+        // Friday 16 October 2026, 13:00.
+        let from = local(2026, 10, 16, 13, 0);
+        assert_eq!(
+            runs("30 12 * * mon-fri; 45 17 * * mon-fri", from, 3),
+            vec![
+                local(2026, 10, 16, 17, 45),
+                local(2026, 10, 19, 12, 30),
+                local(2026, 10, 19, 17, 45),
+            ]
+        );
+    }
+
+    #[test]
+    fn steps_within_an_hour_range() {
+        let from = local(2026, 10, 16, 16, 40);
+        assert_eq!(
+            runs("*/15 9-16 * * *", from, 2),
+            vec![local(2026, 10, 16, 16, 45), local(2026, 10, 17, 9, 0)]
+        );
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 13th, or any Friday; 13 December 2026 is a Sunday.
+        let from = local(2026, 12, 1, 0, 0);
+        let found = runs("0 9 13 * fri", from, 4);
+        assert_eq!(found[0], local(2026, 12, 4, 9, 0));
+        assert_eq!(found[1], local(2026, 12, 11, 9, 0));
+        assert_eq!(found[2], local(2026, 12, 13, 9, 0));
+        assert_eq!(found[2].with_timezone(&NEW_YORK).weekday(), Weekday::Sun);
+        assert_eq!(found[3], local(2026, 12, 18, 9, 0));
+    }
+
+    #[test]
+    fn skipped_wall_clock_time_runs_after_the_gap() {
+        let from = local(2026, 3, 7, 12, 0);
+        let found = runs("30 2 * * *", from, 2);
+        assert_eq!(
+            found[0],
+            Utc.with_ymd_and_hms(2026, 3, 8, 7, 30, 0).unwrap()
+        );
+        assert_eq!(found[1], local(2026, 3, 9, 2, 30));
+    }
+
+    #[test]
+    fn sunday_is_zero_or_seven_and_macros_expand() {
+        assert_eq!(
+            CronSchedule::parse("0 8 * * 7").unwrap(),
+            CronSchedule::parse("0 8 * * sun").unwrap()
+        );
+        assert_eq!(
+            CronSchedule::parse("@daily").unwrap(),
+            CronSchedule::parse("0 0 * * *").unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        assert!(CronSchedule::parse("").is_err());
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("0 17-9 * * *").is_err());
+        assert!(CronSchedule::parse("0 9 * foo *").is_err());
+    }
+
+    #[test]
+    fn impossible_dates_never_match() {
+        let schedule = CronSchedule::parse("0 0 30 feb *").unwrap();
+        assert_eq!(
+            schedule.next_after(&NEW_YORK, local(2026, 1, 1, 0, 0)),
+            None
+        );
+    }
+}
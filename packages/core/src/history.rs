@@ -0,0 +1,200 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use crate::model::{HistoryEntry, HistoryEvent, HistoryFilter};
+
+/// Append-only log of timer events, one JSON object per line in
+/// `history.jsonl`.
+#[derive(Clone)]
+pub struct HistoryStore {
+    storage_path: Arc<PathBuf>,
+    /// Serializes appends from action threads with clears.
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl HistoryStore {
+    pub fn new(storage_path: PathBuf) -> Self {
+        Self {
+            storage_path: Arc::new(storage_path),
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn append(&self, entry: &HistoryEntry) -> Result<(), String> {
+        let _guard = self
+            .write_lock
+            .lock()
+            .map_err(|_| "Failed to lock history log".to_string())?;
+
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to create history storage directory: {err}"))?;
+        }
+
+        let mut line = serde_json::to_string(entry)
+            .map_err(|err| format!("Failed to encode history entry: {err}"))?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.storage_path.as_ref())
+            .map_err(|err| format!("Failed to open history log: {err}"))?;
+        file.write_all(line.as_bytes())
+            .map_err(|err| format!("Failed to write history entry: {err}"))
+    }
+
+    /// Entries matching `filter`, newest first.
+    pub fn list(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>, String> {
+        let mut entries: Vec<HistoryEntry> = self
+            .read_all()?
+            .into_iter()
+            .filter(|entry| matches(filter, entry))
+            .collect();
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Remove the entries matching `filter`, returning how many were removed.
+    /// Wrong-PIN records are kept, so clearing can't hide attempts to get
+    /// past a locked timer.
+    pub fn clear(&self, filter: &HistoryFilter) -> Result<usize, String> {
+        let _guard = self
+            .write_lock
+            .lock()
+            .map_err(|_| "Failed to lock history log".to_string())?;
+
+        let entries = self.read_all()?;
+        let total = entries.len();
+        let kept: Vec<HistoryEntry> = entries
+            .into_iter()
+            .filter(|entry| entry.event == HistoryEvent::PinRejected || !matches(filter, entry))
+            .collect();
+        let removed = total - kept.len();
+        if removed == 0 {
+            return Ok(0);
+        }
+
+        let mut data = String::new();
+        for entry in &kept {
+            data.push_str(
+                &serde_json::to_string(entry)
+                    .map_err(|err| format!("Failed to encode history entry: {err}"))?,
+            );
+            data.push('\n');
+        }
+        fs::write(self.storage_path.as_ref(), data)
+            .map_err(|err| format!("Failed to write history log: {err}"))?;
+        Ok(removed)
+    }
+
+    /// Every entry in file order. Lines that no longer parse are skipped.
+    fn read_all(&self) -> Result<Vec<HistoryEntry>, String> {
+        if !self.storage_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let raw = fs::read_to_string(self.storage_path.as_ref())
+            .map_err(|err| format!("Failed to read history log: {err}"))?;
+        Ok(raw
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+fn matches(filter: &HistoryFilter, entry: &HistoryEntry) -> bool {
+    filter
+        .timer_id
+        .as_ref()
+        .map_or(true, |id| *id == entry.timer_id)
+        && filter
+            .action
+            .as_ref()
+            .map_or(true, |action| *action == entry.action)
+        && filter.from.map_or(true, |from| entry.recorded_at >= from)
+        && filter.to.map_or(true, |to| entry.recorded_at < to)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration as ChronoDuration, Utc};
+
+    use super::*;
+    use crate::model::TimerAction;
+
+    fn entry(timer_id: &str, action: TimerAction, recorded_at: DateTime<Utc>) -> HistoryEntry {
+        HistoryEntry {
+            timer_id: timer_id.to_string(),
+            action,
+            event: HistoryEvent::Executed,
+            scheduled_time: Some(recorded_at),
+            decision: None,
+            error: None,
+            outcome: None,
+            detail: None,
+            recorded_at,
+        }
+    }
+
+    #[test]
+    fn lists_and_clears_by_timer_action_and_date() {
+        let dir = std::env::temp_dir().join(format!("lockpilot-history-{}", uuid::Uuid::new_v4()));
+        let store = HistoryStore::new(dir.join("history.jsonl"));
+        let start = Utc::now() - ChronoDuration::days(3);
+        let day = |n: i64| start + ChronoDuration::days(n);
+
+        store
+            .append(&entry("a", TimerAction::Lock, day(0)))
+            .unwrap();
+        store
+            .append(&entry("b", TimerAction::Shutdown, day(1)))
+            .unwrap();
+        store
+            .append(&entry("a", TimerAction::Lock, day(2)))
+            .unwrap();
+
+        let all = store.list(&HistoryFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].recorded_at, day(2));
+
+        let timer_a = HistoryFilter {
+            timer_id: Some("a".to_string()),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(store.list(&timer_a).unwrap().len(), 2);
+
+        let shutdowns = HistoryFilter {
+            action: Some(TimerAction::Shutdown),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(store.list(&shutdowns).unwrap().len(), 1);
+
+        let first_two_days = HistoryFilter {
+            from: Some(day(0)),
+            to: Some(day(2)),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(store.clear(&first_two_days).unwrap(), 2);
+        let left = store.list(&HistoryFilter::default()).unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].recorded_at, day(2));
+
+        store
+            .append(&HistoryEntry {
+                event: HistoryEvent::PinRejected,
+                ..entry("a", TimerAction::Lock, day(2))
+            })
+            .unwrap();
+        assert_eq!(store.clear(&HistoryFilter::default()).unwrap(), 1);
+        let left = store.list(&HistoryFilter::default()).unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].event, HistoryEvent::PinRejected);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,224 @@
+//! Holiday calendars read from local iCalendar (`.ics`) files.
+//!
+//! Only the parts that place all-day events are used: each `VEVENT`'s
+//! `DTSTART`, an optional exclusive `DTEND`, and an optional `RRULE` for
+//! holidays that repeat (usually `FREQ=YEARLY`). Events using anything
+//! else, such as an RRULE part LockPilot doesn't support, are skipped.
+
+use std::{
+    collections::HashMap,
+    fs,
+    sync::{Arc, Mutex, OnceLock},
+    time::SystemTime,
+};
+
+use chrono::{Duration as ChronoDuration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::rrule::RRule;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HolidayCalendar {
+    events: Vec<Holiday>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Holiday {
+    start: NaiveDate,
+    /// Length in days; at least 1.
+    days: i64,
+    rule: Option<RRule>,
+}
+
+/// An event being read: its dates and rule so far, or `None` once a part
+/// of it couldn't be used.
+type PendingEvent = Option<(Option<NaiveDate>, Option<NaiveDate>, Option<RRule>)>;
+
+/// Parsed calendars by path, with the modification time they were read at.
+type CalendarCache = Mutex<HashMap<String, (SystemTime, Arc<HolidayCalendar>)>>;
+
+impl HolidayCalendar {
+    /// Read and parse the calendar at `path`. Calendars are kept until the
+    /// file changes, since every run computed for a timer checks them.
+    pub fn load(path: &str) -> Result<Arc<Self>, String> {
+        static CACHE: OnceLock<CalendarCache> = OnceLock::new();
+        let cache = CACHE.get_or_init(Default::default);
+
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|err| format!("Failed to read holiday calendar: {err}"))?;
+        if let Some((read_at, calendar)) =
+            cache.lock().ok().and_then(|cache| cache.get(path).cloned())
+        {
+            if read_at == modified {
+                return Ok(calendar);
+            }
+        }
+
+        let raw = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read holiday calendar: {err}"))?;
+        let calendar = Arc::new(Self::parse(&raw)?);
+        if let Ok(mut cache) = cache.lock() {
+            cache.insert(path.to_string(), (modified, calendar.clone()));
+        }
+        Ok(calendar)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut events = Vec::new();
+        let mut current: Option<PendingEvent> = None;
+
+        for line in unfold(text) {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            // Parameters such as `;VALUE=DATE` follow the property name.
+            let property = name.split(';').next().unwrap_or(name).to_ascii_uppercase();
+            match (property.as_str(), current.as_mut()) {
+                ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                    current = Some(Some((None, None, None)));
+                }
+                ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                    if let Some(Some((Some(start), end, rule))) = current.take() {
+                        let days = end.map_or(1, |end| (end - start).num_days().max(1));
+                        events.push(Holiday { start, days, rule });
+                    }
+                }
+                (property, Some(pending)) => {
+                    let Some(event) = pending.as_mut() else {
+                        continue;
+                    };
+                    let read = match property {
+                        "DTSTART" => parse_ics_date(value).map(|date| event.0 = Some(date)),
+                        "DTEND" => parse_ics_date(value).map(|date| event.1 = Some(date)),
+                        "RRULE" => RRule::parse(value).map(|rule| event.2 = Some(rule)),
+                        _ => Ok(()),
+                    };
+                    if read.is_err() {
+                        *pending = None;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if events.is_empty() {
+            return Err("Holiday calendar has no events LockPilot can use.".to_string());
+        }
+        Ok(Self { events })
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.events.iter().any(|event| event.covers(date))
+    }
+}
+
+impl Holiday {
+    fn covers(&self, date: NaiveDate) -> bool {
+        (0..self.days).any(|offset| self.starts_on(date - ChronoDuration::days(offset)))
+    }
+
+    fn starts_on(&self, date: NaiveDate) -> bool {
+        let Some(rule) = self.rule.as_ref() else {
+            return date == self.start;
+        };
+        if date < self.start {
+            return false;
+        }
+        // Dates are expanded in UTC at midnight, so no DST shifts apply.
+        let midnight = Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN));
+        rule.next_after(
+            &Tz::UTC,
+            self.start.and_time(NaiveTime::MIN),
+            midnight - ChronoDuration::seconds(1),
+        )
+        .is_some_and(|next| next.date_naive() == date)
+    }
+}
+
+/// Lines with RFC 5545 folding undone (continuations start with a space or tab).
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (
+            line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')),
+            lines.last_mut(),
+        ) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.trim_end().to_string()),
+        }
+    }
+    lines
+}
+
+/// Date part of an iCalendar `DATE` or `DATE-TIME` value.
+fn parse_ics_date(value: &str) -> Result<NaiveDate, String> {
+    let value = value.trim();
+    value
+        .get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .ok_or_else(|| format!("Invalid date in holiday calendar: {value}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Christmas Day\r\n\
+DTSTART;VALUE=DATE:20201225\r\n\
+RRULE:FREQ=YEARLY\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Company shutdown\r\n\
+DTSTART;VALUE=DATE:20261228\r\n\
+DTEND;VALUE=DATE:2026\r\n \
+1231\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Thanksgiving\r\n\
+DTSTART:20201126T000000Z\r\n\
+RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=4TH\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn matches_single_multi_day_and_repeating_events() {
+        let calendar = HolidayCalendar::parse(CALENDAR).unwrap();
+
+        assert!(calendar.contains(ymd(2026, 12, 25)));
+        assert!(calendar.contains(ymd(2031, 12, 25)));
+        assert!(!calendar.contains(ymd(2019, 12, 25)));
+
+        // DTEND is exclusive.
+        assert!(calendar.contains(ymd(2026, 12, 28)));
+        assert!(calendar.contains(ymd(2026, 12, 30)));
+        assert!(!calendar.contains(ymd(2026, 12, 31)));
+
+        assert!(calendar.contains(ymd(2026, 11, 26)));
+        assert!(!calendar.contains(ymd(2026, 11, 19)));
+    }
+
+    #[test]
+    fn skips_events_it_cannot_use() {
+        let calendar = HolidayCalendar::parse(
+            "BEGIN:VEVENT\nDTSTART;VALUE=DATE:20260101\nRRULE:FREQ=YEARLY;WKST=SU;BYSETPOS=1\nEND:VEVENT\n\
+             BEGIN:VEVENT\nDTSTART;VALUE=DATE:20260704\nEND:VEVENT\n",
+        )
+        .unwrap();
+        assert!(calendar.contains(ymd(2026, 7, 4)));
+        assert!(!calendar.contains(ymd(2026, 1, 1)));
+    }
+
+    #[test]
+    fn rejects_calendars_without_usable_events() {
+        assert!(HolidayCalendar::parse("BEGIN:VCALENDAR\nEND:VCALENDAR\n").is_err());
+        assert!(HolidayCalendar::parse("BEGIN:VEVENT\nSUMMARY:No date\nEND:VEVENT\n").is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! Idle triggers: timers that run once there has been no keyboard or mouse
+//! input for a while, such as "lock after 5 minutes away", rather than at a
+//! set time.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::model::IdleTrigger;
+
+pub const MAX_IDLE_MINUTES: u32 = 24 * 60;
+/// How often a timer that has run checks whether the user is back, and how
+/// long it waits to retry when idle time can't be read.
+pub const IDLE_POLL_SECONDS: u64 = 10;
+
+/// Longest the check made when an idle timer is saved waits for a reading.
+pub const IDLE_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Platform hook that reports how long the user has been away.
+pub trait IdleSource: Send + Sync {
+    /// Seconds since the last keyboard or mouse input.
+    fn idle_seconds(&self) -> Result<u64, String>;
+}
+
+/// An idle-time reading and when it was taken.
+type Reading = (Instant, Result<u64, String>);
+
+/// The latest idle-time reading, shared so that saving an idle timer can
+/// reuse one the scheduler took moments ago instead of asking again.
+#[derive(Clone, Default)]
+pub(crate) struct IdleReadings {
+    latest: Arc<Mutex<Option<Reading>>>,
+}
+
+impl IdleReadings {
+    pub(crate) fn record(&self, idle: &Result<u64, String>) {
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = Some((Instant::now(), idle.clone()));
+        }
+    }
+
+    /// The latest reading, if taken within the last poll interval.
+    pub(crate) fn recent(&self) -> Option<Result<u64, String>> {
+        let latest = self.latest.lock().ok()?;
+        latest
+            .as_ref()
+            .filter(|(at, _)| at.elapsed() < Duration::from_secs(IDLE_POLL_SECONDS))
+            .map(|(_, idle)| idle.clone())
+    }
+}
+
+pub fn normalize_idle_trigger(trigger: Option<IdleTrigger>) -> Result<Option<IdleTrigger>, String> {
+    match trigger {
+        Some(trigger) if trigger.minutes == 0 || trigger.minutes > MAX_IDLE_MINUTES => Err(
+            format!("Idle time must be between 1 and {MAX_IDLE_MINUTES} minutes."),
+        ),
+        trigger => Ok(trigger),
+    }
+}
+
+/// What an idle timer does after finding the user idle for `idle_seconds`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleCheck {
+    /// Idle long enough: run the action.
+    Run,
+    /// Check again after `after`. A timer is `armed` until it runs, and
+    /// re-armed once there has been input since.
+    Wait { armed: bool, after: Duration },
+}
+
+/// Step an idle timer waiting for `trigger`. It runs once per stretch of
+/// idleness: after a run it waits for input before it can run again.
+pub fn check_idle(trigger: &IdleTrigger, armed: bool, idle_seconds: u64) -> IdleCheck {
+    let threshold = trigger.minutes as u64 * 60;
+    if idle_seconds < threshold {
+        // Idle time only grows a second per second, so nothing can happen
+        // before the threshold could be reached.
+        return IdleCheck::Wait {
+            armed: true,
+            after: Duration::from_secs(threshold - idle_seconds),
+        };
+    }
+    if armed {
+        return IdleCheck::Run;
+    }
+    IdleCheck::Wait {
+        armed: false,
+        after: Duration::from_secs(IDLE_POLL_SECONDS),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_once_per_idle_stretch() {
+        let trigger = IdleTrigger { minutes: 5 };
+        assert_eq!(
+            check_idle(&trigger, true, 60),
+            IdleCheck::Wait {
+                armed: true,
+                after: Duration::from_secs(240),
+            }
+        );
+        assert_eq!(check_idle(&trigger, true, 300), IdleCheck::Run);
+
+        // Still away after the run: keep polling until there is input.
+        assert_eq!(
+            check_idle(&trigger, false, 900),
+            IdleCheck::Wait {
+                armed: false,
+                after: Duration::from_secs(IDLE_POLL_SECONDS),
+            }
+        );
+        assert_eq!(
+            check_idle(&trigger, false, 3),
+            IdleCheck::Wait {
+                armed: true,
+                after: Duration::from_secs(297),
+            }
+        );
+
+        assert!(normalize_idle_trigger(Some(IdleTrigger { minutes: 0 })).is_err());
+        assert_eq!(normalize_idle_trigger(None), Ok(None));
+    }
+}
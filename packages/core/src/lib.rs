@@ -0,0 +1,37 @@
+//! Platform-neutral core shared by the LockPilot desktop apps: the timer
+//! model, recurrence rules, scheduler, persistence, history and GitHub
+//! updater.
+//!
+//! Each Tauri app supplies an [`ActionBackend`] for its system actions, a
+//! [`SystemProbe`] for timers' conditions and idle triggers, and a
+//! [`PreActionPrompter`] for the pre-warning window, and forwards its
+//! commands to a [`Scheduler`].
+
+pub mod backend;
+pub mod close;
+pub mod command;
+pub mod conditions;
+pub mod cron;
+pub mod history;
+pub mod holidays;
+pub mod idle;
+pub mod lock;
+pub mod model;
+pub mod open;
+pub mod prewarning;
+pub mod recurrence;
+pub mod rrule;
+pub mod scheduler;
+pub mod sequence;
+pub mod snooze;
+pub mod store;
+pub mod updater;
+
+pub use backend::{ActionBackend, RecordingBackend};
+pub use conditions::{StaticProbe, SystemProbe};
+pub use history::HistoryStore;
+pub use idle::IdleSource;
+pub use lock::LockStore;
+pub use prewarning::PreActionPrompter;
+pub use scheduler::Scheduler;
+pub use store::TimerStore;
@@ -0,0 +1,277 @@
+//! PINs for locked timers. Cancelling, editing, pausing, resuming or
+//! snoozing a locked timer needs its PIN, checked against a salted PBKDF2
+//! hash. Repeated wrong PINs lock a timer's PIN checks out for a growing
+//! while.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use pbkdf2::pbkdf2_hmac;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+const PBKDF2_ROUNDS: u32 = 100_000;
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+pub const MIN_PIN_LEN: usize = 4;
+/// Wrong PINs allowed before checks are throttled.
+const FREE_PIN_ATTEMPTS: u32 = 3;
+const FIRST_LOCKOUT: Duration = Duration::from_secs(30);
+const MAX_LOCKOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Salted PIN hashes for locked timers, kept in `locks.json` next to (but
+/// separate from) `timers.json`.
+#[derive(Clone)]
+pub struct LockStore {
+    inner: Arc<Mutex<HashMap<String, PinHash>>>,
+    /// Wrong PINs per timer since its last right one; kept in memory only.
+    failures: Arc<Mutex<HashMap<String, Failures>>>,
+    storage_path: Arc<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PinHash {
+    salt: String,
+    hash: String,
+    rounds: u32,
+}
+
+#[derive(Debug, Default)]
+struct Failures {
+    count: u32,
+    retry_at: Option<Instant>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedLocks {
+    locks: HashMap<String, PinHash>,
+}
+
+impl LockStore {
+    /// Open the store, reading any locks already on disk.
+    pub fn new(storage_path: PathBuf) -> Result<Self, String> {
+        let locks = if storage_path.exists() {
+            let raw = fs::read_to_string(&storage_path)
+                .map_err(|err| format!("Failed to read lock data: {err}"))?;
+            serde_json::from_str::<PersistedLocks>(&raw)
+                .map_err(|err| format!("Failed to parse lock data: {err}"))?
+                .locks
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(locks)),
+            failures: Arc::new(Mutex::new(HashMap::new())),
+            storage_path: Arc::new(storage_path),
+        })
+    }
+
+    pub fn is_locked(&self, timer_id: &str) -> bool {
+        self.inner
+            .lock()
+            .map(|locks| locks.contains_key(timer_id))
+            .unwrap_or(false)
+    }
+
+    /// Lock a timer behind `pin`, replacing any earlier PIN.
+    pub fn set(&self, timer_id: &str, pin: &str) -> Result<(), String> {
+        self.insert(timer_id, hash_pin(pin)?)
+    }
+
+    /// Lock a timer behind a PIN already hashed with [`hash_pin`].
+    pub(crate) fn insert(&self, timer_id: &str, entry: PinHash) -> Result<(), String> {
+        self.update(|locks| {
+            locks.insert(timer_id.to_string(), entry);
+        })
+    }
+
+    /// Check `pin` against the timer's lock: `Ok(true)` when it matches,
+    /// `Ok(false)` when no PIN is stored for the timer. Wrong PINs past the
+    /// first few are refused unchecked until a lockout, doubling each time,
+    /// runs out.
+    pub fn verify(&self, timer_id: &str, pin: Option<&str>) -> Result<bool, String> {
+        let entry = {
+            let locks = self
+                .inner
+                .lock()
+                .map_err(|_| "Failed to lock PIN store".to_string())?;
+            match locks.get(timer_id) {
+                Some(entry) => entry.clone(),
+                None => return Ok(false),
+            }
+        };
+
+        let Some(pin) = pin.filter(|pin| !pin.is_empty()) else {
+            return Err("This timer is locked. Enter its PIN.".to_string());
+        };
+        let mut failures = self
+            .failures
+            .lock()
+            .map_err(|_| "Failed to lock PIN store".to_string())?;
+        if let Some(wait) = lockout_left(failures.get(timer_id)) {
+            return Err(lockout_message(wait));
+        }
+        // Hold the lock through the check so parallel guesses queue up
+        // behind the lockout rather than racing past it.
+        let salt =
+            hex::decode(&entry.salt).map_err(|_| "Stored PIN salt is corrupt".to_string())?;
+        let expected =
+            hex::decode(&entry.hash).map_err(|_| "Stored PIN hash is corrupt".to_string())?;
+        if constant_time_eq(&derive(pin, &salt, entry.rounds), &expected) {
+            failures.remove(timer_id);
+            return Ok(true);
+        }
+
+        let failed = failures.entry(timer_id.to_string()).or_default();
+        failed.count += 1;
+        if failed.count >= FREE_PIN_ATTEMPTS {
+            let lockout = FIRST_LOCKOUT
+                .saturating_mul(1 << (failed.count - FREE_PIN_ATTEMPTS).min(16))
+                .min(MAX_LOCKOUT);
+            failed.retry_at = Some(Instant::now() + lockout);
+        }
+        Err("Incorrect PIN.".to_string())
+    }
+
+    /// Time left before the timer's PIN can be tried again, after too many
+    /// wrong ones.
+    pub fn lockout(&self, timer_id: &str) -> Option<Duration> {
+        lockout_left(self.failures.lock().ok()?.get(timer_id))
+    }
+
+    /// Drop locks whose timers no longer exist.
+    pub(crate) fn retain(&self, keep: impl Fn(&str) -> bool) -> Result<(), String> {
+        let stale = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock PIN store".to_string())?
+            .keys()
+            .any(|id| !keep(id));
+        if !stale {
+            return Ok(());
+        }
+        self.update(|locks| locks.retain(|id, _| keep(id)))
+    }
+
+    /// Apply `change` and write the result to disk.
+    fn update(&self, change: impl FnOnce(&mut HashMap<String, PinHash>)) -> Result<(), String> {
+        let data = {
+            let mut locks = self
+                .inner
+                .lock()
+                .map_err(|_| "Failed to lock PIN store".to_string())?;
+            change(&mut locks);
+            serde_json::to_string_pretty(&PersistedLocks {
+                locks: locks.clone(),
+            })
+            .map_err(|err| format!("Failed to encode lock data: {err}"))?
+        };
+
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to create lock storage directory: {err}"))?;
+        }
+        fs::write(self.storage_path.as_ref(), data)
+            .map_err(|err| format!("Failed to write lock data: {err}"))
+    }
+}
+
+/// Salt and hash `pin` for [`LockStore::insert`]. This is the slow part of
+/// locking a timer, so callers do it before handing the hash to the loop.
+pub(crate) fn hash_pin(pin: &str) -> Result<PinHash, String> {
+    if pin.chars().count() < MIN_PIN_LEN {
+        return Err(format!("PIN must be at least {MIN_PIN_LEN} characters."));
+    }
+
+    let mut salt = [0u8; SALT_LEN];
+    getrandom::getrandom(&mut salt).map_err(|err| format!("Failed to generate PIN salt: {err}"))?;
+    Ok(PinHash {
+        salt: hex::encode(salt),
+        hash: hex::encode(derive(pin, &salt, PBKDF2_ROUNDS)),
+        rounds: PBKDF2_ROUNDS,
+    })
+}
+
+fn lockout_left(failures: Option<&Failures>) -> Option<Duration> {
+    failures?.retry_at?.checked_duration_since(Instant::now())
+}
+
+pub(crate) fn lockout_message(wait: Duration) -> String {
+    format!("Too many wrong PINs. Try again in {}s.", wait.as_secs() + 1)
+}
+
+fn derive(pin: &str, salt: &[u8], rounds: u32) -> [u8; HASH_LEN] {
+    let mut out = [0u8; HASH_LEN];
+    pbkdf2_hmac::<Sha256>(pin.as_bytes(), salt, rounds, &mut out);
+    out
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_pins_and_keeps_hashes_on_disk() {
+        let dir = std::env::temp_dir().join(format!("lockpilot-locks-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("locks.json");
+        let store = LockStore::new(path.clone()).unwrap();
+
+        assert_eq!(store.verify("timer", None), Ok(false));
+        assert!(store.set("timer", "123").is_err());
+        store.set("timer", "2468").unwrap();
+
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("2468"));
+
+        let reopened = LockStore::new(path).unwrap();
+        assert!(reopened.is_locked("timer"));
+        assert_eq!(
+            reopened.verify("timer", None),
+            Err("This timer is locked. Enter its PIN.".to_string())
+        );
+        assert_eq!(
+            reopened.verify("timer", Some("1357")),
+            Err("Incorrect PIN.".to_string())
+        );
+        assert_eq!(reopened.verify("timer", Some("2468")), Ok(true));
+
+        reopened.retain(|_| false).unwrap();
+        assert!(!reopened.is_locked("timer"));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn throttles_repeated_wrong_pins() {
+        let dir = std::env::temp_dir().join(format!("lockpilot-locks-{}", uuid::Uuid::new_v4()));
+        let store = LockStore::new(dir.join("locks.json")).unwrap();
+        store.set("timer", "2468").unwrap();
+
+        for _ in 0..FREE_PIN_ATTEMPTS {
+            assert_eq!(
+                store.verify("timer", Some("0000")),
+                Err("Incorrect PIN.".to_string())
+            );
+        }
+        // Even the right PIN is refused until the lockout ends.
+        let wait = store.lockout("timer").unwrap();
+        assert!(wait > FIRST_LOCKOUT - Duration::from_secs(5) && wait <= FIRST_LOCKOUT);
+        assert_eq!(
+            store.verify("timer", Some("2468")),
+            Err(lockout_message(wait))
+        );
+        assert_eq!(store.lockout("other"), None);
+        assert_eq!(store.verify("other", Some("0000")), Ok(false));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,475 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerAction {
+    Popup,
+    Lock,
+    Shutdown,
+    Reboot,
+    Sleep,
+    Hibernate,
+    Logout,
+    RunCommand,
+    Open,
+    CloseApps,
+    Sequence,
+}
+
+impl TimerAction {
+    /// Stable snake_case key, matching the serialized form.
+    pub fn key(&self) -> &'static str {
+        match self {
+            TimerAction::Popup => "popup",
+            TimerAction::Lock => "lock",
+            TimerAction::Shutdown => "shutdown",
+            TimerAction::Reboot => "reboot",
+            TimerAction::Sleep => "sleep",
+            TimerAction::Hibernate => "hibernate",
+            TimerAction::Logout => "logout",
+            TimerAction::RunCommand => "run_command",
+            TimerAction::Open => "open",
+            TimerAction::CloseApps => "close_apps",
+            TimerAction::Sequence => "sequence",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecurrencePreset {
+    Daily,
+    Weekdays,
+    SpecificDays,
+    EveryNHours,
+    EveryNMinutes,
+    Cron,
+    Rrule,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurrenceConfig {
+    pub preset: RecurrencePreset,
+    pub interval_hours: Option<u32>,
+    pub interval_minutes: Option<u32>,
+    pub days_of_week: Option<Vec<String>>,
+    /// Expression for the `Cron` preset; see [`crate::cron`].
+    pub cron_expression: Option<String>,
+    /// RFC 5545 rule for the `Rrule` preset, e.g. `FREQ=MONTHLY;BYDAY=1MO`;
+    /// see [`crate::rrule`].
+    pub rrule: Option<String>,
+    /// IANA time zone the schedule follows; the system zone when unset.
+    pub timezone: Option<String>,
+    /// Wall-clock time of day for day-based presets, captured at creation.
+    pub local_time: Option<NaiveTime>,
+    /// Local date of the first run, captured at creation; the `DTSTART` that
+    /// RRULE intervals and counts are measured from.
+    pub start_date: Option<NaiveDate>,
+    /// No runs are scheduled after this instant.
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Stop after this many occurrences have come due.
+    pub max_occurrences: Option<u32>,
+    /// Local dates on which no run happens.
+    pub skip_dates: Option<Vec<NaiveDate>>,
+    /// Path to a local `.ics` file whose events are also skipped.
+    pub holiday_calendar: Option<String>,
+}
+
+/// How far a timer's runs may be pushed back from the pre-action prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnoozePolicy {
+    /// Snooze lengths offered, in minutes.
+    pub durations_minutes: Vec<u32>,
+    /// Snoozes allowed per occurrence; unlimited when unset.
+    pub max_count: Option<u32>,
+    /// Longest a run may be delayed past its scheduled time, in minutes.
+    pub max_total_minutes: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerStatus {
+    #[default]
+    Active,
+    Paused,
+    /// A one-shot that has run, kept with its outcome until it is deleted.
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerInfo {
+    pub id: String,
+    pub action: TimerAction,
+    pub target_time: DateTime<Utc>,
+    pub recurrence: Option<RecurrenceConfig>,
+    /// Pre-warning offsets before each run, in seconds.
+    pub pre_warning_seconds: Option<Vec<u32>>,
+    /// Minute offsets written by older versions; read when
+    /// `pre_warning_seconds` is unset.
+    pub pre_warning_minutes: Option<Vec<u32>>,
+    /// Snooze options and limits; a single unlimited 10-minute snooze when unset.
+    pub snooze: Option<SnoozePolicy>,
+    /// Snoozes used on the current occurrence.
+    #[serde(default)]
+    pub snooze_count: u32,
+    /// Scheduled time of the current occurrence before it was first snoozed.
+    pub snoozed_from: Option<DateTime<Utc>>,
+    pub message: Option<String>,
+    /// Program run by a `RunCommand` timer.
+    pub command: Option<CommandSpec>,
+    /// Link, file or app opened by an `Open` timer.
+    pub open_target: Option<String>,
+    /// Apps closed by a `CloseApps` timer.
+    pub close_apps: Option<CloseAppsSpec>,
+    /// Actions run in order by a `Sequence` timer.
+    pub steps: Option<Vec<ActionStep>>,
+    /// Checked just before each scheduled run.
+    pub conditions: Option<TimerConditions>,
+    /// Run after this long without input instead of at `target_time`, which
+    /// then holds when the timer last came due.
+    pub idle_trigger: Option<IdleTrigger>,
+    /// Why idle time couldn't be read at the last check; cleared once it
+    /// can be again.
+    pub idle_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Occurrences that have come due so far, whether run or cancelled.
+    #[serde(default)]
+    pub occurrence_count: u32,
+    #[serde(default)]
+    pub status: TimerStatus,
+    /// Time left on a paused one-shot timer, restored when it resumes.
+    pub remaining_seconds: Option<i64>,
+    /// Cancelling, editing, pausing, resuming or snoozing needs the PIN kept in
+    /// [`crate::lock::LockStore`].
+    #[serde(default)]
+    pub locked: bool,
+    /// Result of the most recent run of the action.
+    pub last_outcome: Option<ActionOutcome>,
+    /// Progress of a `Sequence` run still under way, saved so the remaining
+    /// steps pick up again after a restart.
+    pub sequence_run: Option<SequenceRun>,
+}
+
+#[cfg(test)]
+impl TimerInfo {
+    /// Active one-shot `action` timer due at `target_time`, with every
+    /// optional setting left unset, for struct-update syntax in tests.
+    pub(crate) fn fixture(action: TimerAction, target_time: DateTime<Utc>) -> Self {
+        Self {
+            id: "timer".to_string(),
+            action,
+            target_time,
+            recurrence: None,
+            pre_warning_seconds: None,
+            pre_warning_minutes: None,
+            snooze: None,
+            snooze_count: 0,
+            snoozed_from: None,
+            message: None,
+            command: None,
+            open_target: None,
+            close_apps: None,
+            steps: None,
+            conditions: None,
+            idle_trigger: None,
+            idle_error: None,
+            created_at: target_time - chrono::Duration::hours(1),
+            occurrence_count: 0,
+            status: TimerStatus::Active,
+            remaining_seconds: None,
+            locked: false,
+            last_outcome: None,
+            sequence_run: None,
+        }
+    }
+}
+
+/// One method tried while running an action, such as a command or API call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionAttempt {
+    /// Short name of the method, e.g. `osascript:lock-shortcut`.
+    pub method: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub stderr: Option<String>,
+    /// Tail of the standard output, kept for commands the user asked to run.
+    pub stdout: Option<String>,
+}
+
+/// Result of running a timer's action: the methods tried, in order, until
+/// one succeeded or all failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionOutcome {
+    pub success: bool,
+    pub attempts: Vec<ActionAttempt>,
+    /// A `Sequence` run's result for each step, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<StepOutcome>,
+}
+
+/// How one step of a `Sequence` run went.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepOutcome {
+    pub action: TimerAction,
+    /// When the step ran; unset when an earlier failure stopped the sequence.
+    pub started_at: Option<DateTime<Utc>>,
+    pub success: bool,
+    pub attempts: Vec<ActionAttempt>,
+}
+
+/// A `Sequence` run part way through its steps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceRun {
+    /// Index of the step to run next.
+    pub next_step: usize,
+    /// When that step is due, once its delay is over.
+    pub next_at: DateTime<Utc>,
+    /// When that step started, until its outcome is back. Saved before the
+    /// step runs, so a step that shut the machine down isn't run again.
+    #[serde(default)]
+    pub step_started_at: Option<DateTime<Utc>>,
+    /// Steps run so far.
+    pub steps: Vec<StepOutcome>,
+    /// History entry logged, with every step's outcome, once the run ends.
+    pub entry: HistoryEntry,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTimerRequest {
+    pub action: TimerAction,
+    pub target_time: String,
+    pub recurrence: Option<RecurrenceConfig>,
+    pub pre_warning_seconds: Option<Vec<u32>>,
+    /// Legacy minute offsets, used when `pre_warning_seconds` is not given.
+    pub pre_warning_minutes: Option<Vec<u32>>,
+    pub snooze: Option<SnoozePolicy>,
+    /// Lock the timer behind this PIN; when editing, replaces its PIN.
+    pub lock_pin: Option<String>,
+    pub message: Option<String>,
+    pub command: Option<CommandSpec>,
+    pub open_target: Option<String>,
+    pub close_apps: Option<CloseAppsSpec>,
+    pub steps: Option<Vec<ActionStep>>,
+    pub conditions: Option<TimerConditions>,
+    /// Makes the timer idle-triggered; `target_time` is then ignored.
+    pub idle_trigger: Option<IdleTrigger>,
+}
+
+/// Program run by a `RunCommand` timer. It is started directly, without a
+/// shell, unless `shell` is set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandSpec {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables, added to LockPilot's own.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    pub working_dir: Option<String>,
+    /// Seconds before the command is killed.
+    pub timeout_seconds: Option<u32>,
+    /// Run `program` as a command line through `sh -c` (`cmd /C` on Windows).
+    #[serde(default)]
+    pub shell: bool,
+}
+
+/// Conditions a scheduled run must meet, and what happens when it doesn't.
+/// Every condition set must hold.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerConditions {
+    /// Run only on this power source.
+    pub power: Option<PowerSource>,
+    /// Run only once there's been no keyboard or mouse input for this long.
+    pub min_idle_minutes: Option<u32>,
+    /// Don't run while any of these processes is running.
+    #[serde(default)]
+    pub blocking_processes: Vec<String>,
+    /// Don't run while overall CPU use is above this percentage.
+    pub max_cpu_percent: Option<u32>,
+    #[serde(default)]
+    pub when_unmet: UnmetPolicy,
+    /// How far `Defer` puts the run off.
+    pub defer_minutes: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerSource {
+    Ac,
+    Battery,
+}
+
+/// What to do with a run whose conditions aren't met.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnmetPolicy {
+    /// Skip this occurrence.
+    #[default]
+    Skip,
+    /// Check again after `defer_minutes`.
+    Defer,
+    /// Run anyway, logging which conditions weren't met.
+    RunAnyway,
+}
+
+/// Runs a timer once the user has been idle for `minutes`; see
+/// [`crate::idle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdleTrigger {
+    pub minutes: u32,
+}
+
+/// One action of a `Sequence` timer, with the same settings a single-action
+/// timer would have.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionStep {
+    pub action: TimerAction,
+    /// Seconds to wait after the previous step before running this one.
+    #[serde(default)]
+    pub delay_seconds: u32,
+    /// Keep going with the next steps when this one fails.
+    #[serde(default)]
+    pub continue_on_error: bool,
+    pub message: Option<String>,
+    pub command: Option<CommandSpec>,
+    pub open_target: Option<String>,
+    pub close_apps: Option<CloseAppsSpec>,
+}
+
+/// Apps closed by a `CloseApps` timer: each is asked to quit, and killed if
+/// it is still running after the grace period.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseAppsSpec {
+    /// Process names, or bundle ids on macOS.
+    pub apps: Vec<String>,
+    /// Seconds to wait for apps to quit before killing them.
+    pub grace_seconds: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewRecurrenceRequest {
+    pub recurrence: RecurrenceConfig,
+    /// First run to preview from (RFC 3339); defaults to now.
+    pub start_time: Option<String>,
+    pub count: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurrencePreview {
+    pub runs: Vec<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreActionDecision {
+    RunNow,
+    #[serde(alias = "snooze10")]
+    Snooze,
+    CancelAction,
+    ContinueScheduled,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvePreActionRequest {
+    pub prompt_id: String,
+    pub decision: PreActionDecision,
+    /// Length of a `Snooze`; the timer's shortest option when unset.
+    pub snooze_minutes: Option<u32>,
+    /// PIN of a locked timer, needed to snooze or cancel the run.
+    pub pin: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreActionWarningPayload {
+    pub prompt_id: String,
+    pub timer_id: String,
+    pub action: TimerAction,
+    /// Offset of the warning being shown, in seconds before the run.
+    pub warning_seconds: u32,
+    /// Time actually left until the run.
+    pub countdown_seconds: u32,
+    /// Snooze lengths still allowed for this run, in minutes.
+    pub snooze_options_minutes: Vec<u32>,
+    /// Snoozes left for this run; unlimited when unset.
+    pub snoozes_remaining: Option<u32>,
+    pub locked: bool,
+    /// Apps a `CloseApps` run will close.
+    pub apps: Vec<String>,
+}
+
+/// Sent to the UI when a timer's action has finished running.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionOutcomePayload {
+    pub timer_id: String,
+    pub action: TimerAction,
+    pub outcome: ActionOutcome,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryEvent {
+    /// An occurrence's action was run.
+    Executed,
+    /// An occurrence was cancelled from the prompt or skipped.
+    Skipped,
+    /// A recurring timer reached its end and was removed.
+    Ended,
+    /// A wrong PIN was entered for a locked timer.
+    PinRejected,
+    /// A run was put off because its conditions weren't met.
+    Deferred,
+    /// An idle timer found it couldn't read idle time, and keeps checking.
+    IdleUnavailable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub timer_id: String,
+    pub action: TimerAction,
+    pub event: HistoryEvent,
+    /// When the occurrence was due, before any snoozes.
+    pub scheduled_time: Option<DateTime<Utc>>,
+    /// Last answer given at the occurrence's pre-warning prompt.
+    pub decision: Option<PreActionDecision>,
+    /// Why the action failed; unset when it ran.
+    pub error: Option<String>,
+    /// Methods tried when the action ran.
+    pub outcome: Option<ActionOutcome>,
+    pub detail: Option<String>,
+    /// When the event happened; for `Executed`, when the action started.
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Which history entries to list or clear; unset fields match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryFilter {
+    pub timer_id: Option<String>,
+    pub action: Option<TimerAction>,
+    /// Entries recorded at or after this instant.
+    pub from: Option<DateTime<Utc>>,
+    /// Entries recorded before this instant.
+    pub to: Option<DateTime<Utc>>,
+}
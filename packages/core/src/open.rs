@@ -0,0 +1,147 @@
+//! `Open` timers: what a target names (a link, a file or an app) and which
+//! links may be opened.
+
+use std::path::{Path, PathBuf};
+
+/// URL schemes an `Open` timer may follow: web and mail links plus the
+/// usual meeting and chat apps.
+pub const ALLOWED_OPEN_SCHEMES: &[&str] = &[
+    "http", "https", "mailto", "tel", "zoommtg", "zoomus", "msteams", "slack", "webex",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenTargetKind {
+    Url,
+    File,
+    /// An app name, bundle id (macOS) or desktop entry id (Linux).
+    App,
+}
+
+/// Tell what `target` names: `scheme:` links are URLs, absolute or `~/`
+/// paths are files, and anything else is an app.
+pub fn open_target_kind(target: &str) -> OpenTargetKind {
+    if url_scheme(target).is_some() {
+        OpenTargetKind::Url
+    } else if target.starts_with('~') || is_absolute(target) {
+        OpenTargetKind::File
+    } else {
+        OpenTargetKind::App
+    }
+}
+
+/// Trim and check a requested target: links need an allowed scheme, files
+/// must exist (`~/` is expanded), and app names are taken as given.
+pub fn normalize_open_target(target: Option<String>) -> Result<String, String> {
+    let target = target
+        .map(|target| target.trim().to_string())
+        .unwrap_or_default();
+    if target.is_empty() {
+        return Err("Open needs a link, file or app.".to_string());
+    }
+    if target.chars().any(|ch| ch == '"' || ch.is_control()) {
+        return Err("Open target must not contain quotes or control characters.".to_string());
+    }
+
+    match open_target_kind(&target) {
+        OpenTargetKind::Url => {
+            let scheme = url_scheme(&target).unwrap_or_default().to_ascii_lowercase();
+            if !ALLOWED_OPEN_SCHEMES.contains(&scheme.as_str()) {
+                return Err(format!(
+                    "Links starting with {scheme}: can't be opened. Allowed: {}.",
+                    ALLOWED_OPEN_SCHEMES.join(", ")
+                ));
+            }
+            Ok(target)
+        }
+        OpenTargetKind::File => {
+            let path = expand_home(&target)?;
+            if !path.exists() {
+                return Err(format!("File not found: {}", path.display()));
+            }
+            Ok(path.to_string_lossy().to_string())
+        }
+        OpenTargetKind::App => Ok(target),
+    }
+}
+
+/// The scheme of a `scheme:rest` link. Single letters are left alone so
+/// Windows drive paths such as `C:\` aren't read as links.
+fn url_scheme(target: &str) -> Option<&str> {
+    let (scheme, _) = target.split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = scheme.len() > 1
+        && chars.next().is_some_and(|ch| ch.is_ascii_alphabetic())
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '-' | '.'));
+    valid.then_some(scheme)
+}
+
+fn is_absolute(target: &str) -> bool {
+    // Checked by hand as well so Windows paths are recognized on any host.
+    Path::new(target).is_absolute()
+        || target.starts_with('/')
+        || target.starts_with('\\')
+        || (target.len() > 2
+            && target.as_bytes()[1] == b':'
+            && matches!(target.as_bytes()[2], b'\\' | b'/'))
+}
+
+fn expand_home(target: &str) -> Result<PathBuf, String> {
+    let Some(rest) = target.strip_prefix('~') else {
+        return Ok(PathBuf::from(target));
+    };
+    let home = std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .ok_or_else(|| "Can't expand ~: no home directory is set".to_string())?;
+    Ok(PathBuf::from(home).join(rest.trim_start_matches(['/', '\\'])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tells_links_files_and_apps_apart() {
+        assert_eq!(
+            open_target_kind("https://meet.example.com/standup"),
+            OpenTargetKind::Url
+        );
+        assert_eq!(
+            open_target_kind("zoommtg://zoom.us/join?confno=1"),
+            OpenTargetKind::Url
+        );
+        assert_eq!(
+            open_target_kind("/Users/me/notes.txt"),
+            OpenTargetKind::File
+        );
+        assert_eq!(
+            open_target_kind("C:\\Users\\me\\notes.txt"),
+            OpenTargetKind::File
+        );
+        assert_eq!(open_target_kind("~/notes.txt"), OpenTargetKind::File);
+        assert_eq!(
+            open_target_kind("com.tinyspeck.slackmacgap"),
+            OpenTargetKind::App
+        );
+        assert_eq!(open_target_kind("Slack"), OpenTargetKind::App);
+    }
+
+    #[test]
+    fn checks_schemes_and_files() {
+        assert_eq!(
+            normalize_open_target(Some(" https://example.com ".to_string())).unwrap(),
+            "https://example.com"
+        );
+        assert!(normalize_open_target(Some("javascript:alert(1)".to_string())).is_err());
+        assert!(normalize_open_target(Some("file:///etc/passwd".to_string())).is_err());
+        assert!(normalize_open_target(Some("\"calc".to_string())).is_err());
+        assert!(normalize_open_target(None).is_err());
+
+        let existing = std::env::temp_dir();
+        assert!(normalize_open_target(Some(existing.to_string_lossy().to_string())).is_ok());
+        assert!(normalize_open_target(Some("/definitely/not/here.txt".to_string())).is_err());
+        assert_eq!(
+            normalize_open_target(Some("Slack".to_string())).unwrap(),
+            "Slack"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    model::{ActionOutcomePayload, PreActionWarningPayload, RecurrenceConfig},
+    recurrence::{anchor_recurrence, compute_next_run},
+};
+
+/// Platform hook that shows and dismisses the pre-warning window, and tells
+/// the UI how each action went.
+pub trait PreActionPrompter: Send + Sync {
+    /// Open the pre-warning window, or update it in place when the timer's
+    /// window is already open, and notify the UI about the pending prompt.
+    fn open(&self, payload: &PreActionWarningPayload) -> Result<(), String>;
+    fn close(&self, timer_id: &str);
+    fn action_finished(&self, payload: &ActionOutcomePayload);
+}
+
+/// Longest pre-warning offered: one day.
+pub const MAX_PRE_WARNING_SECONDS: u32 = 24 * 60 * 60;
+
+/// Runs looked at when checking that warnings fit between occurrences.
+const GAP_SAMPLE_RUNS: usize = 8;
+
+/// Pre-warning offsets in seconds, sorted and de-duplicated. Explicit
+/// seconds win; legacy minute offsets (older requests and `timers.json`
+/// files) are converted.
+pub fn normalize_pre_warning_seconds(
+    seconds: Option<&Vec<u32>>,
+    legacy_minutes: Option<&Vec<u32>>,
+) -> Result<Option<Vec<u32>>, String> {
+    let mut normalized: Vec<u32> = match (seconds, legacy_minutes) {
+        (Some(seconds), _) => seconds.clone(),
+        (None, Some(minutes)) => minutes
+            .iter()
+            .map(|minutes| minutes.saturating_mul(60))
+            .collect(),
+        (None, None) => return Ok(None),
+    };
+
+    if normalized
+        .iter()
+        .any(|value| *value == 0 || *value > MAX_PRE_WARNING_SECONDS)
+    {
+        return Err("Pre-warnings must be between 1 second and 24 hours.".to_string());
+    }
+
+    normalized.sort_unstable();
+    normalized.dedup();
+    Ok(Some(normalized))
+}
+
+/// Check that a one-shot timer's warnings fit in the time left before it
+/// runs, and that a recurring timer's are shorter than the gap between its
+/// runs, so one run's warnings never start before the previous run. A
+/// recurring timer's first warnings may fall before `now`; those are shown
+/// straight away.
+pub fn validate_pre_warnings(
+    offsets: &[u32],
+    first_run: DateTime<Utc>,
+    recurrence: Option<&RecurrenceConfig>,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let Some(longest) = offsets.last().copied() else {
+        return Ok(());
+    };
+    let Some(recurrence) = recurrence else {
+        let left = (first_run - now).num_seconds();
+        if longest as i64 > left {
+            return Err(format!(
+                "Pre-warning {} is longer than the {} left before the timer runs.",
+                format_offset(longest),
+                format_offset(left.clamp(0, u32::MAX as i64) as u32)
+            ));
+        }
+        return Ok(());
+    };
+
+    let recurrence = anchor_recurrence(recurrence.clone(), first_run);
+    let mut run = first_run;
+    let mut shortest_gap: Option<i64> = None;
+    for _ in 0..GAP_SAMPLE_RUNS {
+        let Some(next) = compute_next_run(run, &recurrence, run) else {
+            break;
+        };
+        let gap = (next - run).num_seconds();
+        shortest_gap = Some(shortest_gap.map_or(gap, |shortest| shortest.min(gap)));
+        run = next;
+    }
+
+    match shortest_gap {
+        Some(gap) if longest as i64 >= gap => Err(format!(
+            "Pre-warning {} must be shorter than the {} between runs.",
+            format_offset(longest),
+            format_offset(gap.max(0) as u32)
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Short form of an offset, such as `30s`, `2m` or `1h 30m`.
+pub fn format_offset(seconds: u32) -> String {
+    let (hours, minutes, secs) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    let parts: Vec<String> = [(hours, "h"), (minutes, "m"), (secs, "s")]
+        .into_iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{value}{unit}"))
+        .collect();
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// The apps a `CloseApps` run will close, one per line and percent-encoded,
+/// so the list is safe both in the pre-warning window's URL and inside a
+/// script string passed to `decodeURIComponent`.
+pub fn encode_app_list(apps: &[String]) -> String {
+    let mut encoded = String::new();
+    for byte in apps.join("\n").bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::RecurrencePreset;
+    use chrono::Duration as ChronoDuration;
+
+    fn every_minutes(minutes: u32) -> RecurrenceConfig {
+        RecurrenceConfig {
+            preset: RecurrencePreset::EveryNMinutes,
+            interval_hours: None,
+            interval_minutes: Some(minutes),
+            days_of_week: None,
+            cron_expression: None,
+            rrule: None,
+            timezone: Some("UTC".to_string()),
+            local_time: None,
+            start_date: None,
+            ends_at: None,
+            max_occurrences: None,
+            skip_dates: None,
+            holiday_calendar: None,
+        }
+    }
+
+    #[test]
+    fn legacy_minutes_become_seconds() {
+        assert_eq!(
+            normalize_pre_warning_seconds(None, Some(&vec![10, 1, 5])),
+            Ok(Some(vec![60, 300, 600]))
+        );
+        assert_eq!(
+            normalize_pre_warning_seconds(Some(&vec![30, 3600, 30]), Some(&vec![10])),
+            Ok(Some(vec![30, 3600]))
+        );
+        assert!(normalize_pre_warning_seconds(Some(&vec![0]), None).is_err());
+        assert!(
+            normalize_pre_warning_seconds(Some(&vec![MAX_PRE_WARNING_SECONDS + 1]), None).is_err()
+        );
+    }
+
+    #[test]
+    fn recurring_warnings_must_fit_between_runs() {
+        let now = Utc::now();
+        let first_run = now + ChronoDuration::minutes(5);
+
+        let every_15 = every_minutes(15);
+        assert!(validate_pre_warnings(&[30, 600], first_run, Some(&every_15), now).is_ok());
+        assert_eq!(
+            validate_pre_warnings(&[900], first_run, Some(&every_15), now),
+            Err("Pre-warning 15m must be shorter than the 15m between runs.".to_string())
+        );
+    }
+
+    #[test]
+    fn one_shot_warnings_must_fit_before_the_run() {
+        let now = Utc::now();
+        let first_run = now + ChronoDuration::minutes(5);
+
+        assert!(validate_pre_warnings(&[60, 300], first_run, None, now).is_ok());
+        assert_eq!(
+            validate_pre_warnings(&[60, 3600], first_run, None, now),
+            Err("Pre-warning 1h is longer than the 5m left before the timer runs.".to_string())
+        );
+        assert!(validate_pre_warnings(&[], first_run, None, now).is_ok());
+    }
+
+    #[test]
+    fn formats_offsets_compactly() {
+        assert_eq!(format_offset(30), "30s");
+        assert_eq!(format_offset(120), "2m");
+        assert_eq!(format_offset(5400), "1h 30m");
+    }
+
+    #[test]
+    fn encodes_app_lists_for_urls_and_scripts() {
+        let apps = vec![
+            "com.tinyspeck.slackmacgap".to_string(),
+            "Visual Studio \"Code\"".to_string(),
+        ];
+        assert_eq!(
+            encode_app_list(&apps),
+            "com.tinyspeck.slackmacgap%0AVisual%20Studio%20%22Code%22"
+        );
+    }
+}
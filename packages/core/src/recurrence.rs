@@ -0,0 +1,628 @@
+use std::sync::Arc;
+
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, LocalResult, NaiveDate, NaiveDateTime, Offset,
+    TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+use crate::{
+    cron::CronSchedule,
+    holidays::HolidayCalendar,
+    model::{PreviewRecurrenceRequest, RecurrenceConfig, RecurrencePreset, RecurrencePreview},
+    rrule::RRule,
+};
+
+const MAX_PREVIEW_RUNS: u32 = 50;
+
+/// Excluded runs to step over before giving up on finding one that is allowed.
+const MAX_EXCLUDED_RUNS: u32 = 100_000;
+
+pub fn validate_recurrence(recurrence: Option<&RecurrenceConfig>) -> Result<(), String> {
+    let Some(recurrence) = recurrence else {
+        return Ok(());
+    };
+
+    if let Some(name) = recurrence.timezone.as_deref() {
+        parse_timezone(name)?;
+    }
+    if recurrence.max_occurrences == Some(0) {
+        return Err("Max occurrences must be at least 1.".to_string());
+    }
+    if let Some(path) = recurrence.holiday_calendar.as_deref() {
+        HolidayCalendar::load(path)?;
+    }
+
+    match recurrence.preset {
+        RecurrencePreset::Daily | RecurrencePreset::Weekdays => Ok(()),
+        RecurrencePreset::SpecificDays => {
+            let Some(days) = recurrence.days_of_week.as_ref() else {
+                return Err("Specific Days requires at least one day.".to_string());
+            };
+            if days.is_empty() {
+                return Err("Specific Days requires at least one day.".to_string());
+            }
+            if days.len() > 7 {
+                return Err("Specific Days can include at most 7 days.".to_string());
+            }
+            if days.iter().any(|day| parse_weekday(day).is_none()) {
+                return Err("Specific Days contains an invalid weekday.".to_string());
+            }
+            Ok(())
+        }
+        RecurrencePreset::EveryNHours => {
+            let Some(hours) = recurrence.interval_hours else {
+                return Err("Every N Hours requires an interval.".to_string());
+            };
+            if (1..=24).contains(&hours) {
+                Ok(())
+            } else {
+                Err("Interval hours must be between 1 and 24.".to_string())
+            }
+        }
+        RecurrencePreset::EveryNMinutes => {
+            let Some(minutes) = recurrence.interval_minutes else {
+                return Err("Every N Minutes requires an interval.".to_string());
+            };
+            if (1..=1440).contains(&minutes) {
+                Ok(())
+            } else {
+                Err("Interval minutes must be between 1 and 1440.".to_string())
+            }
+        }
+        RecurrencePreset::Cron => {
+            let schedule = parse_cron(recurrence)?;
+            let tz = recurrence_timezone(recurrence);
+            if schedule.next_after(&tz, Utc::now()).is_none() {
+                return Err("Cron expression never matches a date.".to_string());
+            }
+            Ok(())
+        }
+        RecurrencePreset::Rrule => {
+            parse_rrule(recurrence)?;
+            Ok(())
+        }
+    }
+}
+
+/// First run of a new timer requested for `requested`.
+///
+/// Schedules that pick their own times (cron) or days (RRULE) start at their
+/// first match at or after the requested time; the rest run exactly when
+/// requested. A first run on an excluded date moves to the next allowed run.
+pub fn align_first_run(
+    requested: DateTime<Utc>,
+    recurrence: Option<&RecurrenceConfig>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let Some(recurrence) = recurrence else {
+        return Some(requested);
+    };
+
+    let first = match recurrence.preset {
+        RecurrencePreset::Cron => {
+            let after = (requested - ChronoDuration::milliseconds(1)).max(now);
+            parse_cron(recurrence)
+                .ok()?
+                .next_after(&recurrence_timezone(recurrence), after)
+        }
+        RecurrencePreset::Rrule => {
+            // The requested time is the rule's DTSTART until the first run is known.
+            let tz = recurrence_timezone(recurrence);
+            let after = (requested - ChronoDuration::milliseconds(1)).max(now);
+            parse_rrule(recurrence).ok()?.next_after(
+                &tz,
+                requested.with_timezone(&tz).naive_local(),
+                after,
+            )
+        }
+        _ => Some(requested),
+    }?;
+
+    if Exclusions::load(recurrence).contains(first) {
+        let recurrence = anchor_recurrence(recurrence.clone(), first);
+        compute_next_run(first, &recurrence, first)
+    } else {
+        Some(first)
+    }
+}
+
+/// Upcoming runs of a recurrence, starting with its first run.
+pub fn preview_recurrence(request: PreviewRecurrenceRequest) -> Result<RecurrencePreview, String> {
+    validate_recurrence(Some(&request.recurrence))?;
+
+    let now = Utc::now();
+    let requested = match request.start_time.as_deref() {
+        Some(start_time) => DateTime::parse_from_rfc3339(start_time)
+            .map_err(|_| "Invalid date/time format".to_string())?
+            .with_timezone(&Utc),
+        None => now,
+    };
+    let count = request.count.unwrap_or(5).clamp(1, MAX_PREVIEW_RUNS) as usize;
+
+    let Some(first) = align_first_run(requested, Some(&request.recurrence), now) else {
+        return Ok(RecurrencePreview { runs: Vec::new() });
+    };
+    let recurrence = anchor_recurrence(request.recurrence, first);
+
+    if recurrence.ends_at.is_some_and(|ends_at| first > ends_at) {
+        return Ok(RecurrencePreview { runs: Vec::new() });
+    }
+
+    let mut runs = vec![first];
+    while runs.len() < count {
+        let last = runs[runs.len() - 1];
+        match next_occurrence(last, &recurrence, runs.len() as u32, last) {
+            Some(next) => runs.push(next),
+            None => break,
+        }
+    }
+    Ok(RecurrencePreview { runs })
+}
+
+/// Pin a new recurrence to a time zone (the system zone unless one was
+/// given) and to the local date and wall-clock time of its first run.
+pub fn anchor_recurrence(
+    mut recurrence: RecurrenceConfig,
+    first_run: DateTime<Utc>,
+) -> RecurrenceConfig {
+    let tz = recurrence_timezone(&recurrence);
+    let local = first_run.with_timezone(&tz);
+    recurrence.timezone = Some(tz.name().to_string());
+    recurrence.local_time = Some(local.time());
+    recurrence.start_date = Some(local.date_naive());
+    if let Ok(rule) = parse_rrule(&recurrence) {
+        recurrence.rrule = Some(rule.to_string());
+    }
+    recurrence
+}
+
+/// Next run of a timer that has had `completed` occurrences, or `None` once
+/// its end date or occurrence limit is reached.
+pub fn next_occurrence(
+    current_target: DateTime<Utc>,
+    recurrence: &RecurrenceConfig,
+    completed: u32,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if recurrence
+        .max_occurrences
+        .is_some_and(|max| completed >= max)
+    {
+        return None;
+    }
+    compute_next_run(current_target, recurrence, now)
+}
+
+/// Next run strictly after both `current_target` and `now`, no later than
+/// the recurrence's end date and not on a skip date or holiday.
+///
+/// Interval presets count elapsed time, so DST changes do not affect them.
+/// Day-based presets follow the wall clock in the recurrence's time zone: a
+/// time skipped by a spring-forward jump runs later by the size of the gap
+/// (02:30 becomes 03:30), and a time repeated by a fall-back runs at its
+/// first occurrence.
+pub fn compute_next_run(
+    current_target: DateTime<Utc>,
+    recurrence: &RecurrenceConfig,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let exclusions = Exclusions::load(recurrence);
+    let mut after = current_target;
+    for _ in 0..MAX_EXCLUDED_RUNS {
+        let next = next_scheduled_run(after, recurrence, now)?;
+        if recurrence.ends_at.is_some_and(|ends_at| next > ends_at) {
+            return None;
+        }
+        if !exclusions.contains(next) {
+            return Some(next);
+        }
+        after = next;
+    }
+    None
+}
+
+/// Next run of the recurrence's own schedule, ignoring end and exclusions.
+fn next_scheduled_run(
+    current_target: DateTime<Utc>,
+    recurrence: &RecurrenceConfig,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    match recurrence.preset {
+        RecurrencePreset::EveryNHours => {
+            let interval = ChronoDuration::hours(recurrence.interval_hours? as i64);
+            Some(next_interval(current_target, interval, now))
+        }
+        RecurrencePreset::EveryNMinutes => {
+            let interval = ChronoDuration::minutes(recurrence.interval_minutes? as i64);
+            Some(next_interval(current_target, interval, now))
+        }
+        RecurrencePreset::Daily => next_matching_day(current_target, recurrence, now, |_| true),
+        RecurrencePreset::Weekdays => next_matching_day(current_target, recurrence, now, |day| {
+            day != Weekday::Sat && day != Weekday::Sun
+        }),
+        RecurrencePreset::SpecificDays => {
+            let allowed_days = recurrence
+                .days_of_week
+                .as_ref()?
+                .iter()
+                .filter_map(|day| parse_weekday(day))
+                .collect::<Vec<_>>();
+            if allowed_days.is_empty() {
+                return None;
+            }
+
+            next_matching_day(current_target, recurrence, now, |day| {
+                allowed_days.contains(&day)
+            })
+        }
+        RecurrencePreset::Cron => parse_cron(recurrence)
+            .ok()?
+            .next_after(&recurrence_timezone(recurrence), current_target.max(now)),
+        RecurrencePreset::Rrule => {
+            let tz = recurrence_timezone(recurrence);
+            let start = match (recurrence.start_date, recurrence.local_time) {
+                (Some(date), Some(time)) => date.and_time(time),
+                _ => current_target.with_timezone(&tz).naive_local(),
+            };
+            parse_rrule(recurrence)
+                .ok()?
+                .next_after(&tz, start, current_target.max(now))
+        }
+    }
+}
+
+/// Dates a recurrence never runs on, as local dates in its time zone.
+struct Exclusions<'a> {
+    tz: Tz,
+    skip_dates: &'a [NaiveDate],
+    holidays: Option<Arc<HolidayCalendar>>,
+}
+
+impl<'a> Exclusions<'a> {
+    /// An unreadable holiday calendar excludes nothing; it is checked when
+    /// the timer is created.
+    fn load(recurrence: &'a RecurrenceConfig) -> Self {
+        Self {
+            tz: recurrence_timezone(recurrence),
+            skip_dates: recurrence.skip_dates.as_deref().unwrap_or_default(),
+            holidays: recurrence
+                .holiday_calendar
+                .as_deref()
+                .and_then(|path| HolidayCalendar::load(path).ok()),
+        }
+    }
+
+    fn contains(&self, run: DateTime<Utc>) -> bool {
+        let date = run.with_timezone(&self.tz).date_naive();
+        self.skip_dates.contains(&date)
+            || self
+                .holidays
+                .as_ref()
+                .is_some_and(|holidays| holidays.contains(date))
+    }
+}
+
+/// Time zone the recurrence follows, falling back to the system zone.
+pub fn recurrence_timezone(recurrence: &RecurrenceConfig) -> Tz {
+    recurrence
+        .timezone
+        .as_deref()
+        .and_then(|name| parse_timezone(name).ok())
+        .unwrap_or_else(system_timezone)
+}
+
+pub fn system_timezone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| parse_timezone(&name).ok())
+        .unwrap_or(Tz::UTC)
+}
+
+fn parse_cron(recurrence: &RecurrenceConfig) -> Result<CronSchedule, String> {
+    let expression = recurrence
+        .cron_expression
+        .as_deref()
+        .map(str::trim)
+        .filter(|expression| !expression.is_empty())
+        .ok_or_else(|| "Cron requires an expression.".to_string())?;
+    CronSchedule::parse(expression)
+}
+
+fn parse_rrule(recurrence: &RecurrenceConfig) -> Result<RRule, String> {
+    let rule = recurrence
+        .rrule
+        .as_deref()
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .ok_or_else(|| "RRULE requires a rule.".to_string())?;
+    RRule::parse(rule)
+}
+
+fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| format!("Unknown time zone: {name}"))
+}
+
+fn next_interval(
+    current_target: DateTime<Utc>,
+    interval: ChronoDuration,
+    now: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let mut next = current_target + interval;
+    if next <= now {
+        let behind = (now - next).num_milliseconds() / interval.num_milliseconds() + 1;
+        next += interval * behind as i32;
+    }
+    next
+}
+
+fn next_matching_day(
+    current_target: DateTime<Utc>,
+    recurrence: &RecurrenceConfig,
+    now: DateTime<Utc>,
+    matches: impl Fn(Weekday) -> bool,
+) -> Option<DateTime<Utc>> {
+    let tz = recurrence_timezone(recurrence);
+    let local_target = current_target.with_timezone(&tz);
+    let time = recurrence.local_time.unwrap_or_else(|| local_target.time());
+
+    let mut date = (local_target.date_naive() + ChronoDuration::days(1))
+        .max(now.with_timezone(&tz).date_naive());
+    for _ in 0..14 {
+        if matches(date.weekday()) {
+            let candidate = resolve_local(&tz, date.and_time(time));
+            if candidate > now {
+                return Some(candidate);
+            }
+        }
+        date += ChronoDuration::days(1);
+    }
+    None
+}
+
+/// Map a wall-clock time in `tz` to an instant, shifting skipped times past
+/// the gap and taking the first of two repeated times.
+pub(crate) fn resolve_local(tz: &Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(time) => time.with_timezone(&Utc),
+        LocalResult::Ambiguous(first, _) => first.with_timezone(&Utc),
+        LocalResult::None => {
+            // Read the wall time with the offset in force before the gap.
+            let before = tz
+                .offset_from_utc_datetime(&(local - ChronoDuration::days(1)))
+                .fix();
+            Utc.from_utc_datetime(
+                &(local - ChronoDuration::seconds(before.local_minus_utc() as i64)),
+            )
+        }
+    }
+}
+
+pub fn parse_weekday(input: &str) -> Option<Weekday> {
+    match input.trim().to_ascii_lowercase().as_str() {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tues" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thur" | "thurs" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+
+    const NEW_YORK: Tz = chrono_tz::America::New_York;
+
+    fn recurrence(preset: RecurrencePreset, tz: Tz) -> RecurrenceConfig {
+        RecurrenceConfig {
+            preset,
+            interval_hours: None,
+            interval_minutes: None,
+            days_of_week: None,
+            cron_expression: None,
+            rrule: None,
+            timezone: Some(tz.name().to_string()),
+            local_time: None,
+            start_date: None,
+            ends_at: None,
+            max_occurrences: None,
+            skip_dates: None,
+            holiday_calendar: None,
+        }
+    }
+
+    fn local(tz: Tz, y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        tz.with_ymd_and_hms(y, m, d, h, min, 0)
+            .earliest()
+            .expect("valid local time")
+            .with_timezone(&Utc)
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn daily_keeps_wall_clock_across_spring_forward() {
+        let first = local(NEW_YORK, 2026, 3, 7, 22, 0);
+        let rule = anchor_recurrence(recurrence(RecurrencePreset::Daily, NEW_YORK), first);
+
+        let next = compute_next_run(first, &rule, first).unwrap();
+        assert_eq!(next, local(NEW_YORK, 2026, 3, 8, 22, 0));
+        assert_eq!(next - first, ChronoDuration::hours(23));
+    }
+
+    #[test]
+    fn daily_keeps_wall_clock_across_fall_back() {
+        let first = local(NEW_YORK, 2026, 10, 31, 22, 0);
+        let rule = anchor_recurrence(recurrence(RecurrencePreset::Daily, NEW_YORK), first);
+
+        let next = compute_next_run(first, &rule, first).unwrap();
+        assert_eq!(next, local(NEW_YORK, 2026, 11, 1, 22, 0));
+        assert_eq!(next - first, ChronoDuration::hours(25));
+    }
+
+    #[test]
+    fn skipped_time_runs_after_the_gap_then_returns_to_anchor() {
+        let first = local(NEW_YORK, 2026, 3, 7, 2, 30);
+        let rule = anchor_recurrence(recurrence(RecurrencePreset::Daily, NEW_YORK), first);
+
+        // 02:30 does not exist on 8 March; it runs at 03:30 EDT instead.
+        let shifted = compute_next_run(first, &rule, first).unwrap();
+        assert_eq!(shifted, utc(2026, 3, 8, 7, 30));
+        assert_eq!(
+            shifted.with_timezone(&NEW_YORK).time(),
+            NaiveTime::from_hms_opt(3, 30, 0).unwrap()
+        );
+
+        let after = compute_next_run(shifted, &rule, shifted).unwrap();
+        assert_eq!(after, utc(2026, 3, 9, 6, 30));
+        assert_eq!(
+            after.with_timezone(&NEW_YORK).time(),
+            NaiveTime::from_hms_opt(2, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn repeated_time_runs_once_at_first_occurrence() {
+        let first = local(NEW_YORK, 2026, 10, 31, 1, 30);
+        let rule = anchor_recurrence(recurrence(RecurrencePreset::Daily, NEW_YORK), first);
+
+        // 01:30 happens twice on 1 November; the EDT instance wins.
+        let repeated = compute_next_run(first, &rule, first).unwrap();
+        assert_eq!(repeated, utc(2026, 11, 1, 5, 30));
+
+        let after = compute_next_run(repeated, &rule, repeated).unwrap();
+        assert_eq!(after, utc(2026, 11, 2, 6, 30));
+    }
+
+    #[test]
+    fn weekdays_use_local_day_near_midnight() {
+        let tokyo = chrono_tz::Asia::Tokyo;
+        // Friday 00:30 in Tokyo is still Thursday in UTC.
+        let first = local(tokyo, 2026, 10, 16, 0, 30);
+        let rule = anchor_recurrence(recurrence(RecurrencePreset::Weekdays, tokyo), first);
+
+        let next = compute_next_run(first, &rule, first).unwrap();
+        assert_eq!(next, local(tokyo, 2026, 10, 19, 0, 30));
+        assert_eq!(next.with_timezone(&tokyo).weekday(), Weekday::Mon);
+    }
+
+    #[test]
+    fn specific_days_follow_local_calendar() {
+        let first = local(NEW_YORK, 2026, 3, 6, 23, 0);
+        let mut rule = recurrence(RecurrencePreset::SpecificDays, NEW_YORK);
+        rule.days_of_week = Some(vec!["sun".to_string()]);
+        let rule = anchor_recurrence(rule, first);
+
+        let next = compute_next_run(first, &rule, first).unwrap();
+        assert_eq!(next, local(NEW_YORK, 2026, 3, 8, 23, 0));
+    }
+
+    #[test]
+    fn interval_presets_count_elapsed_time_across_dst() {
+        let first = local(NEW_YORK, 2026, 3, 8, 1, 0);
+        let mut rule = recurrence(RecurrencePreset::EveryNHours, NEW_YORK);
+        rule.interval_hours = Some(2);
+
+        let next = compute_next_run(first, &rule, first).unwrap();
+        assert_eq!(next - first, ChronoDuration::hours(2));
+        assert_eq!(
+            next.with_timezone(&NEW_YORK).time(),
+            NaiveTime::from_hms_opt(4, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn overdue_runs_skip_to_the_first_future_occurrence() {
+        let first = local(NEW_YORK, 2026, 3, 1, 9, 0);
+        let rule = anchor_recurrence(recurrence(RecurrencePreset::Weekdays, NEW_YORK), first);
+
+        let now = local(NEW_YORK, 2026, 4, 15, 12, 0);
+        let next = compute_next_run(first, &rule, now).unwrap();
+        assert_eq!(next, local(NEW_YORK, 2026, 4, 16, 9, 0));
+
+        let mut every = recurrence(RecurrencePreset::EveryNMinutes, NEW_YORK);
+        every.interval_minutes = Some(15);
+        let next = compute_next_run(first, &every, now).unwrap();
+        assert_eq!(next, local(NEW_YORK, 2026, 4, 15, 12, 15));
+    }
+
+    #[test]
+    fn rrule_starts_on_first_match_and_counts_from_it() {
+        let mut rule = recurrence(RecurrencePreset::Rrule, NEW_YORK);
+        rule.rrule = Some("rrule:freq=monthly;byday=2tu;count=3".to_string());
+
+        // Requested on Friday 16 October 2026; the second Tuesday of November is next.
+        let requested = local(NEW_YORK, 2026, 10, 16, 8, 0);
+        let first = align_first_run(requested, Some(&rule), requested).unwrap();
+        assert_eq!(first, local(NEW_YORK, 2026, 11, 10, 8, 0));
+
+        let rule = anchor_recurrence(rule, first);
+        assert_eq!(
+            rule.rrule.as_deref(),
+            Some("FREQ=MONTHLY;BYDAY=2TU;COUNT=3")
+        );
+        let second = compute_next_run(first, &rule, first).unwrap();
+        assert_eq!(second, local(NEW_YORK, 2026, 12, 8, 8, 0));
+        let third = compute_next_run(second, &rule, second).unwrap();
+        assert_eq!(third, local(NEW_YORK, 2027, 1, 12, 8, 0));
+        assert_eq!(compute_next_run(third, &rule, third), None);
+    }
+
+    #[test]
+    fn end_date_and_occurrence_limit_stop_the_schedule() {
+        let first = local(NEW_YORK, 2026, 10, 16, 9, 0);
+        let mut rule = recurrence(RecurrencePreset::Daily, NEW_YORK);
+        rule.ends_at = Some(local(NEW_YORK, 2026, 10, 18, 9, 0));
+        let rule = anchor_recurrence(rule, first);
+
+        let second = compute_next_run(first, &rule, first).unwrap();
+        let third = compute_next_run(second, &rule, second).unwrap();
+        assert_eq!(third, local(NEW_YORK, 2026, 10, 18, 9, 0));
+        assert_eq!(compute_next_run(third, &rule, third), None);
+
+        let mut limited = recurrence(RecurrencePreset::EveryNMinutes, NEW_YORK);
+        limited.interval_minutes = Some(30);
+        limited.max_occurrences = Some(2);
+        assert!(next_occurrence(first, &limited, 1, first).is_some());
+        assert_eq!(next_occurrence(first, &limited, 2, first), None);
+    }
+
+    #[test]
+    fn skip_dates_are_jumped_over_in_local_time() {
+        // Thursday 24 December 2026, 18:00.
+        let first = local(NEW_YORK, 2026, 12, 24, 18, 0);
+        let mut rule = recurrence(RecurrencePreset::Weekdays, NEW_YORK);
+        rule.skip_dates = Some(vec![
+            NaiveDate::from_ymd_opt(2026, 12, 25).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 12, 28).unwrap(),
+        ]);
+        let rule = anchor_recurrence(rule, first);
+
+        let next = compute_next_run(first, &rule, first).unwrap();
+        assert_eq!(next, local(NEW_YORK, 2026, 12, 29, 18, 0));
+
+        // A requested first run on a skipped date moves to the next allowed run.
+        let requested = local(NEW_YORK, 2026, 12, 25, 18, 0);
+        let aligned = align_first_run(requested, Some(&rule), first).unwrap();
+        assert_eq!(aligned, local(NEW_YORK, 2026, 12, 29, 18, 0));
+    }
+
+    #[test]
+    fn rejects_unknown_time_zone() {
+        let mut rule = recurrence(RecurrencePreset::Daily, NEW_YORK);
+        rule.timezone = Some("Mars/Olympus_Mons".to_string());
+        assert_eq!(
+            validate_recurrence(Some(&rule)),
+            Err("Unknown time zone: Mars/Olympus_Mons".to_string())
+        );
+    }
+}
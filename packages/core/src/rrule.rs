@@ -0,0 +1,632 @@
+//! RFC 5545 recurrence rules (`RRULE`), limited to what LockPilot schedules
+//! need: `FREQ` (daily through yearly), `INTERVAL`, `BYDAY` with ordinals,
+//! `BYMONTHDAY`, `BYMONTH`, `COUNT`, `UNTIL` and `WKST=MO`.
+//!
+//! The rule is expanded from a start date-time (the timer's first run) in the
+//! recurrence's time zone, so every run keeps the start's wall-clock time.
+
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+use crate::recurrence::resolve_local;
+
+/// Upper bound on periods to walk before giving up on finding a run.
+const MAX_PERIODS: u32 = 20_000;
+/// Largest `INTERVAL` accepted.
+pub const MAX_INTERVAL: u32 = 1_000;
+
+const WEEKDAY_CODES: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Mon),
+    ("TU", Weekday::Tue),
+    ("WE", Weekday::Wed),
+    ("TH", Weekday::Thu),
+    ("FR", Weekday::Fri),
+    ("SA", Weekday::Sat),
+    ("SU", Weekday::Sun),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ByDay {
+    /// `1` for the first, `-1` for the last, `None` for every one.
+    ordinal: Option<i32>,
+    weekday: Weekday,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Until {
+    Date(NaiveDate),
+    Local(NaiveDateTime),
+    Utc(DateTime<Utc>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RRule {
+    freq: Frequency,
+    interval: u32,
+    by_day: Vec<ByDay>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+    count: Option<u32>,
+    until: Option<Until>,
+}
+
+impl RRule {
+    /// Parse a rule such as `FREQ=MONTHLY;BYDAY=1MO`; an `RRULE:` prefix is accepted.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let body = text
+            .strip_prefix("RRULE:")
+            .or_else(|| text.strip_prefix("rrule:"))
+            .unwrap_or(text);
+
+        let mut freq = None;
+        let mut rule = RRule {
+            freq: Frequency::Daily,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            count: None,
+            until: None,
+        };
+
+        for part in body
+            .split(';')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+        {
+            let Some((name, value)) = part.split_once('=') else {
+                return Err(format!("Invalid RRULE part: {part}"));
+            };
+            let value = value.trim().to_ascii_uppercase();
+            match name.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("Unsupported RRULE frequency: {value}")),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|interval| (1..=MAX_INTERVAL).contains(interval))
+                        .ok_or_else(|| format!("Invalid RRULE interval: {value}"))?;
+                }
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(parse_by_day)
+                        .collect::<Result<_, _>>()?;
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = value
+                        .split(',')
+                        .map(|day| {
+                            day.parse::<i32>()
+                                .ok()
+                                .filter(|day| *day != 0 && (-31..=31).contains(day))
+                                .ok_or_else(|| format!("Invalid RRULE month day: {day}"))
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                "BYMONTH" => {
+                    rule.by_month = value
+                        .split(',')
+                        .map(|month| {
+                            month
+                                .parse::<u32>()
+                                .ok()
+                                .filter(|month| (1..=12).contains(month))
+                                .ok_or_else(|| format!("Invalid RRULE month: {month}"))
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|count| *count > 0)
+                            .ok_or_else(|| format!("Invalid RRULE count: {value}"))?,
+                    );
+                }
+                "UNTIL" => rule.until = Some(parse_until(&value)?),
+                "WKST" if value == "MO" => {}
+                other => return Err(format!("Unsupported RRULE part: {other}")),
+            }
+        }
+
+        rule.freq = freq.ok_or_else(|| "RRULE requires FREQ.".to_string())?;
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err("RRULE cannot have both COUNT and UNTIL.".to_string());
+        }
+        if matches!(rule.freq, Frequency::Daily | Frequency::Weekly)
+            && rule.by_day.iter().any(|day| day.ordinal.is_some())
+        {
+            return Err("RRULE BYDAY ordinals need FREQ=MONTHLY or FREQ=YEARLY.".to_string());
+        }
+        if rule.freq == Frequency::Weekly && !rule.by_month_day.is_empty() {
+            return Err("RRULE BYMONTHDAY cannot be used with FREQ=WEEKLY.".to_string());
+        }
+        Ok(rule)
+    }
+
+    /// First run strictly after `after`, expanding from `start` (local time in `tz`).
+    pub fn next_after(
+        &self,
+        tz: &Tz,
+        start: NaiveDateTime,
+        after: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let time = start.time();
+        let mut emitted = 0u32;
+
+        for period in 0..MAX_PERIODS {
+            let offset = period.checked_mul(self.interval)?;
+            // Past the last date chrono can represent there are no more runs.
+            for date in self.period_dates(start.date(), offset)? {
+                if date < start.date() {
+                    continue;
+                }
+                let local = date.and_time(time);
+                let instant = resolve_local(tz, local);
+                let past_until = match self.until {
+                    Some(Until::Date(until)) => date > until,
+                    Some(Until::Local(until)) => local > until,
+                    Some(Until::Utc(until)) => instant > until,
+                    None => false,
+                };
+                if past_until {
+                    return None;
+                }
+
+                emitted += 1;
+                if self.count.is_some_and(|count| emitted > count) {
+                    return None;
+                }
+                if instant > after {
+                    return Some(instant);
+                }
+            }
+        }
+        None
+    }
+
+    /// Candidate dates, in order, for the period `offset` periods after the
+    /// start's; `None` once that period is beyond the representable dates.
+    fn period_dates(&self, start: NaiveDate, offset: u32) -> Option<Vec<NaiveDate>> {
+        let mut dates = match self.freq {
+            Frequency::Daily => {
+                let date = start.checked_add_days(Days::new(offset as u64))?;
+                let matches = (self.by_month.is_empty() || self.by_month.contains(&date.month()))
+                    && (self.by_month_day.is_empty()
+                        || self.by_month_day.iter().any(|day| {
+                            resolve_month_day(date.year(), date.month(), *day) == Some(date)
+                        }))
+                    && (self.by_day.is_empty()
+                        || self.by_day.iter().any(|day| day.weekday == date.weekday()));
+                if matches {
+                    vec![date]
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Weekly => {
+                let week_start = start
+                    .checked_sub_days(Days::new(start.weekday().num_days_from_monday() as u64))?
+                    .checked_add_days(Days::new(offset as u64 * 7))?;
+                let weekdays = if self.by_day.is_empty() {
+                    vec![start.weekday()]
+                } else {
+                    self.by_day.iter().map(|day| day.weekday).collect()
+                };
+                weekdays
+                    .into_iter()
+                    .filter_map(|weekday| {
+                        week_start
+                            .checked_add_days(Days::new(weekday.num_days_from_monday() as u64))
+                    })
+                    .filter(|date| {
+                        self.by_month.is_empty() || self.by_month.contains(&date.month())
+                    })
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let months = start.year() as i64 * 12 + start.month0() as i64 + offset as i64;
+                let (year, month) = (i32::try_from(months / 12).ok()?, (months % 12) as u32 + 1);
+                if self.by_month.is_empty() || self.by_month.contains(&month) {
+                    self.month_dates(year, month, start.day())
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(i32::try_from(offset).ok()?)?;
+                if !self.by_month.is_empty() {
+                    self.by_month
+                        .iter()
+                        .flat_map(|month| self.month_dates(year, *month, start.day()))
+                        .collect()
+                } else if !self.by_month_day.is_empty() {
+                    (1..=12)
+                        .flat_map(|month| self.month_dates(year, month, start.day()))
+                        .collect()
+                } else if !self.by_day.is_empty() {
+                    self.year_weekday_dates(year)
+                } else {
+                    NaiveDate::from_ymd_opt(year, start.month(), start.day())
+                        .into_iter()
+                        .collect()
+                }
+            }
+        };
+        dates.sort_unstable();
+        dates.dedup();
+        Some(dates)
+    }
+
+    fn month_dates(&self, year: i32, month: u32, default_day: u32) -> Vec<NaiveDate> {
+        let weekday_dates = || -> Vec<NaiveDate> {
+            let days: Vec<NaiveDate> = (1..=days_in_month(year, month))
+                .filter_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+                .collect();
+            self.by_day
+                .iter()
+                .flat_map(|by_day| pick_weekdays(&days, *by_day))
+                .collect()
+        };
+
+        if !self.by_month_day.is_empty() {
+            let month_days = self
+                .by_month_day
+                .iter()
+                .filter_map(|day| resolve_month_day(year, month, *day));
+            if self.by_day.is_empty() {
+                month_days.collect()
+            } else {
+                let allowed = weekday_dates();
+                month_days.filter(|date| allowed.contains(date)).collect()
+            }
+        } else if !self.by_day.is_empty() {
+            weekday_dates()
+        } else {
+            NaiveDate::from_ymd_opt(year, month, default_day)
+                .into_iter()
+                .collect()
+        }
+    }
+
+    fn year_weekday_dates(&self, year: i32) -> Vec<NaiveDate> {
+        let days: Vec<NaiveDate> = (1..=12)
+            .flat_map(|month| {
+                (1..=days_in_month(year, month))
+                    .filter_map(move |day| NaiveDate::from_ymd_opt(year, month, day))
+            })
+            .collect();
+        self.by_day
+            .iter()
+            .flat_map(|by_day| pick_weekdays(&days, *by_day))
+            .collect()
+    }
+}
+
+impl fmt::Display for RRule {
+    /// Canonical `FREQ=...;...` form, without the `RRULE:` prefix.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let freq = match self.freq {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={freq}")?;
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_month.is_empty() {
+            write!(f, ";BYMONTH={}", join(self.by_month.iter()))?;
+        }
+        if !self.by_month_day.is_empty() {
+            write!(f, ";BYMONTHDAY={}", join(self.by_month_day.iter()))?;
+        }
+        if !self.by_day.is_empty() {
+            let days = self.by_day.iter().map(|day| {
+                let code = WEEKDAY_CODES
+                    .iter()
+                    .find(|(_, weekday)| *weekday == day.weekday)
+                    .map(|(code, _)| *code)
+                    .unwrap_or("MO");
+                match day.ordinal {
+                    Some(ordinal) => format!("{ordinal}{code}"),
+                    None => code.to_string(),
+                }
+            });
+            write!(f, ";BYDAY={}", join(days))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        match self.until {
+            Some(Until::Date(date)) => write!(f, ";UNTIL={}", date.format("%Y%m%d"))?,
+            Some(Until::Local(local)) => write!(f, ";UNTIL={}", local.format("%Y%m%dT%H%M%S"))?,
+            Some(Until::Utc(utc)) => write!(f, ";UNTIL={}", utc.format("%Y%m%dT%H%M%SZ"))?,
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+fn join<T: ToString>(values: impl Iterator<Item = T>) -> String {
+    values
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_by_day(text: &str) -> Result<ByDay, String> {
+    let text = text.trim();
+    // The weekday code is the last two characters; split on a char boundary
+    // so a stray multi-byte character is rejected rather than panicking.
+    let split = text
+        .char_indices()
+        .rev()
+        .nth(1)
+        .map_or(0, |(index, _)| index);
+    let (ordinal, code) = text.split_at(split);
+    let weekday = WEEKDAY_CODES
+        .iter()
+        .find(|(name, _)| *name == code)
+        .map(|(_, weekday)| *weekday)
+        .ok_or_else(|| format!("Invalid RRULE weekday: {text}"))?;
+
+    let ordinal = if ordinal.is_empty() {
+        None
+    } else {
+        Some(
+            ordinal
+                .trim_start_matches('+')
+                .parse::<i32>()
+                .ok()
+                .filter(|ordinal| *ordinal != 0 && (-53..=53).contains(ordinal))
+                .ok_or_else(|| format!("Invalid RRULE weekday ordinal: {text}"))?,
+        )
+    };
+    Ok(ByDay { ordinal, weekday })
+}
+
+fn parse_until(value: &str) -> Result<Until, String> {
+    let invalid = || format!("Invalid RRULE UNTIL: {value}");
+    if let Some(utc) = value.strip_suffix('Z') {
+        let local = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+        Ok(Until::Utc(Utc.from_utc_datetime(&local)))
+    } else if value.contains('T') {
+        let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+        Ok(Until::Local(local))
+    } else {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| invalid())?;
+        Ok(Until::Date(date))
+    }
+}
+
+/// Dates among `days` (one month or year, in order) that `by_day` selects.
+fn pick_weekdays(days: &[NaiveDate], by_day: ByDay) -> Vec<NaiveDate> {
+    let matching: Vec<NaiveDate> = days
+        .iter()
+        .copied()
+        .filter(|date| date.weekday() == by_day.weekday)
+        .collect();
+    match by_day.ordinal {
+        None => matching,
+        Some(ordinal) if ordinal > 0 => matching
+            .get(ordinal as usize - 1)
+            .copied()
+            .into_iter()
+            .collect(),
+        Some(ordinal) => matching
+            .len()
+            .checked_sub(ordinal.unsigned_abs() as usize)
+            .and_then(|index| matching.get(index).copied())
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Day `day` of the month, counting back from the end when negative.
+fn resolve_month_day(year: i32, month: u32, day: i32) -> Option<NaiveDate> {
+    let last = days_in_month(year, month) as i32;
+    let day = if day < 0 { last + 1 + day } else { day };
+    if (1..=last).contains(&day) {
+        NaiveDate::from_ymd_opt(year, month, day as u32)
+    } else {
+        None
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|first| first.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(28)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration as ChronoDuration, NaiveTime};
+
+    const BERLIN: Tz = chrono_tz::Europe::Berlin;
+
+    fn start(y: i32, m: u32, d: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_time(NaiveTime::from_hms_opt(9, 0, 0).unwrap())
+    }
+
+    /// Local dates of the first `count` runs at or after `from`.
+    fn dates(rule: &str, from: NaiveDateTime, count: usize) -> Vec<NaiveDate> {
+        let rule = RRule::parse(rule).unwrap();
+        let mut after = resolve_local(&BERLIN, from) - ChronoDuration::seconds(1);
+        let mut found = Vec::new();
+        while found.len() < count {
+            let Some(next) = rule.next_after(&BERLIN, from, after) else {
+                break;
+            };
+            found.push(next.with_timezone(&BERLIN).date_naive());
+            after = next;
+        }
+        found
+    }
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn first_monday_of_each_month() {
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=1MO", start(2026, 10, 5), 3),
+            vec![ymd(2026, 10, 5), ymd(2026, 11, 2), ymd(2026, 12, 7)]
+        );
+    }
+
+    #[test]
+    fn last_friday_of_each_month() {
+        assert_eq!(
+            dates("RRULE:FREQ=MONTHLY;BYDAY=-1FR", start(2026, 10, 30), 3),
+            vec![ymd(2026, 10, 30), ymd(2026, 11, 27), ymd(2026, 12, 25)]
+        );
+    }
+
+    #[test]
+    fn every_other_week_on_several_days() {
+        assert_eq!(
+            dates("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH", start(2026, 10, 12), 4),
+            vec![
+                ymd(2026, 10, 12),
+                ymd(2026, 10, 15),
+                ymd(2026, 10, 26),
+                ymd(2026, 10, 29)
+            ]
+        );
+    }
+
+    #[test]
+    fn last_day_of_month_and_skipped_short_months() {
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYMONTHDAY=-1", start(2027, 1, 31), 3),
+            vec![ymd(2027, 1, 31), ymd(2027, 2, 28), ymd(2027, 3, 31)]
+        );
+        // Without BYMONTHDAY the start's day is used, and months without it are skipped.
+        assert_eq!(
+            dates("FREQ=MONTHLY", start(2027, 1, 31), 3),
+            vec![ymd(2027, 1, 31), ymd(2027, 3, 31), ymd(2027, 5, 31)]
+        );
+    }
+
+    #[test]
+    fn yearly_last_sunday_of_march() {
+        assert_eq!(
+            dates("FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU", start(2026, 3, 29), 3),
+            vec![ymd(2026, 3, 29), ymd(2027, 3, 28), ymd(2028, 3, 26)]
+        );
+    }
+
+    #[test]
+    fn count_and_until_end_the_rule() {
+        assert_eq!(
+            dates("FREQ=DAILY;COUNT=3", start(2026, 10, 16), 10).len(),
+            3
+        );
+        assert_eq!(
+            dates("FREQ=WEEKLY;UNTIL=20261030", start(2026, 10, 16), 10),
+            vec![ymd(2026, 10, 16), ymd(2026, 10, 23), ymd(2026, 10, 30)]
+        );
+        assert_eq!(
+            dates(
+                "FREQ=WEEKLY;UNTIL=20261030T070000Z",
+                start(2026, 10, 16),
+                10
+            ),
+            vec![ymd(2026, 10, 16), ymd(2026, 10, 23)]
+        );
+    }
+
+    #[test]
+    fn keeps_wall_clock_time_across_dst() {
+        let rule = RRule::parse("FREQ=WEEKLY").unwrap();
+        let from = start(2026, 10, 19);
+        let first = rule
+            .next_after(&BERLIN, from, Utc::now() - ChronoDuration::days(3650))
+            .unwrap();
+        let after_change = rule
+            .next_after(&BERLIN, from, first + ChronoDuration::days(7))
+            .unwrap();
+        assert_eq!(
+            after_change.with_timezone(&BERLIN).time(),
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn round_trips_to_canonical_text() {
+        let rule = RRule::parse("rrule:freq=monthly;byday=-1fr,+2mo;interval=2;count=6").unwrap();
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=MONTHLY;INTERVAL=2;BYDAY=-1FR,2MO;COUNT=6"
+        );
+        assert_eq!(RRule::parse(&rule.to_string()).unwrap(), rule);
+    }
+
+    #[test]
+    fn rejects_unsupported_rules() {
+        assert!(RRule::parse("INTERVAL=2").is_err());
+        assert!(RRule::parse("FREQ=HOURLY").is_err());
+        assert!(RRule::parse("FREQ=WEEKLY;BYDAY=1MO").is_err());
+        assert!(RRule::parse("FREQ=DAILY;COUNT=2;UNTIL=20270101").is_err());
+        assert!(RRule::parse("FREQ=MONTHLY;BYSETPOS=1").is_err());
+        assert!(RRule::parse("FREQ=MONTHLY;BYMONTHDAY=0").is_err());
+    }
+
+    #[test]
+    fn huge_intervals_run_out_instead_of_overflowing() {
+        assert!(RRule::parse("FREQ=DAILY;INTERVAL=100000000").is_err());
+        assert!(RRule::parse("FREQ=WEEKLY;INTERVAL=20000000").is_err());
+
+        let from = start(2026, 10, 12);
+        for freq in ["DAILY", "WEEKLY", "MONTHLY", "YEARLY"] {
+            let rule = RRule::parse(&format!("FREQ={freq};INTERVAL={MAX_INTERVAL}")).unwrap();
+            assert_eq!(
+                rule.next_after(&BERLIN, from, DateTime::<Utc>::MAX_UTC),
+                None
+            );
+            let _ = rule.period_dates(from.date(), u32::MAX);
+        }
+    }
+
+    #[test]
+    fn rejects_non_ascii_weekdays_without_panicking() {
+        assert!(RRule::parse("FREQ=MONTHLY;BYDAY=1É").is_err());
+        assert!(RRule::parse("FREQ=MONTHLY;BYDAY=É").is_err());
+        assert!(RRule::parse("FREQ=MONTHLY;BYDAY=1MÖ").is_err());
+        assert!(RRule::parse("FREQ=WEEKLY;BYDAY=ÉMO").is_err());
+    }
+}
@@ -0,0 +1,2161 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap},
+    sync::mpsc::{self, RecvTimeoutError},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    backend::{self, ActionBackend},
+    close::{apps_to_close, normalize_close_apps},
+    command::normalize_command,
+    conditions::{normalize_conditions, unmet_conditions, SystemProbe, DEFAULT_DEFER_MINUTES},
+    history::HistoryStore,
+    idle::{
+        check_idle, normalize_idle_trigger, IdleCheck, IdleReadings, IDLE_POLL_SECONDS,
+        IDLE_READ_TIMEOUT,
+    },
+    lock::{hash_pin, lockout_message, LockStore, PinHash},
+    model::{
+        ActionOutcome, ActionOutcomePayload, CreateTimerRequest, HistoryEntry, HistoryEvent,
+        HistoryFilter, PreActionDecision, PreActionWarningPayload, ResolvePreActionRequest,
+        SequenceRun, StepOutcome, TimerAction, TimerInfo, TimerStatus, UnmetPolicy,
+    },
+    open::normalize_open_target,
+    prewarning::{
+        format_offset, normalize_pre_warning_seconds, validate_pre_warnings, PreActionPrompter,
+    },
+    recurrence::{align_first_run, anchor_recurrence, next_occurrence, validate_recurrence},
+    sequence::{self, normalize_steps},
+    snooze::{
+        apply_snooze, available_snoozes, end_snooze, normalize_snooze_policy, snoozes_remaining,
+    },
+    store::TimerStore,
+};
+
+/// Handle to the scheduler loop.
+///
+/// All timers are driven by one background thread that sleeps until the
+/// earliest pending wake-up in its queue. Commands are sent to it as messages,
+/// so every state change is applied in one place, in order.
+#[derive(Clone)]
+pub struct Scheduler {
+    store: TimerStore,
+    history: HistoryStore,
+    locks: LockStore,
+    probe: Arc<dyn SystemProbe>,
+    idle_readings: IdleReadings,
+    tx: mpsc::Sender<Message>,
+}
+
+enum Message {
+    /// Add a new timer, storing its PIN hash, if any, with it.
+    Create {
+        info: Box<TimerInfo>,
+        lock: Option<PinHash>,
+        reply: mpsc::Sender<Result<(), String>>,
+    },
+    Insert {
+        timers: Vec<TimerInfo>,
+        reply: mpsc::Sender<Result<(), String>>,
+    },
+    Update {
+        info: Box<TimerInfo>,
+        pin: PinCheck,
+        lock: Option<PinHash>,
+        reply: mpsc::Sender<Result<TimerInfo, String>>,
+    },
+    Cancel {
+        id: String,
+        pin: PinCheck,
+        reply: mpsc::Sender<Result<bool, String>>,
+    },
+    SkipNext {
+        id: String,
+        pin: PinCheck,
+        reply: mpsc::Sender<Result<bool, String>>,
+    },
+    Pause {
+        id: String,
+        pin: PinCheck,
+        reply: mpsc::Sender<Result<bool, String>>,
+    },
+    Resume {
+        id: String,
+        pin: PinCheck,
+        reply: mpsc::Sender<Result<bool, String>>,
+    },
+    Decision {
+        prompt_id: String,
+        decision: PreActionDecision,
+        snooze_minutes: Option<u32>,
+        pin: PinCheck,
+        reply: mpsc::Sender<Result<bool, String>>,
+    },
+    /// Look up which timer a prompt belongs to.
+    PromptTimer {
+        prompt_id: String,
+        reply: mpsc::Sender<Result<Option<String>, String>>,
+    },
+    /// Sent by an action thread once the action has run.
+    Finished {
+        entry: Box<HistoryEntry>,
+        outcome: ActionOutcome,
+    },
+    /// Sent by a worker thread once a due timer's conditions are checked;
+    /// ignored if the timer's `generation` has moved on since.
+    ConditionsChecked {
+        id: String,
+        generation: u64,
+        unmet: Vec<String>,
+    },
+    /// Sent by a worker thread with an idle timer's idle time; ignored if
+    /// the timer's `generation` has moved on since.
+    IdleRead {
+        id: String,
+        generation: u64,
+        armed: bool,
+        idle: Result<u64, String>,
+    },
+    /// Sent by an action thread once a sequence step has run; ignored if the
+    /// timer's `generation` has moved on since.
+    StepFinished {
+        id: String,
+        generation: u64,
+        outcome: StepOutcome,
+    },
+}
+
+/// What the caller found when checking the PIN sent with a command. PBKDF2
+/// is slow on purpose, so PINs are checked on the calling thread and the
+/// loop only sees the verdict; see [`SchedulerLoop::check_pin`].
+#[derive(Debug, Clone)]
+enum PinCheck {
+    NotGiven,
+    Matched,
+    /// A PIN was given but no hash is stored for the timer.
+    NoStoredPin,
+    Wrong(String),
+    Throttled(String),
+}
+
+impl Scheduler {
+    /// Spawn the scheduler loop.
+    pub fn start(
+        store: TimerStore,
+        history: HistoryStore,
+        locks: LockStore,
+        backend: Arc<dyn ActionBackend>,
+        probe: Arc<dyn SystemProbe>,
+        prompter: Arc<dyn PreActionPrompter>,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let idle_readings = IdleReadings::default();
+        let scheduler_loop = SchedulerLoop {
+            store: store.clone(),
+            history: history.clone(),
+            locks: locks.clone(),
+            tx: tx.clone(),
+            backend,
+            probe: probe.clone(),
+            idle_readings: idle_readings.clone(),
+            prompter,
+            timers: HashMap::new(),
+            queue: BinaryHeap::new(),
+            prompts: HashMap::new(),
+            next_generation: 0,
+        };
+        thread::spawn(move || scheduler_loop.run(rx));
+
+        Self {
+            store,
+            history,
+            locks,
+            probe,
+            idle_readings,
+            tx,
+        }
+    }
+
+    pub fn list_timers(&self) -> Result<Vec<TimerInfo>, String> {
+        self.store.list()
+    }
+
+    pub fn create_timer(&self, mut request: CreateTimerRequest) -> Result<TimerInfo, String> {
+        let lock_pin = request.lock_pin.take();
+        let mut info = build_timer(request, Utc::now())?;
+        self.check_idle_source(&info)?;
+        // Hashed here, as hashing is slow, but stored by the loop along
+        // with the timer, so a save in between can't drop it.
+        let lock = lock_pin.map(|pin| hash_pin(&pin)).transpose()?;
+        info.locked = lock.is_some();
+        let boxed = Box::new(info.clone());
+        self.send(|reply| Message::Create {
+            info: boxed,
+            lock,
+            reply,
+        })?;
+        Ok(info)
+    }
+
+    /// Replace a timer's settings, keeping its id, creation time, run count
+    /// and lock. `pin` unlocks a locked timer for the edit.
+    pub fn update_timer(
+        &self,
+        id: &str,
+        mut request: CreateTimerRequest,
+        pin: Option<&str>,
+    ) -> Result<TimerInfo, String> {
+        let lock_pin = request.lock_pin.take();
+        let mut info = build_timer(request, Utc::now())?;
+        self.check_idle_source(&info)?;
+        let lock = lock_pin.map(|pin| hash_pin(&pin)).transpose()?;
+        info.id = id.to_string();
+        let info = Box::new(info);
+        let pin = self.check_pin(id, pin);
+        self.send(|reply| Message::Update {
+            info,
+            pin,
+            lock,
+            reply,
+        })
+    }
+
+    pub fn cancel_timer(&self, id: &str, pin: Option<&str>) -> Result<bool, String> {
+        let pin = self.check_pin(id, pin);
+        let id = id.to_string();
+        self.send(|reply| Message::Cancel { id, pin, reply })
+    }
+
+    /// Drop the upcoming occurrence of a recurring timer without running it.
+    pub fn skip_next_occurrence(&self, id: &str, pin: Option<&str>) -> Result<bool, String> {
+        let pin = self.check_pin(id, pin);
+        let id = id.to_string();
+        self.send(|reply| Message::SkipNext { id, pin, reply })
+    }
+
+    /// Stop a timer without deleting it. One-shots keep their remaining time;
+    /// recurring timers miss every run until resumed.
+    pub fn pause_timer(&self, id: &str, pin: Option<&str>) -> Result<bool, String> {
+        let pin = self.check_pin(id, pin);
+        let id = id.to_string();
+        self.send(|reply| Message::Pause { id, pin, reply })
+    }
+
+    /// Reactivate a paused timer. Like pausing, this needs a locked timer's
+    /// PIN.
+    pub fn resume_timer(&self, id: &str, pin: Option<&str>) -> Result<bool, String> {
+        let pin = self.check_pin(id, pin);
+        let id = id.to_string();
+        self.send(|reply| Message::Resume { id, pin, reply })
+    }
+
+    pub fn resolve_pre_action(&self, request: ResolvePreActionRequest) -> Result<bool, String> {
+        let pin = match request.pin.as_deref().filter(|pin| !pin.is_empty()) {
+            None => PinCheck::NotGiven,
+            Some(pin) => {
+                let prompt_id = request.prompt_id.clone();
+                match self.send(|reply| Message::PromptTimer { prompt_id, reply })? {
+                    Some(timer_id) => self.check_pin(&timer_id, Some(pin)),
+                    None => return Ok(false),
+                }
+            }
+        };
+        self.send(|reply| Message::Decision {
+            prompt_id: request.prompt_id,
+            decision: request.decision,
+            snooze_minutes: request.snooze_minutes,
+            pin,
+            reply,
+        })
+    }
+
+    pub fn list_history(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>, String> {
+        self.history.list(filter)
+    }
+
+    /// Delete matching history entries, returning how many were removed.
+    pub fn clear_history(&self, filter: &HistoryFilter) -> Result<usize, String> {
+        self.history.clear(filter)
+    }
+
+    /// Reload persisted timers, rolling overdue recurring timers forward and
+    /// dropping overdue one-shots and recurring timers that have ended.
+    /// Paused and done timers come back untouched, and sequence runs pick
+    /// up at the step they had reached.
+    pub fn restore_timers(&self) -> Result<(), String> {
+        let restored = self.store.load_persisted_infos()?;
+        if restored.is_empty() {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        let mut timers = Vec::with_capacity(restored.len());
+        for mut info in restored {
+            if info.sequence_run.is_some() {
+                // Mid-run: the occurrence isn't over until its last step.
+            } else if info.idle_trigger.is_some() {
+                end_snooze(&mut info);
+            } else if info.status == TimerStatus::Active && info.target_time <= now {
+                if info.recurrence.is_none() {
+                    continue;
+                }
+                let scheduled = end_snooze(&mut info);
+                let Some(next) = info.recurrence.as_ref().and_then(|recurrence| {
+                    next_occurrence(scheduled, recurrence, info.occurrence_count, now)
+                }) else {
+                    let _ = self.history.append(&ended_entry(&info));
+                    continue;
+                };
+                info.target_time = next;
+            }
+            timers.push(info);
+        }
+
+        self.send(|reply| Message::Insert { timers, reply })
+    }
+
+    /// Refuse an idle timer where idle time can't be read, rather than let
+    /// it wait for idleness it will never see. A reading the loop took
+    /// moments ago is reused; otherwise a worker thread takes one, so a hung
+    /// probe can't hold the caller for long.
+    fn check_idle_source(&self, info: &TimerInfo) -> Result<(), String> {
+        if info.idle_trigger.is_none() {
+            return Ok(());
+        }
+        let idle = self.idle_readings.recent().unwrap_or_else(|| {
+            let (tx, rx) = mpsc::channel();
+            let probe = self.probe.clone();
+            let readings = self.idle_readings.clone();
+            thread::spawn(move || {
+                let idle = probe.idle_seconds();
+                readings.record(&idle);
+                let _ = tx.send(idle);
+            });
+            rx.recv_timeout(IDLE_READ_TIMEOUT)
+                .unwrap_or_else(|_| Err("reading idle time timed out".to_string()))
+        });
+        idle.map(|_| ())
+            .map_err(|err| format!("Idle timers can't run here: {err}"))
+    }
+
+    fn check_pin(&self, id: &str, pin: Option<&str>) -> PinCheck {
+        let Some(pin) = pin.filter(|pin| !pin.is_empty()) else {
+            return PinCheck::NotGiven;
+        };
+        if let Some(wait) = self.locks.lockout(id) {
+            return PinCheck::Throttled(lockout_message(wait));
+        }
+        match self.locks.verify(id, Some(pin)) {
+            Ok(true) => PinCheck::Matched,
+            Ok(false) => PinCheck::NoStoredPin,
+            Err(err) => PinCheck::Wrong(err),
+        }
+    }
+
+    /// Send a message to the loop and wait for its reply.
+    fn send<T>(
+        &self,
+        build: impl FnOnce(mpsc::Sender<Result<T, String>>) -> Message,
+    ) -> Result<T, String> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.tx
+            .send(build(reply_tx))
+            .map_err(|_| "Scheduler is not running".to_string())?;
+        reply_rx
+            .recv()
+            .map_err(|_| "Scheduler is not running".to_string())?
+    }
+}
+
+// ─── Scheduler loop ───────────────────────────────────────────────
+
+/// Where a timer is within its current occurrence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Stage {
+    /// Waiting to show the pre-warning given `seconds` before the run.
+    Warning { seconds: u32 },
+    /// Waiting for `target_time` to run the action, and then for its
+    /// conditions to be checked.
+    Fire,
+    /// Waiting to run, or running, the next step of the timer's
+    /// `sequence_run`.
+    Step,
+    /// Waiting to check an idle-triggered timer's idle time; see
+    /// [`check_idle`].
+    Idle { armed: bool },
+}
+
+struct ScheduledTimer {
+    info: TimerInfo,
+    stage: Stage,
+    generation: u64,
+    /// Prompt opened by an earlier warning that is still awaiting a decision.
+    prompt: Option<String>,
+    /// Last decision made at this occurrence's prompt, for the history.
+    decision: Option<PreActionDecision>,
+    /// Whether this occurrence's action has started; a one-shot that ran
+    /// is kept, done, with its outcome.
+    ran: bool,
+}
+
+/// Queue entry; stale once the timer's generation moves on.
+#[derive(Debug, PartialEq, Eq)]
+struct Wakeup {
+    due: DateTime<Utc>,
+    generation: u64,
+    timer_id: String,
+}
+
+impl Ord for Wakeup {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.due
+            .cmp(&other.due)
+            .then(self.generation.cmp(&other.generation))
+    }
+}
+
+impl PartialOrd for Wakeup {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+struct SchedulerLoop {
+    store: TimerStore,
+    history: HistoryStore,
+    locks: LockStore,
+    /// Handed to action threads so they can report back.
+    tx: mpsc::Sender<Message>,
+    backend: Arc<dyn ActionBackend>,
+    probe: Arc<dyn SystemProbe>,
+    idle_readings: IdleReadings,
+    prompter: Arc<dyn PreActionPrompter>,
+    timers: HashMap<String, ScheduledTimer>,
+    queue: BinaryHeap<Reverse<Wakeup>>,
+    /// Open prompt id -> timer id.
+    prompts: HashMap<String, String>,
+    next_generation: u64,
+}
+
+impl SchedulerLoop {
+    fn run(mut self, rx: mpsc::Receiver<Message>) {
+        loop {
+            let received = match self.next_wait() {
+                Some(wait) => rx.recv_timeout(wait),
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            match received {
+                Ok(message) => self.handle_message(message),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            self.run_due();
+        }
+    }
+
+    /// Time until the earliest live wake-up, discarding stale entries.
+    fn next_wait(&mut self) -> Option<Duration> {
+        while let Some(Reverse(next)) = self.queue.peek() {
+            if self.is_live(next) {
+                let wait = (next.due - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+                return Some(wait);
+            }
+            self.queue.pop();
+        }
+        None
+    }
+
+    fn is_live(&self, wakeup: &Wakeup) -> bool {
+        self.timers
+            .get(&wakeup.timer_id)
+            .is_some_and(|timer| timer.generation == wakeup.generation)
+    }
+
+    fn run_due(&mut self) {
+        loop {
+            let now = Utc::now();
+            let Some(Reverse(next)) = self.queue.peek() else {
+                return;
+            };
+            if next.due > now {
+                return;
+            }
+
+            let Some(Reverse(wakeup)) = self.queue.pop() else {
+                return;
+            };
+            if self.is_live(&wakeup) {
+                self.on_due(&wakeup.timer_id, now);
+            }
+        }
+    }
+
+    fn handle_message(&mut self, message: Message) {
+        match message {
+            Message::Create { info, lock, reply } => {
+                let stored = match lock {
+                    Some(lock) => self.locks.insert(&info.id, lock),
+                    None => Ok(()),
+                };
+                let result = stored.and_then(|_| {
+                    self.insert(*info);
+                    self.persist()
+                });
+                let _ = reply.send(result);
+            }
+            Message::Insert { timers, reply } => {
+                for info in timers {
+                    self.insert(info);
+                }
+                let _ = reply.send(self.persist());
+            }
+            Message::Update {
+                info,
+                pin,
+                lock,
+                reply,
+            } => {
+                let result = self.update(*info, &pin, lock);
+                let _ = reply.send(result);
+            }
+            Message::Cancel { id, pin, reply } => {
+                let result = match self.check_pin(&id, &pin, "cancel") {
+                    Err(err) => Err(err),
+                    Ok(()) => {
+                        self.end_sequence(&id, Some("cancelled"));
+                        self.interrupt(&id);
+                        match self.timers.remove(&id) {
+                            Some(_) => self.persist().map(|_| true),
+                            None => Ok(false),
+                        }
+                    }
+                };
+                let _ = reply.send(result);
+            }
+            Message::SkipNext { id, pin, reply } => {
+                let result = match self.timers.get(&id) {
+                    None => Ok(false),
+                    Some(timer) if timer.info.recurrence.is_none() => {
+                        Err("Only recurring timers can skip an occurrence".to_string())
+                    }
+                    Some(_) => self.check_pin(&id, &pin, "skip a run").map(|_| {
+                        self.interrupt(&id);
+                        // Skipping mid-run skips the steps still to come.
+                        if !self.end_sequence(&id, Some("skipped")) {
+                            self.record_skip(&id, "Skipped from the timer list");
+                        }
+                        self.finish_occurrence(&id);
+                        true
+                    }),
+                };
+                let _ = reply.send(result);
+            }
+            Message::Pause { id, pin, reply } => {
+                let result = self.pause(&id, &pin);
+                let _ = reply.send(result);
+            }
+            Message::Resume { id, pin, reply } => {
+                let result = match self.timers.get(&id).map(|timer| timer.info.status) {
+                    Some(TimerStatus::Paused) => {
+                        self.check_pin(&id, &pin, "resume").and_then(|_| {
+                            self.resume(&id, Utc::now());
+                            self.persist().map(|_| true)
+                        })
+                    }
+                    Some(TimerStatus::Active | TimerStatus::Done) | None => Ok(false),
+                };
+                let _ = reply.send(result);
+            }
+            Message::Decision {
+                prompt_id,
+                decision,
+                snooze_minutes,
+                pin,
+                reply,
+            } => {
+                let result = match self.prompts.get(&prompt_id).cloned() {
+                    Some(timer_id) => self
+                        .apply_decision(&timer_id, decision, snooze_minutes, &pin)
+                        .map(|_| true),
+                    None => Ok(false),
+                };
+                let _ = reply.send(result);
+            }
+            Message::PromptTimer { prompt_id, reply } => {
+                let _ = reply.send(Ok(self.prompts.get(&prompt_id).cloned()));
+            }
+            Message::Finished { entry, outcome } => self.record_outcome(*entry, outcome),
+            Message::ConditionsChecked {
+                id,
+                generation,
+                unmet,
+            } => self.conditions_checked(&id, generation, unmet),
+            Message::IdleRead {
+                id,
+                generation,
+                armed,
+                idle,
+            } => self.idle_read(&id, generation, armed, idle),
+            Message::StepFinished {
+                id,
+                generation,
+                outcome,
+            } => self.step_finished(&id, generation, outcome),
+        }
+    }
+
+    /// Add a timer and queue its first stage.
+    fn insert(&mut self, mut info: TimerInfo) {
+        let id = info.id.clone();
+        // The stored PIN decides; `locked` only shows it.
+        info.locked |= self.locks.is_locked(&id);
+        let ran = info.sequence_run.is_some();
+        self.timers.insert(
+            id.clone(),
+            ScheduledTimer {
+                info,
+                stage: Stage::Fire,
+                generation: 0,
+                prompt: None,
+                decision: None,
+                ran,
+            },
+        );
+        self.schedule_occurrence(&id);
+    }
+
+    /// Apply an edit, once the PIN of a locked timer checks out.
+    fn update(
+        &mut self,
+        mut info: TimerInfo,
+        pin: &PinCheck,
+        lock: Option<PinHash>,
+    ) -> Result<TimerInfo, String> {
+        if !self.timers.contains_key(&info.id) {
+            return Err("Timer not found".to_string());
+        }
+        self.check_pin(&info.id, pin, "edit")?;
+        if let Some(lock) = lock {
+            self.locks.insert(&info.id, lock)?;
+        }
+        self.end_sequence(&info.id, Some("edited"));
+
+        let Some(timer) = self.timers.get(&info.id) else {
+            return Err("Timer not found".to_string());
+        };
+        let (created_at, occurrence_count, status, locked, last_outcome) = (
+            timer.info.created_at,
+            timer.info.occurrence_count,
+            timer.info.status,
+            timer.info.locked,
+            timer.info.last_outcome.clone(),
+        );
+
+        info.created_at = created_at;
+        info.occurrence_count = occurrence_count;
+        info.locked = locked || self.locks.is_locked(&info.id);
+        info.last_outcome = last_outcome;
+        if status == TimerStatus::Paused {
+            info.status = TimerStatus::Paused;
+            info.remaining_seconds = remaining_seconds(&info, Utc::now());
+        }
+
+        // A late answer to a prompt opened before the edit is ignored.
+        self.interrupt(&info.id);
+        if let Some(timer) = self.timers.get_mut(&info.id) {
+            timer.info = info.clone();
+            timer.decision = None;
+        }
+        self.schedule_occurrence(&info.id);
+        self.persist().map(|_| info)
+    }
+
+    fn pause(&mut self, id: &str, pin: &PinCheck) -> Result<bool, String> {
+        if self.timers.get(id).map(|timer| timer.info.status) != Some(TimerStatus::Active) {
+            return Ok(false);
+        }
+        self.check_pin(id, pin, "pause")?;
+        // A sequence run under way ends here, and with it the occurrence.
+        if self.end_sequence(id, Some("paused")) {
+            self.finish_occurrence(id);
+        }
+
+        self.interrupt(id);
+        if let Some(timer) = self.timers.get_mut(id) {
+            timer.info.status = TimerStatus::Paused;
+            timer.info.remaining_seconds = remaining_seconds(&timer.info, Utc::now());
+        }
+        self.persist().map(|_| true)
+    }
+
+    /// Check the PIN guarding `operation` on a locked timer, logging wrong
+    /// guesses to the history. A stored PIN locks the timer whatever
+    /// `timers.json` says, and a timer marked locked stays locked even if
+    /// its stored PIN has gone missing.
+    fn check_pin(&self, id: &str, pin: &PinCheck, operation: &str) -> Result<(), String> {
+        let Some(timer) = self.timers.get(id) else {
+            return Ok(());
+        };
+        if !timer.info.locked && !self.locks.is_locked(id) {
+            return Ok(());
+        }
+        match pin {
+            PinCheck::Matched => Ok(()),
+            PinCheck::NotGiven => Err("This timer is locked. Enter its PIN.".to_string()),
+            PinCheck::NoStoredPin => {
+                Err("This timer's PIN data is missing, so it can't be unlocked.".to_string())
+            }
+            PinCheck::Throttled(err) => Err(err.clone()),
+            PinCheck::Wrong(err) => {
+                let _ = self.history.append(&history_entry(
+                    &timer.info,
+                    HistoryEvent::PinRejected,
+                    Some(format!("Wrong PIN given to {operation}")),
+                ));
+                Err(err.clone())
+            }
+        }
+    }
+
+    /// Stop the timer's current occurrence: close its prompt and drop its
+    /// queued wake-up.
+    fn interrupt(&mut self, id: &str) {
+        let Some(timer) = self.timers.get_mut(id) else {
+            return;
+        };
+        self.next_generation += 1;
+        timer.generation = self.next_generation;
+        timer.stage = Stage::Fire;
+        self.clear_prompt(id);
+    }
+
+    /// Close the timer's open prompt; a late answer to it is ignored.
+    fn clear_prompt(&mut self, id: &str) {
+        if let Some(prompt_id) = self
+            .timers
+            .get_mut(id)
+            .and_then(|timer| timer.prompt.take())
+        {
+            self.prompts.remove(&prompt_id);
+        }
+        self.prompter.close(id);
+    }
+
+    /// Reactivate a paused timer: one-shots get back their remaining time,
+    /// recurring timers move to their first run after `now`.
+    fn resume(&mut self, id: &str, now: DateTime<Utc>) {
+        let Some(timer) = self.timers.get_mut(id) else {
+            return;
+        };
+        timer.info.status = TimerStatus::Active;
+        let remaining = timer.info.remaining_seconds.take();
+
+        if timer.info.idle_trigger.is_some() {
+            // Idle timers have no run time to restore.
+        } else if timer.info.recurrence.is_none() {
+            let remaining = remaining.unwrap_or_default().max(0);
+            timer.info.target_time = now + ChronoDuration::seconds(remaining);
+        } else if timer.info.target_time <= now {
+            let scheduled = end_snooze(&mut timer.info);
+            let next = timer.info.recurrence.as_ref().and_then(|recurrence| {
+                next_occurrence(scheduled, recurrence, timer.info.occurrence_count, now)
+            });
+            match next {
+                Some(next_run) => timer.info.target_time = next_run,
+                None => {
+                    if let Some(timer) = self.timers.remove(id) {
+                        let _ = self.history.append(&ended_entry(&timer.info));
+                    }
+                    return;
+                }
+            }
+        }
+        self.schedule_occurrence(id);
+    }
+
+    /// Queue the first stage of the timer's current occurrence; paused and
+    /// done timers are left out of the queue.
+    fn schedule_occurrence(&mut self, id: &str) {
+        let Some(timer) = self.timers.get(id) else {
+            return;
+        };
+        if timer.info.status != TimerStatus::Active {
+            return;
+        }
+        if let Some(run) = &timer.info.sequence_run {
+            if run.step_started_at.is_some() {
+                // Cut off by a restart, most likely one the step caused.
+                self.step_interrupted(id);
+                return;
+            }
+            let due = run.next_at;
+            self.set_stage(id, Stage::Step, due);
+            return;
+        }
+        if timer.info.idle_trigger.is_some() {
+            self.set_stage(id, Stage::Idle { armed: true }, Utc::now());
+            return;
+        }
+        let (stage, due) = next_stage(&timer.info, None, Utc::now());
+        self.set_stage(id, stage, due);
+    }
+
+    fn set_stage(&mut self, id: &str, stage: Stage, due: DateTime<Utc>) {
+        let Some(timer) = self.timers.get_mut(id) else {
+            return;
+        };
+        self.next_generation += 1;
+        timer.stage = stage;
+        timer.generation = self.next_generation;
+        self.queue.push(Reverse(Wakeup {
+            due,
+            generation: self.next_generation,
+            timer_id: id.to_string(),
+        }));
+    }
+
+    fn on_due(&mut self, id: &str, now: DateTime<Utc>) {
+        let Some(timer) = self.timers.get(id) else {
+            return;
+        };
+
+        match timer.stage.clone() {
+            Stage::Warning { seconds } => {
+                // An unanswered prompt is updated in place rather than replaced.
+                let prompt_id = timer
+                    .prompt
+                    .clone()
+                    .unwrap_or_else(|| Uuid::new_v4().to_string());
+                let payload = PreActionWarningPayload {
+                    prompt_id: prompt_id.clone(),
+                    timer_id: id.to_string(),
+                    action: timer.info.action.clone(),
+                    warning_seconds: seconds,
+                    countdown_seconds: (timer.info.target_time - now)
+                        .num_seconds()
+                        .clamp(1, u32::MAX as i64) as u32,
+                    snooze_options_minutes: available_snoozes(&timer.info, now),
+                    snoozes_remaining: snoozes_remaining(&timer.info),
+                    locked: timer.info.locked,
+                    apps: apps_to_close(&timer.info),
+                };
+                let (next, due) = next_stage(&timer.info, Some(seconds), now);
+
+                if self.prompter.open(&payload).is_ok() {
+                    self.prompts.insert(prompt_id.clone(), id.to_string());
+                    if let Some(timer) = self.timers.get_mut(id) {
+                        timer.prompt = Some(prompt_id);
+                    }
+                }
+                self.set_stage(id, next, due);
+            }
+            Stage::Fire => {
+                self.clear_prompt(id);
+                self.run_scheduled(id);
+            }
+            Stage::Idle { armed } => self.read_idle(id, armed),
+            Stage::Step => self.run_step(id),
+        }
+    }
+
+    /// Read an idle timer's idle time. Reading it can mean starting a
+    /// process, so a worker thread does it and reports back with an
+    /// `IdleRead` message.
+    fn read_idle(&mut self, id: &str, armed: bool) {
+        let Some(timer) = self.timers.get(id) else {
+            return;
+        };
+        let probe = self.probe.clone();
+        let readings = self.idle_readings.clone();
+        let tx = self.tx.clone();
+        let id = id.to_string();
+        let generation = timer.generation;
+        thread::spawn(move || {
+            let idle = probe.idle_seconds();
+            readings.record(&idle);
+            let _ = tx.send(Message::IdleRead {
+                id,
+                generation,
+                armed,
+                idle,
+            });
+        });
+    }
+
+    /// Run an idle-triggered timer if the user has been away long enough,
+    /// or work out when to look again, unless the timer was paused, edited
+    /// or deleted while `read_idle` ran. While idle time can't be read, the
+    /// timer keeps retrying, and says why once in its history.
+    fn idle_read(&mut self, id: &str, generation: u64, armed: bool, idle: Result<u64, String>) {
+        let Some(timer) = self
+            .timers
+            .get_mut(id)
+            .filter(|timer| timer.generation == generation)
+        else {
+            return;
+        };
+        let Some(trigger) = timer.info.idle_trigger else {
+            return;
+        };
+        let now = Utc::now();
+        let check = match idle {
+            Ok(idle) => {
+                if timer.info.idle_error.take().is_some() {
+                    let _ = self.persist();
+                }
+                check_idle(&trigger, armed, idle)
+            }
+            Err(err) => {
+                if timer.info.idle_error.is_none() {
+                    let detail = format!("Can't read idle time: {err}");
+                    let _ = self.history.append(&history_entry(
+                        &timer.info,
+                        HistoryEvent::IdleUnavailable,
+                        Some(detail),
+                    ));
+                    timer.info.idle_error = Some(err);
+                    let _ = self.persist();
+                }
+                IdleCheck::Wait {
+                    armed,
+                    after: Duration::from_secs(IDLE_POLL_SECONDS),
+                }
+            }
+        };
+
+        let Some(timer) = self.timers.get_mut(id) else {
+            return;
+        };
+        match check {
+            IdleCheck::Run => {
+                timer.info.target_time = now;
+                self.run_scheduled(id);
+            }
+            IdleCheck::Wait { armed, after } => {
+                if armed {
+                    // The user is back, so a deferred run is no longer due.
+                    end_snooze(&mut timer.info);
+                }
+                let due = now
+                    + ChronoDuration::from_std(after).unwrap_or_else(|_| ChronoDuration::zero());
+                self.set_stage(id, Stage::Idle { armed }, due);
+            }
+        }
+    }
+
+    /// Act on an answer to the prompt. Continuing leaves the remaining
+    /// warnings and the run queued; every other decision replaces them.
+    /// A snooze the timer's policy does not allow, or a snooze or cancel
+    /// without a locked timer's PIN, is rejected and leaves the prompt open.
+    fn apply_decision(
+        &mut self,
+        id: &str,
+        decision: PreActionDecision,
+        snooze_minutes: Option<u32>,
+        pin: &PinCheck,
+    ) -> Result<(), String> {
+        match decision {
+            PreActionDecision::Snooze => self.check_pin(id, pin, "snooze")?,
+            PreActionDecision::CancelAction => self.check_pin(id, pin, "cancel a run")?,
+            PreActionDecision::RunNow | PreActionDecision::ContinueScheduled => {}
+        }
+        let Some(timer) = self.timers.get_mut(id) else {
+            return Ok(());
+        };
+        if matches!(decision, PreActionDecision::Snooze) {
+            let now = Utc::now();
+            let minutes = snooze_minutes
+                .or_else(|| available_snoozes(&timer.info, now).first().copied())
+                .ok_or_else(|| "No snoozes left for this run.".to_string())?;
+            apply_snooze(&mut timer.info, minutes, now)?;
+        }
+        timer.decision = Some(decision);
+        self.clear_prompt(id);
+
+        match decision {
+            PreActionDecision::RunNow => self.run_now(id, None),
+            PreActionDecision::Snooze => {
+                self.schedule_occurrence(id);
+                let _ = self.persist();
+            }
+            PreActionDecision::CancelAction => {
+                self.record_skip(id, "Cancelled at the pre-warning");
+                self.finish_occurrence(id);
+            }
+            PreActionDecision::ContinueScheduled => {}
+        }
+        Ok(())
+    }
+
+    /// Run a due occurrence if its conditions hold; otherwise skip it, put
+    /// it off or run it anyway, as the timer asks, and log why. Checking
+    /// conditions can take a while, so a worker thread does it and reports
+    /// back with a `ConditionsChecked` message.
+    fn run_scheduled(&mut self, id: &str) {
+        let Some(timer) = self.timers.get(id) else {
+            return;
+        };
+        let Some(conditions) = timer.info.conditions.clone() else {
+            self.run_now(id, None);
+            return;
+        };
+        let probe = self.probe.clone();
+        let tx = self.tx.clone();
+        let id = id.to_string();
+        let generation = timer.generation;
+        thread::spawn(move || {
+            let unmet = unmet_conditions(probe.as_ref(), &conditions);
+            let _ = tx.send(Message::ConditionsChecked {
+                id,
+                generation,
+                unmet,
+            });
+        });
+    }
+
+    /// Act on a condition check started by `run_scheduled`, unless the timer
+    /// was paused, edited or deleted while it ran.
+    fn conditions_checked(&mut self, id: &str, generation: u64, unmet: Vec<String>) {
+        let Some(timer) = self
+            .timers
+            .get(id)
+            .filter(|timer| timer.generation == generation)
+        else {
+            return;
+        };
+        let Some(conditions) = timer.info.conditions.as_ref().filter(|_| !unmet.is_empty()) else {
+            self.run_now(id, None);
+            return;
+        };
+        let reasons = unmet.join("; ");
+
+        match conditions.when_unmet {
+            UnmetPolicy::Skip => {
+                self.record_skip(id, &format!("Conditions not met: {reasons}"));
+                self.finish_occurrence(id);
+            }
+            UnmetPolicy::RunAnyway => {
+                self.run_now(
+                    id,
+                    Some(format!("Ran although conditions were not met: {reasons}")),
+                );
+            }
+            UnmetPolicy::Defer => {
+                let minutes = conditions.defer_minutes.unwrap_or(DEFAULT_DEFER_MINUTES);
+                self.defer(id, minutes, &reasons, Utc::now());
+            }
+        }
+    }
+
+    /// Put the current occurrence off by `minutes`, straight to its run
+    /// without repeating the warnings. Like a snooze, the next occurrence
+    /// still follows the original schedule. Idle timers check again that
+    /// the user is still away.
+    fn defer(&mut self, id: &str, minutes: u32, reasons: &str, now: DateTime<Utc>) {
+        let Some(timer) = self.timers.get_mut(id) else {
+            return;
+        };
+        timer
+            .info
+            .snoozed_from
+            .get_or_insert(timer.info.target_time);
+        timer.info.target_time = now + ChronoDuration::minutes(minutes as i64);
+        let due = timer.info.target_time;
+        let detail = format!("Deferred {}: {reasons}", format_offset(minutes * 60));
+        let _ = self.history.append(&history_entry(
+            &timer.info,
+            HistoryEvent::Deferred,
+            Some(detail),
+        ));
+        let stage = match timer.info.idle_trigger {
+            Some(_) => Stage::Idle { armed: true },
+            None => Stage::Fire,
+        };
+        self.set_stage(id, stage, due);
+        let _ = self.persist();
+    }
+
+    /// Run the occurrence's action, logged with `detail`, and move on to the
+    /// next occurrence. A sequence only starts here: its steps are queued one
+    /// at a time, and the occurrence finishes after the last.
+    fn run_now(&mut self, id: &str, detail: Option<String>) {
+        let Some(timer) = self.timers.get_mut(id) else {
+            return;
+        };
+        timer.ran = true;
+        if timer.info.action != TimerAction::Sequence {
+            self.execute(id, detail);
+            self.finish_occurrence(id);
+            return;
+        }
+
+        let mut entry = history_entry(&timer.info, HistoryEvent::Executed, detail);
+        entry.decision = timer.decision;
+        let now = Utc::now();
+        timer.info.sequence_run = Some(SequenceRun {
+            next_step: 0,
+            next_at: now,
+            step_started_at: None,
+            steps: Vec::new(),
+            entry,
+        });
+        self.queue_step(id, now);
+    }
+
+    /// Queue the sequence run's next step for when its delay is over, or end
+    /// the run once no steps are left.
+    fn queue_step(&mut self, id: &str, now: DateTime<Utc>) {
+        let Some(timer) = self.timers.get_mut(id) else {
+            return;
+        };
+        let Some(run) = timer.info.sequence_run.as_mut() else {
+            return;
+        };
+        match timer
+            .info
+            .steps
+            .as_deref()
+            .unwrap_or_default()
+            .get(run.next_step)
+        {
+            Some(step) => {
+                run.next_at = now + ChronoDuration::seconds(step.delay_seconds as i64);
+                let due = run.next_at;
+                self.set_stage(id, Stage::Step, due);
+                let _ = self.persist();
+            }
+            None => {
+                self.end_sequence(id, None);
+                self.finish_occurrence(id);
+            }
+        }
+    }
+
+    /// Run the sequence's next step off the loop thread, once it is saved
+    /// as started. Its outcome comes back as a `StepFinished` message.
+    fn run_step(&mut self, id: &str) {
+        let Some(timer) = self.timers.get_mut(id) else {
+            return;
+        };
+        let Some(run) = timer.info.sequence_run.as_mut() else {
+            return;
+        };
+        let Some(step) = timer
+            .info
+            .steps
+            .as_ref()
+            .and_then(|steps| steps.get(run.next_step).cloned())
+        else {
+            return;
+        };
+        run.step_started_at = Some(Utc::now());
+        let generation = timer.generation;
+        let _ = self.persist();
+
+        let backend = self.backend.clone();
+        let tx = self.tx.clone();
+        let id = id.to_string();
+        thread::spawn(move || {
+            let outcome = sequence::run_step(backend, &step);
+            let _ = tx.send(Message::StepFinished {
+                id,
+                generation,
+                outcome,
+            });
+        });
+    }
+
+    /// Note a finished step and queue the next, unless the timer was
+    /// stopped, edited or deleted while the step ran.
+    fn step_finished(&mut self, id: &str, generation: u64, outcome: StepOutcome) {
+        if self
+            .timers
+            .get(id)
+            .is_some_and(|timer| timer.generation == generation)
+        {
+            self.finish_step(id, outcome);
+        }
+    }
+
+    /// Count a step that was running when the app stopped as done rather
+    /// than run it again.
+    fn step_interrupted(&mut self, id: &str) {
+        let Some(timer) = self.timers.get(id) else {
+            return;
+        };
+        let Some(run) = timer.info.sequence_run.as_ref() else {
+            return;
+        };
+        let Some(step) = timer
+            .info
+            .steps
+            .as_deref()
+            .unwrap_or_default()
+            .get(run.next_step)
+        else {
+            return;
+        };
+        let outcome = sequence::interrupted_step(step, run.step_started_at);
+        self.finish_step(id, outcome);
+    }
+
+    fn finish_step(&mut self, id: &str, outcome: StepOutcome) {
+        let Some(timer) = self.timers.get_mut(id) else {
+            return;
+        };
+        let Some(run) = timer.info.sequence_run.as_mut() else {
+            return;
+        };
+        let Some(step) = timer
+            .info
+            .steps
+            .as_deref()
+            .unwrap_or_default()
+            .get(run.next_step)
+        else {
+            return;
+        };
+        let go_on = sequence::continues_after(step, &outcome);
+        run.steps.push(outcome);
+        run.next_step += 1;
+        run.step_started_at = None;
+
+        if go_on {
+            self.queue_step(id, Utc::now());
+        } else {
+            self.end_sequence(id, None);
+            self.finish_occurrence(id);
+        }
+    }
+
+    /// Log the timer's sequence run with every step's outcome and clear it.
+    /// A run cut short says why it `stopped`; its remaining steps never run.
+    /// Returns whether there was a run to end.
+    fn end_sequence(&mut self, id: &str, stopped: Option<&str>) -> bool {
+        let Some(timer) = self.timers.get_mut(id) else {
+            return false;
+        };
+        let Some(run) = timer.info.sequence_run.take() else {
+            return false;
+        };
+        let steps = timer.info.steps.as_deref().unwrap_or_default();
+        let mut entry = run.entry;
+        if let Some(why) = stopped {
+            let note = format!(
+                "Stopped after {} of {} steps: {why}",
+                run.steps.len(),
+                steps.len()
+            );
+            entry.detail = Some(match entry.detail {
+                Some(detail) => format!("{detail}; {note}"),
+                None => note,
+            });
+        }
+        let outcome = sequence::sequence_outcome(steps, run.steps);
+        self.record_outcome(entry, outcome);
+        true
+    }
+
+    /// Run a single action off the loop thread, since popups block until
+    /// dismissed. The outcome comes back as a `Finished` message, logged
+    /// with `detail`.
+    fn execute(&self, id: &str, detail: Option<String>) {
+        let Some(timer) = self.timers.get(id) else {
+            return;
+        };
+        let backend = self.backend.clone();
+        let tx = self.tx.clone();
+        let info = timer.info.clone();
+        let mut entry = history_entry(&timer.info, HistoryEvent::Executed, detail);
+        entry.decision = timer.decision;
+        thread::spawn(move || {
+            let outcome = backend::run_action(backend.as_ref(), &info);
+            let _ = tx.send(Message::Finished {
+                entry: Box::new(entry),
+                outcome,
+            });
+        });
+    }
+
+    /// Log a finished action, keep its outcome on the timer if it still
+    /// exists, and pass it on to the UI.
+    fn record_outcome(&mut self, mut entry: HistoryEntry, outcome: ActionOutcome) {
+        entry.error = outcome.error();
+        entry.outcome = Some(outcome.clone());
+        let _ = self.history.append(&entry);
+
+        if let Some(timer) = self.timers.get_mut(&entry.timer_id) {
+            timer.info.last_outcome = Some(outcome.clone());
+            let _ = self.persist();
+        }
+        self.prompter.action_finished(&ActionOutcomePayload {
+            timer_id: entry.timer_id,
+            action: entry.action,
+            outcome,
+        });
+    }
+
+    /// Log that the timer's current occurrence will not run.
+    fn record_skip(&self, id: &str, detail: &str) {
+        if let Some(timer) = self.timers.get(id) {
+            let mut entry =
+                history_entry(&timer.info, HistoryEvent::Skipped, Some(detail.to_string()));
+            entry.decision = timer.decision;
+            let _ = self.history.append(&entry);
+        }
+    }
+
+    /// Move a timer to its next occurrence, or drop it when it has none. A
+    /// one-shot that ran is kept as done instead, so its outcome, which
+    /// comes back later, is stored with it. Idle timers wait for the user
+    /// to come back before they can run again.
+    fn finish_occurrence(&mut self, id: &str) {
+        let Some(timer) = self.timers.get_mut(id) else {
+            return;
+        };
+        timer.info.occurrence_count += 1;
+        timer.decision = None;
+        let ran = std::mem::take(&mut timer.ran);
+        let scheduled = end_snooze(&mut timer.info);
+
+        if timer.info.idle_trigger.is_some() {
+            let due = Utc::now() + ChronoDuration::seconds(IDLE_POLL_SECONDS as i64);
+            self.set_stage(id, Stage::Idle { armed: false }, due);
+            let _ = self.persist();
+            return;
+        }
+
+        let next = timer.info.recurrence.as_ref().and_then(|recurrence| {
+            next_occurrence(
+                scheduled,
+                recurrence,
+                timer.info.occurrence_count,
+                Utc::now(),
+            )
+        });
+        match next {
+            Some(next_run) => {
+                timer.info.target_time = next_run;
+                self.schedule_occurrence(id);
+            }
+            None if ran && timer.info.recurrence.is_none() => {
+                timer.info.status = TimerStatus::Done;
+                // Drop any wake-up still queued for the run.
+                self.interrupt(id);
+            }
+            None => {
+                if let Some(timer) = self.timers.remove(id) {
+                    if timer.info.recurrence.is_some() {
+                        let _ = self.history.append(&ended_entry(&timer.info));
+                    }
+                }
+            }
+        }
+        let _ = self.persist();
+    }
+
+    /// Save the timers, dropping the PINs of any that are gone.
+    fn persist(&self) -> Result<(), String> {
+        self.store.save(
+            self.timers
+                .values()
+                .map(|timer| timer.info.clone())
+                .collect(),
+        )?;
+        self.locks.retain(|id| self.timers.contains_key(id))
+    }
+}
+
+/// Validate a create or update request into a timer with a fresh id.
+fn build_timer(request: CreateTimerRequest, now: DateTime<Utc>) -> Result<TimerInfo, String> {
+    // Idle timers come due whenever the user is away, so `target_time` is
+    // only a placeholder until they first run.
+    let idle_trigger = normalize_idle_trigger(request.idle_trigger)?;
+    if idle_trigger.is_some() && request.recurrence.is_some() {
+        return Err("Idle timers can't also repeat on a schedule".to_string());
+    }
+    let target = match idle_trigger {
+        Some(_) => now,
+        None => scheduled_target(&request, now)?,
+    };
+
+    let pre_warning_seconds = normalize_pre_warning_seconds(
+        request.pre_warning_seconds.as_ref(),
+        request.pre_warning_minutes.as_ref(),
+    )?;
+    if idle_trigger.is_some()
+        && pre_warning_seconds
+            .as_ref()
+            .is_some_and(|seconds| !seconds.is_empty())
+    {
+        return Err("Idle timers can't have pre-warnings".to_string());
+    }
+    validate_pre_warnings(
+        pre_warning_seconds.as_deref().unwrap_or_default(),
+        target,
+        request.recurrence.as_ref(),
+        now,
+    )?;
+    let snooze = normalize_snooze_policy(request.snooze)?;
+    let command = match request.action {
+        TimerAction::RunCommand => Some(normalize_command(request.command)?),
+        _ => None,
+    };
+    let open_target = match request.action {
+        TimerAction::Open => Some(normalize_open_target(request.open_target)?),
+        _ => None,
+    };
+    let close_apps = match request.action {
+        TimerAction::CloseApps => Some(normalize_close_apps(request.close_apps)?),
+        _ => None,
+    };
+    let steps = match request.action {
+        TimerAction::Sequence => Some(normalize_steps(request.steps)?),
+        _ => None,
+    };
+    let conditions = normalize_conditions(request.conditions)?;
+
+    Ok(TimerInfo {
+        id: Uuid::new_v4().to_string(),
+        action: request.action,
+        target_time: target,
+        recurrence: request
+            .recurrence
+            .map(|recurrence| anchor_recurrence(recurrence, target)),
+        pre_warning_seconds,
+        pre_warning_minutes: None,
+        snooze,
+        snooze_count: 0,
+        snoozed_from: None,
+        message: request.message.map(|msg| msg.trim().to_string()),
+        command,
+        open_target,
+        close_apps,
+        steps,
+        conditions,
+        idle_trigger,
+        idle_error: None,
+        created_at: now,
+        occurrence_count: 0,
+        status: TimerStatus::Active,
+        remaining_seconds: None,
+        locked: false,
+        last_outcome: None,
+        sequence_run: None,
+    })
+}
+
+/// First run of a timer with a set time, checked against its recurrence.
+fn scheduled_target(
+    request: &CreateTimerRequest,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, String> {
+    let requested = DateTime::parse_from_rfc3339(&request.target_time)
+        .map_err(|_| "Invalid date/time format".to_string())?
+        .with_timezone(&Utc);
+
+    validate_recurrence(request.recurrence.as_ref())?;
+
+    let target = align_first_run(requested, request.recurrence.as_ref(), now)
+        .ok_or_else(|| "Recurrence has no upcoming run".to_string())?;
+    if target <= now {
+        return Err("Selected time must be in the future".to_string());
+    }
+    if request
+        .recurrence
+        .as_ref()
+        .and_then(|recurrence| recurrence.ends_at)
+        .is_some_and(|ends_at| ends_at < target)
+    {
+        return Err("End date must be after the first run".to_string());
+    }
+    Ok(target)
+}
+
+/// Time left until a one-shot timer runs; recurring and idle timers have
+/// none.
+fn remaining_seconds(info: &TimerInfo, now: DateTime<Utc>) -> Option<i64> {
+    if info.recurrence.is_some() || info.idle_trigger.is_some() {
+        return None;
+    }
+    Some((info.target_time - now).num_seconds().max(0))
+}
+
+/// History record for a recurring timer that has no further runs.
+fn ended_entry(info: &TimerInfo) -> HistoryEntry {
+    let recurrence = info.recurrence.as_ref();
+    let detail = match (
+        recurrence.and_then(|recurrence| recurrence.max_occurrences),
+        recurrence.and_then(|recurrence| recurrence.ends_at),
+    ) {
+        (Some(max), _) if info.occurrence_count >= max => {
+            format!("Completed {} of {max} occurrences", info.occurrence_count)
+        }
+        (_, Some(ends_at)) => format!("Reached end date {}", ends_at.to_rfc3339()),
+        _ => "Schedule has no further runs".to_string(),
+    };
+
+    history_entry(info, HistoryEvent::Ended, Some(detail))
+}
+
+/// History record of `event` for the timer's current occurrence.
+fn history_entry(info: &TimerInfo, event: HistoryEvent, detail: Option<String>) -> HistoryEntry {
+    HistoryEntry {
+        timer_id: info.id.clone(),
+        action: info.action.clone(),
+        event,
+        scheduled_time: Some(info.snoozed_from.unwrap_or(info.target_time)),
+        decision: None,
+        error: None,
+        outcome: None,
+        detail,
+        recorded_at: Utc::now(),
+    }
+}
+
+/// The occurrence's next stage after `now`: the largest pre-warning below
+/// `below` seconds that is still ahead, or else the run itself.
+///
+/// At the start of an occurrence (`below` unset) that is already inside a
+/// warning's lead time, the closest such warning is shown straight away.
+fn next_stage(info: &TimerInfo, below: Option<u32>, now: DateTime<Utc>) -> (Stage, DateTime<Utc>) {
+    let target = info.target_time;
+    let offsets = warning_offsets(info);
+
+    if below.is_none() && target > now {
+        let left = (target - now).num_seconds();
+        if let Some(seconds) = offsets
+            .iter()
+            .copied()
+            .find(|seconds| *seconds as i64 >= left)
+        {
+            return (Stage::Warning { seconds }, now);
+        }
+    }
+
+    offsets
+        .into_iter()
+        .rev()
+        .filter(|seconds| below.map_or(true, |below| *seconds < below))
+        .map(|seconds| (seconds, target - ChronoDuration::seconds(seconds as i64)))
+        .find(|(_, due)| *due > now)
+        .map(|(seconds, due)| (Stage::Warning { seconds }, due))
+        .unwrap_or((Stage::Fire, target))
+}
+
+/// Pre-warning offsets in seconds, smallest first.
+fn warning_offsets(info: &TimerInfo) -> Vec<u32> {
+    normalize_pre_warning_seconds(
+        info.pre_warning_seconds.as_ref(),
+        info.pre_warning_minutes.as_ref(),
+    )
+    .ok()
+    .flatten()
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Mutex};
+
+    use super::*;
+    use crate::{
+        backend::RecordedCall,
+        conditions::{StaticProbe, SystemState},
+        model::{IdleTrigger, PowerSource, RecurrenceConfig, RecurrencePreset, TimerAction},
+        IdleSource, RecordingBackend,
+    };
+
+    fn timer(target_time: DateTime<Utc>, pre_warning_seconds: Vec<u32>) -> TimerInfo {
+        TimerInfo {
+            pre_warning_seconds: Some(pre_warning_seconds),
+            ..TimerInfo::fixture(TimerAction::Lock, target_time)
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingPrompter {
+        opened: Mutex<Vec<PreActionWarningPayload>>,
+    }
+
+    impl PreActionPrompter for RecordingPrompter {
+        fn open(&self, payload: &PreActionWarningPayload) -> Result<(), String> {
+            self.opened.lock().unwrap().push(payload.clone());
+            Ok(())
+        }
+
+        fn close(&self, _timer_id: &str) {}
+
+        fn action_finished(&self, _payload: &ActionOutcomePayload) {}
+    }
+
+    /// A scheduler loop driven step by step from the test thread, which also
+    /// receives what its action threads send back.
+    struct TestLoop {
+        inner: SchedulerLoop,
+        rx: mpsc::Receiver<Message>,
+        backend: Arc<RecordingBackend>,
+        prompter: Arc<RecordingPrompter>,
+        dir: PathBuf,
+    }
+
+    impl TestLoop {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("lockpilot-loop-{}", Uuid::new_v4()));
+            let (tx, rx) = mpsc::channel();
+            let backend = Arc::new(RecordingBackend::new());
+            let prompter = Arc::new(RecordingPrompter::default());
+            let inner = SchedulerLoop {
+                store: TimerStore::new(dir.join("timers.json")),
+                history: HistoryStore::new(dir.join("history.jsonl")),
+                locks: LockStore::new(dir.join("locks.json")).unwrap(),
+                tx,
+                backend: backend.clone(),
+                probe: Arc::new(StaticProbe::new(SystemState::default())),
+                idle_readings: IdleReadings::default(),
+                prompter: prompter.clone(),
+                timers: HashMap::new(),
+                queue: BinaryHeap::new(),
+                prompts: HashMap::new(),
+                next_generation: 0,
+            };
+            Self {
+                inner,
+                rx,
+                backend,
+                prompter,
+                dir,
+            }
+        }
+
+        fn insert(&mut self, info: TimerInfo) {
+            let timers = vec![info];
+            self.send(|reply| Message::Insert { timers, reply })
+                .unwrap();
+        }
+
+        /// Handle a message as the loop would and return its reply.
+        fn send<T>(
+            &mut self,
+            build: impl FnOnce(mpsc::Sender<Result<T, String>>) -> Message,
+        ) -> Result<T, String> {
+            let (reply_tx, reply_rx) = mpsc::channel();
+            self.inner.handle_message(build(reply_tx));
+            reply_rx.recv().unwrap()
+        }
+
+        /// Handle the next message from an action thread.
+        fn finish_action(&mut self) {
+            let message = self
+                .rx
+                .recv_timeout(Duration::from_secs(5))
+                .expect("no action finished");
+            self.inner.handle_message(message);
+        }
+
+        /// Wake-ups the loop would still act on, earliest first.
+        fn live_wakeups(&self) -> Vec<(String, DateTime<Utc>)> {
+            let mut live: Vec<_> = self
+                .inner
+                .queue
+                .iter()
+                .filter(|Reverse(wakeup)| self.inner.is_live(wakeup))
+                .map(|Reverse(wakeup)| (wakeup.due, wakeup.generation, wakeup.timer_id.clone()))
+                .collect();
+            live.sort();
+            live.into_iter().map(|(due, _, id)| (id, due)).collect()
+        }
+
+        fn generation(&self, id: &str) -> u64 {
+            self.inner.timers[id].generation
+        }
+
+        fn history_events(&self) -> Vec<HistoryEvent> {
+            self.inner
+                .history
+                .list(&HistoryFilter::default())
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.event)
+                .collect()
+        }
+    }
+
+    impl Drop for TestLoop {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[test]
+    fn created_timers_run_once_due() {
+        let mut test = TestLoop::new();
+        let target = Utc::now() - ChronoDuration::seconds(1);
+        test.insert(TimerInfo::fixture(TimerAction::Lock, target));
+        assert_eq!(test.live_wakeups(), vec![("timer".to_string(), target)]);
+
+        test.inner.run_due();
+        test.finish_action();
+        assert_eq!(test.backend.calls(), vec![RecordedCall::Lock]);
+        assert_eq!(test.history_events(), vec![HistoryEvent::Executed]);
+        // Kept, done, with the outcome that came back from the run.
+        let done = &test.inner.timers["timer"].info;
+        assert_eq!(done.status, TimerStatus::Done);
+        assert!(done
+            .last_outcome
+            .as_ref()
+            .is_some_and(|outcome| outcome.success));
+        assert_eq!(test.inner.next_wait(), None);
+    }
+
+    #[test]
+    fn cancelled_timers_never_wake() {
+        let mut test = TestLoop::new();
+        test.insert(TimerInfo::fixture(
+            TimerAction::Lock,
+            Utc::now() + ChronoDuration::hours(1),
+        ));
+        let id = "timer".to_string();
+        let pin = PinCheck::NotGiven;
+        assert_eq!(
+            test.send(|reply| Message::Cancel { id, pin, reply }),
+            Ok(true)
+        );
+
+        assert!(test.live_wakeups().is_empty());
+        assert_eq!(test.inner.next_wait(), None);
+        assert!(test.inner.queue.is_empty());
+        let id = "timer".to_string();
+        let pin = PinCheck::NotGiven;
+        assert_eq!(
+            test.send(|reply| Message::Cancel { id, pin, reply }),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn snoozing_rearms_the_occurrence() {
+        let mut test = TestLoop::new();
+        let target = Utc::now() + ChronoDuration::seconds(30);
+        test.insert(timer(target, vec![60]));
+        test.inner.run_due();
+        let prompt = test.prompter.opened.lock().unwrap()[0].clone();
+        let shown_at = test.generation("timer");
+        assert_eq!(test.live_wakeups(), vec![("timer".to_string(), target)]);
+
+        let snoozed_at = Utc::now();
+        let snoozed = test.send(|reply| Message::Decision {
+            prompt_id: prompt.prompt_id,
+            decision: PreActionDecision::Snooze,
+            snooze_minutes: None,
+            pin: PinCheck::NotGiven,
+            reply,
+        });
+        assert_eq!(snoozed, Ok(true));
+
+        let snoozed = &test.inner.timers["timer"].info;
+        // Snoozes count from when they're chosen, not from the run time.
+        let run_at = snoozed.target_time;
+        assert!(run_at >= snoozed_at + ChronoDuration::minutes(10));
+        assert!(run_at <= Utc::now() + ChronoDuration::minutes(10));
+        assert_eq!(snoozed.snoozed_from, Some(target));
+        assert_eq!(snoozed.snooze_count, 1);
+        assert!(test.generation("timer") > shown_at);
+        // The warning comes round again before the snoozed run.
+        assert_eq!(
+            test.live_wakeups(),
+            vec![("timer".to_string(), run_at - ChronoDuration::seconds(60))]
+        );
+        assert!(test.backend.calls().is_empty());
+    }
+
+    #[test]
+    fn stale_wakeups_are_ignored() {
+        let mut test = TestLoop::new();
+        test.insert(TimerInfo::fixture(
+            TimerAction::Lock,
+            Utc::now() - ChronoDuration::seconds(1),
+        ));
+        let stale = test.generation("timer");
+        let later = Utc::now() + ChronoDuration::hours(1);
+        test.inner.set_stage("timer", Stage::Fire, later);
+
+        test.inner.run_due();
+        assert!(test.backend.calls().is_empty());
+        assert!(test.generation("timer") > stale);
+        assert_eq!(test.live_wakeups(), vec![("timer".to_string(), later)]);
+        // The stale entry was dropped on the way past.
+        assert_eq!(test.inner.queue.len(), 1);
+    }
+
+    #[test]
+    fn timers_wake_in_due_order() {
+        let mut test = TestLoop::new();
+        let now = Utc::now();
+        for (id, minutes) in [("third", 3), ("first", 1), ("second", 2), ("also-first", 1)] {
+            test.insert(TimerInfo {
+                id: id.to_string(),
+                ..TimerInfo::fixture(TimerAction::Lock, now + ChronoDuration::minutes(minutes))
+            });
+        }
+
+        let order: Vec<String> = test.live_wakeups().into_iter().map(|(id, _)| id).collect();
+        // Timers due together wake in the order they were queued.
+        assert_eq!(order, ["first", "also-first", "second", "third"]);
+        let wait = test.inner.next_wait().unwrap();
+        assert!(wait > Duration::from_secs(55) && wait <= Duration::from_secs(60));
+
+        let mut fired = Vec::new();
+        while let Some(Reverse(wakeup)) = test.inner.queue.pop() {
+            fired.push(wakeup.timer_id);
+        }
+        assert_eq!(fired, order);
+    }
+
+    #[test]
+    fn edits_rearm_the_timer_and_drop_its_old_wakeup() {
+        let mut test = TestLoop::new();
+        test.insert(TimerInfo::fixture(
+            TimerAction::Lock,
+            Utc::now() - ChronoDuration::seconds(1),
+        ));
+        let before = test.generation("timer");
+
+        let later = Utc::now() + ChronoDuration::hours(1);
+        let info = Box::new(TimerInfo::fixture(TimerAction::Popup, later));
+        let pin = PinCheck::NotGiven;
+        let edited = test.send(|reply| Message::Update {
+            info,
+            pin,
+            lock: None,
+            reply,
+        });
+        assert_eq!(edited.map(|info| info.action), Ok(TimerAction::Popup));
+        assert!(test.generation("timer") > before);
+        assert_eq!(test.live_wakeups(), vec![("timer".to_string(), later)]);
+
+        // The wake-up queued before the edit is overdue, but no longer live.
+        test.inner.run_due();
+        assert!(test.backend.calls().is_empty());
+        assert_eq!(test.inner.timers["timer"].info.action, TimerAction::Popup);
+    }
+
+    #[test]
+    fn locked_timers_reject_edits_without_their_pin() {
+        let mut test = TestLoop::new();
+        let target = Utc::now() + ChronoDuration::hours(1);
+        test.insert(TimerInfo {
+            locked: true,
+            ..TimerInfo::fixture(TimerAction::Lock, target)
+        });
+        let before = test.generation("timer");
+        let edit = |test: &mut TestLoop, pin: PinCheck| {
+            let info = Box::new(TimerInfo::fixture(TimerAction::Shutdown, target));
+            test.send(|reply| Message::Update {
+                info,
+                pin,
+                lock: None,
+                reply,
+            })
+        };
+
+        assert_eq!(
+            edit(&mut test, PinCheck::NotGiven).err(),
+            Some("This timer is locked. Enter its PIN.".to_string())
+        );
+        assert_eq!(
+            edit(&mut test, PinCheck::Wrong("Incorrect PIN.".to_string())).err(),
+            Some("Incorrect PIN.".to_string())
+        );
+        assert_eq!(test.history_events(), vec![HistoryEvent::PinRejected]);
+        assert_eq!(test.inner.timers["timer"].info.action, TimerAction::Lock);
+        assert_eq!(test.generation("timer"), before);
+
+        let edited = edit(&mut test, PinCheck::Matched).unwrap();
+        assert_eq!(edited.action, TimerAction::Shutdown);
+        assert!(edited.locked);
+    }
+
+    #[test]
+    fn timers_with_a_stored_pin_stay_locked() {
+        let mut test = TestLoop::new();
+        test.inner.locks.set("timer", "2468").unwrap();
+        // As if `locked` had been cleared in timers.json.
+        test.insert(TimerInfo::fixture(
+            TimerAction::Lock,
+            Utc::now() + ChronoDuration::hours(1),
+        ));
+        assert!(test.inner.timers["timer"].info.locked);
+
+        let id = "timer".to_string();
+        let pin = PinCheck::NotGiven;
+        assert_eq!(
+            test.send(|reply| Message::Cancel { id, pin, reply }),
+            Err("This timer is locked. Enter its PIN.".to_string())
+        );
+        assert!(test.inner.timers.contains_key("timer"));
+    }
+
+    fn pause(test: &mut TestLoop, pin: PinCheck) -> Result<bool, String> {
+        let id = "timer".to_string();
+        test.send(|reply| Message::Pause { id, pin, reply })
+    }
+
+    fn resume(test: &mut TestLoop, pin: PinCheck) -> Result<bool, String> {
+        let id = "timer".to_string();
+        test.send(|reply| Message::Resume { id, pin, reply })
+    }
+
+    #[test]
+    fn paused_timers_do_not_fire() {
+        let mut test = TestLoop::new();
+        test.insert(TimerInfo::fixture(
+            TimerAction::Lock,
+            Utc::now() - ChronoDuration::seconds(1),
+        ));
+        assert_eq!(pause(&mut test, PinCheck::NotGiven), Ok(true));
+
+        test.inner.run_due();
+        assert!(test.backend.calls().is_empty());
+        assert!(test.live_wakeups().is_empty());
+        let paused = &test.inner.timers["timer"].info;
+        assert_eq!(paused.status, TimerStatus::Paused);
+        assert_eq!(paused.remaining_seconds, Some(0));
+    }
+
+    #[test]
+    fn timers_paused_past_their_run_resume_sensibly() {
+        let mut test = TestLoop::new();
+        let now = Utc::now();
+        test.insert(TimerInfo {
+            recurrence: Some(RecurrenceConfig {
+                preset: RecurrencePreset::EveryNMinutes,
+                interval_hours: None,
+                interval_minutes: Some(60),
+                days_of_week: None,
+                cron_expression: None,
+                rrule: None,
+                timezone: Some("UTC".to_string()),
+                local_time: None,
+                start_date: None,
+                ends_at: None,
+                max_occurrences: None,
+                skip_dates: None,
+                holiday_calendar: None,
+            }),
+            ..TimerInfo::fixture(TimerAction::Lock, now + ChronoDuration::minutes(10))
+        });
+        assert_eq!(pause(&mut test, PinCheck::NotGiven), Ok(true));
+        // Stay paused through two runs.
+        test.inner.timers.get_mut("timer").unwrap().info.target_time =
+            now - ChronoDuration::minutes(110);
+
+        assert_eq!(resume(&mut test, PinCheck::NotGiven), Ok(true));
+        test.inner.run_due();
+        assert!(
+            test.backend.calls().is_empty(),
+            "missed runs are not made up"
+        );
+        let resumed = &test.inner.timers["timer"].info;
+        assert_eq!(resumed.status, TimerStatus::Active);
+        assert_eq!(resumed.target_time, now + ChronoDuration::minutes(10));
+        assert_eq!(resumed.occurrence_count, 0);
+        assert_eq!(
+            test.live_wakeups(),
+            vec![("timer".to_string(), resumed.target_time)]
+        );
+
+        // A one-shot keeps the time it had left, however long it was paused.
+        let mut test = TestLoop::new();
+        test.insert(TimerInfo::fixture(
+            TimerAction::Lock,
+            Utc::now() + ChronoDuration::minutes(5),
+        ));
+        assert_eq!(pause(&mut test, PinCheck::NotGiven), Ok(true));
+        test.inner.timers.get_mut("timer").unwrap().info.target_time =
+            now - ChronoDuration::hours(1);
+        assert_eq!(resume(&mut test, PinCheck::NotGiven), Ok(true));
+        let left = test.inner.timers["timer"].info.target_time - Utc::now();
+        assert!(left > ChronoDuration::minutes(4) && left <= ChronoDuration::minutes(5));
+    }
+
+    #[test]
+    fn every_pre_warning_gets_its_own_stage() {
+        let now = Utc::now();
+        let info = timer(now + ChronoDuration::minutes(30), vec![30, 120, 600]);
+        let seconds_before = |seconds: i64| info.target_time - ChronoDuration::seconds(seconds);
+
+        assert_eq!(
+            next_stage(&info, None, now),
+            (Stage::Warning { seconds: 600 }, seconds_before(600))
+        );
+        assert_eq!(
+            next_stage(&info, Some(600), seconds_before(600)),
+            (Stage::Warning { seconds: 120 }, seconds_before(120))
+        );
+        assert_eq!(
+            next_stage(&info, Some(120), seconds_before(120)),
+            (Stage::Warning { seconds: 30 }, seconds_before(30))
+        );
+        assert_eq!(
+            next_stage(&info, Some(30), seconds_before(30)),
+            (Stage::Fire, info.target_time)
+        );
+    }
+
+    #[test]
+    fn late_start_shows_the_closest_passed_warning_now() {
+        let now = Utc::now();
+        // Four minutes out: inside the 10- and 5-minute lead times.
+        let info = timer(now + ChronoDuration::minutes(4), vec![60, 300, 600]);
+        assert_eq!(
+            next_stage(&info, None, now),
+            (Stage::Warning { seconds: 300 }, now)
+        );
+        assert_eq!(
+            next_stage(&info, Some(300), now),
+            (
+                Stage::Warning { seconds: 60 },
+                info.target_time - ChronoDuration::minutes(1)
+            )
+        );
+    }
+
+    #[test]
+    fn new_timers_keep_the_pin_they_were_created_with() {
+        let mut test = TestLoop::new();
+        let target = Utc::now() + ChronoDuration::hours(1);
+        let info = Box::new(TimerInfo::fixture(TimerAction::Lock, target));
+        let lock = Some(hash_pin("2468").unwrap());
+        test.send(|reply| Message::Create { info, lock, reply })
+            .unwrap();
+        // Any later save keeps the PIN of a timer the loop knows about.
+        test.insert(TimerInfo {
+            id: "other".to_string(),
+            ..TimerInfo::fixture(TimerAction::Popup, target)
+        });
+
+        assert!(test.inner.locks.is_locked("timer"));
+        assert!(test.inner.timers["timer"].info.locked);
+        let id = "timer".to_string();
+        let pin = PinCheck::NotGiven;
+        assert!(test
+            .send(|reply| Message::Cancel { id, pin, reply })
+            .is_err());
+    }
+
+    #[test]
+    fn locked_timers_need_their_pin_to_resume() {
+        let mut test = TestLoop::new();
+        test.insert(TimerInfo {
+            locked: true,
+            ..TimerInfo::fixture(TimerAction::Lock, Utc::now() + ChronoDuration::hours(1))
+        });
+        assert!(pause(&mut test, PinCheck::NotGiven).is_err());
+        assert_eq!(pause(&mut test, PinCheck::Matched), Ok(true));
+
+        assert_eq!(
+            resume(&mut test, PinCheck::NotGiven),
+            Err("This timer is locked. Enter its PIN.".to_string())
+        );
+        assert_eq!(test.inner.timers["timer"].info.status, TimerStatus::Paused);
+        assert_eq!(resume(&mut test, PinCheck::Matched), Ok(true));
+        assert_eq!(test.inner.timers["timer"].info.status, TimerStatus::Active);
+    }
+
+    /// Probe for a desktop with no way to read idle time.
+    struct NoIdleProbe;
+
+    impl SystemProbe for NoIdleProbe {
+        fn power_source(&self) -> Result<PowerSource, String> {
+            Ok(PowerSource::Ac)
+        }
+
+        fn is_running(&self, _name: &str) -> Result<bool, String> {
+            Ok(false)
+        }
+
+        fn cpu_percent(&self) -> Result<f32, String> {
+            Ok(0.0)
+        }
+    }
+
+    impl IdleSource for NoIdleProbe {
+        fn idle_seconds(&self) -> Result<u64, String> {
+            Err("no idle monitor".to_string())
+        }
+    }
+
+    #[test]
+    fn unreadable_idle_time_is_reported_once() {
+        let mut test = TestLoop::new();
+        test.inner.probe = Arc::new(NoIdleProbe);
+        // Restored timers skip the check made when a timer is created.
+        test.insert(TimerInfo {
+            idle_trigger: Some(IdleTrigger { minutes: 5 }),
+            ..TimerInfo::fixture(TimerAction::Lock, Utc::now())
+        });
+
+        for _ in 0..3 {
+            test.inner
+                .set_stage("timer", Stage::Idle { armed: true }, Utc::now());
+            test.inner.run_due();
+            test.finish_action();
+        }
+        assert_eq!(test.history_events(), vec![HistoryEvent::IdleUnavailable]);
+        let entries = test.inner.history.list(&HistoryFilter::default()).unwrap();
+        assert_eq!(
+            entries[0].detail.as_deref(),
+            Some("Can't read idle time: no idle monitor")
+        );
+        assert_eq!(
+            test.inner.timers["timer"].info.idle_error.as_deref(),
+            Some("no idle monitor")
+        );
+        let (_, due) = test.live_wakeups()[0];
+        assert!(due > Utc::now() + ChronoDuration::seconds(IDLE_POLL_SECONDS as i64 - 2));
+
+        // Once it can be read again, the error clears.
+        test.inner.probe = Arc::new(StaticProbe::new(SystemState::default()));
+        test.inner
+            .set_stage("timer", Stage::Idle { armed: true }, Utc::now());
+        test.inner.run_due();
+        test.finish_action();
+        assert_eq!(test.inner.timers["timer"].info.idle_error, None);
+        assert!(test.backend.calls().is_empty());
+    }
+
+    #[test]
+    fn idle_readings_are_ignored_once_the_timer_moves_on() {
+        let mut test = TestLoop::new();
+        test.inner.probe = Arc::new(StaticProbe::new(SystemState {
+            idle_seconds: 600,
+            ..SystemState::default()
+        }));
+        test.insert(TimerInfo {
+            idle_trigger: Some(IdleTrigger { minutes: 5 }),
+            ..TimerInfo::fixture(TimerAction::Lock, Utc::now())
+        });
+
+        // The reading comes back after the timer was paused.
+        test.inner.run_due();
+        assert!(test.live_wakeups().is_empty());
+        assert_eq!(pause(&mut test, PinCheck::NotGiven), Ok(true));
+        test.finish_action();
+        assert!(test.backend.calls().is_empty());
+
+        assert_eq!(resume(&mut test, PinCheck::NotGiven), Ok(true));
+        test.inner.run_due();
+        test.finish_action();
+        test.finish_action();
+        assert_eq!(test.backend.calls(), vec![RecordedCall::Lock]);
+    }
+}
@@ -0,0 +1,284 @@
+//! `Sequence` timers: an ordered list of actions, each with its own delay
+//! and settings, run one after another. The scheduler queues each step as
+//! its own wake-up, so a run can be stopped between steps and survives a
+//! restart.
+
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    backend::{self, ActionBackend},
+    close::normalize_close_apps,
+    command::normalize_command,
+    model::{ActionAttempt, ActionOutcome, ActionStep, StepOutcome, TimerAction},
+    open::normalize_open_target,
+};
+
+pub const MAX_SEQUENCE_STEPS: usize = 20;
+/// Longest wait before a step: one day.
+pub const MAX_STEP_DELAY_SECONDS: u32 = 24 * 60 * 60;
+/// How long a popup step waits to see whether its popup failed to open
+/// before the sequence moves on without waiting for it to be dismissed.
+const POPUP_SETTLE: Duration = Duration::from_secs(1);
+
+/// Check every step and normalize its settings the way a single-action
+/// timer's are, dropping settings its action doesn't use.
+pub fn normalize_steps(steps: Option<Vec<ActionStep>>) -> Result<Vec<ActionStep>, String> {
+    let steps = steps.unwrap_or_default();
+    if steps.is_empty() {
+        return Err("A sequence needs at least one step.".to_string());
+    }
+    if steps.len() > MAX_SEQUENCE_STEPS {
+        return Err(format!(
+            "A sequence can have at most {MAX_SEQUENCE_STEPS} steps."
+        ));
+    }
+    steps
+        .into_iter()
+        .enumerate()
+        .map(|(index, step)| {
+            normalize_step(step).map_err(|err| format!("Step {}: {err}", index + 1))
+        })
+        .collect()
+}
+
+fn normalize_step(step: ActionStep) -> Result<ActionStep, String> {
+    if step.action == TimerAction::Sequence {
+        return Err("Steps can't be sequences themselves.".to_string());
+    }
+    if step.delay_seconds > MAX_STEP_DELAY_SECONDS {
+        return Err("Delays must be at most 24 hours.".to_string());
+    }
+    Ok(ActionStep {
+        message: match step.action {
+            TimerAction::Popup => step.message.map(|msg| msg.trim().to_string()),
+            _ => None,
+        },
+        command: match step.action {
+            TimerAction::RunCommand => Some(normalize_command(step.command)?),
+            _ => None,
+        },
+        open_target: match step.action {
+            TimerAction::Open => Some(normalize_open_target(step.open_target)?),
+            _ => None,
+        },
+        close_apps: match step.action {
+            TimerAction::CloseApps => Some(normalize_close_apps(step.close_apps)?),
+            _ => None,
+        },
+        ..step
+    })
+}
+
+/// Run one step of a sequence. A popup is left open on its own thread, so
+/// an unattended machine still gets on to the steps after it; it only
+/// counts as failed if it fails straight away.
+pub fn run_step(backend: Arc<dyn ActionBackend>, step: &ActionStep) -> StepOutcome {
+    let started_at = Utc::now();
+    let outcome = match step.action {
+        TimerAction::Popup => show_popup(backend, step.clone()),
+        _ => backend::run_step(backend.as_ref(), step),
+    };
+    StepOutcome {
+        action: step.action.clone(),
+        started_at: Some(started_at),
+        success: outcome.success,
+        attempts: outcome.attempts,
+    }
+}
+
+/// Outcome of a step that was running when the app stopped. What it did is
+/// unknown, but a Shutdown or Reboot step most likely did just that, so the
+/// step counts as done.
+pub fn interrupted_step(step: &ActionStep, started_at: Option<DateTime<Utc>>) -> StepOutcome {
+    StepOutcome {
+        action: step.action.clone(),
+        started_at,
+        success: true,
+        attempts: vec![ActionAttempt::from_result(
+            "interrupted by a restart",
+            Ok(()),
+        )],
+    }
+}
+
+fn show_popup(backend: Arc<dyn ActionBackend>, step: ActionStep) -> ActionOutcome {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(backend::run_step(backend.as_ref(), &step));
+    });
+    match rx.recv_timeout(POPUP_SETTLE) {
+        Ok(outcome) => outcome,
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            ActionOutcome::from_attempts(vec![ActionAttempt::from_result(
+                "popup (left open)",
+                Ok(()),
+            )])
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+            ActionOutcome::from_attempts(vec![ActionAttempt::from_result(
+                "popup",
+                Err("Popup thread stopped unexpectedly".to_string()),
+            )])
+        }
+    }
+}
+
+/// Whether a sequence goes on after `step` produced `outcome`.
+pub fn continues_after(step: &ActionStep, outcome: &StepOutcome) -> bool {
+    outcome.success || step.continue_on_error
+}
+
+/// Outcome of a run that got through the steps in `done`; the rest are
+/// reported as not run.
+pub fn sequence_outcome(steps: &[ActionStep], mut done: Vec<StepOutcome>) -> ActionOutcome {
+    done.extend(steps.iter().skip(done.len()).map(|step| StepOutcome {
+        action: step.action.clone(),
+        started_at: None,
+        success: false,
+        attempts: Vec::new(),
+    }));
+    ActionOutcome::from_steps(done)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{
+        backend::RecordedCall,
+        model::{CloseAppsSpec, CommandSpec},
+        RecordingBackend,
+    };
+
+    fn step(action: TimerAction) -> ActionStep {
+        ActionStep {
+            action,
+            delay_seconds: 0,
+            continue_on_error: false,
+            message: None,
+            command: None,
+            open_target: None,
+            close_apps: None,
+        }
+    }
+
+    #[test]
+    fn validates_and_trims_each_step() {
+        let mut popup = step(TimerAction::Popup);
+        popup.message = Some(" Wrapping up ".to_string());
+        let mut lock = step(TimerAction::Lock);
+        lock.message = Some("unused".to_string());
+        let normalized = normalize_steps(Some(vec![popup, lock])).unwrap();
+        assert_eq!(normalized[0].message.as_deref(), Some("Wrapping up"));
+        assert_eq!(normalized[1].message, None);
+
+        assert!(normalize_steps(None).is_err());
+        assert_eq!(
+            normalize_steps(Some(vec![
+                step(TimerAction::Lock),
+                step(TimerAction::CloseApps)
+            ])),
+            Err("Step 2: Close apps needs at least one app.".to_string())
+        );
+        assert!(normalize_steps(Some(vec![step(TimerAction::Sequence)])).is_err());
+    }
+
+    /// Backend whose popups stay open until the test closes them.
+    struct OpenPopups {
+        closed: Mutex<mpsc::Receiver<()>>,
+    }
+
+    impl ActionBackend for OpenPopups {
+        fn lock(&self) -> ActionOutcome {
+            unreachable!()
+        }
+        fn shutdown(&self) -> ActionOutcome {
+            unreachable!()
+        }
+        fn reboot(&self) -> ActionOutcome {
+            unreachable!()
+        }
+        fn sleep(&self) -> ActionOutcome {
+            unreachable!()
+        }
+        fn hibernate(&self) -> ActionOutcome {
+            unreachable!()
+        }
+        fn logout(&self) -> ActionOutcome {
+            unreachable!()
+        }
+        fn popup(&self, _message: &str) -> ActionOutcome {
+            let _ = self.closed.lock().unwrap().recv();
+            ActionOutcome::from_attempts(vec![ActionAttempt::from_result("popup", Ok(()))])
+        }
+        fn open(&self, _target: &str, _kind: crate::open::OpenTargetKind) -> ActionOutcome {
+            unreachable!()
+        }
+        fn close_apps(&self, _spec: &CloseAppsSpec) -> ActionOutcome {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn popups_do_not_hold_up_the_next_step() {
+        let (close, closed) = mpsc::channel();
+        let backend = Arc::new(OpenPopups {
+            closed: Mutex::new(closed),
+        });
+        let outcome = run_step(backend, &step(TimerAction::Popup));
+        assert!(outcome.success);
+        assert_eq!(outcome.attempts[0].method, "popup (left open)");
+        let _ = close.send(());
+
+        let backend = Arc::new(RecordingBackend::new());
+        backend.set_failure(Some("no display"));
+        let outcome = run_step(backend.clone(), &step(TimerAction::Popup));
+        assert!(!outcome.success);
+        assert_eq!(
+            backend.calls(),
+            vec![RecordedCall::Popup(
+                backend::DEFAULT_POPUP_MESSAGE.to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn steps_after_a_stop_are_reported_as_not_run() {
+        let backend = Arc::new(RecordingBackend::new());
+        let mut command = step(TimerAction::RunCommand);
+        command.continue_on_error = true;
+        command.command = Some(CommandSpec {
+            program: "git".to_string(),
+            args: Vec::new(),
+            env: Default::default(),
+            working_dir: None,
+            timeout_seconds: None,
+            shell: false,
+        });
+        let steps = vec![command, step(TimerAction::Lock), step(TimerAction::Logout)];
+        backend.set_failure(Some("denied"));
+
+        let first = run_step(backend.clone(), &steps[0]);
+        assert!(!first.success && continues_after(&steps[0], &first));
+        let second = run_step(backend.clone(), &steps[1]);
+        assert!(!continues_after(&steps[1], &second));
+
+        let outcome = sequence_outcome(&steps, vec![first, second]);
+        assert!(!outcome.success);
+        assert!(outcome.steps[1].started_at.is_some());
+        assert!(outcome.steps[2].started_at.is_none());
+        let error = outcome.error().unwrap();
+        assert!(error.starts_with("step 1 (run_command): "), "{error}");
+        assert!(
+            error.ends_with("; step 2 (lock): recording:Lock: denied"),
+            "{error}"
+        );
+    }
+}
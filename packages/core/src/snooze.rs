@@ -0,0 +1,162 @@
+//! Snooze options offered by the pre-action prompt, and the per-timer limits
+//! on how often and how far a run can be pushed back.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+
+use crate::model::{SnoozePolicy, TimerInfo};
+
+/// Snooze offered when a timer has no policy of its own.
+pub const DEFAULT_SNOOZE_MINUTES: u32 = 10;
+pub const MAX_SNOOZE_MINUTES: u32 = 24 * 60;
+
+/// Sort and deduplicate a requested policy's durations, rejecting empty or
+/// out-of-range ones.
+pub fn normalize_snooze_policy(
+    policy: Option<SnoozePolicy>,
+) -> Result<Option<SnoozePolicy>, String> {
+    let Some(mut policy) = policy else {
+        return Ok(None);
+    };
+
+    policy.durations_minutes.sort_unstable();
+    policy.durations_minutes.dedup();
+    if policy.durations_minutes.is_empty() {
+        return Err("Snooze needs at least one duration.".to_string());
+    }
+    if policy
+        .durations_minutes
+        .iter()
+        .any(|minutes| *minutes == 0 || *minutes > MAX_SNOOZE_MINUTES)
+    {
+        return Err(format!(
+            "Snooze durations must be between 1 and {MAX_SNOOZE_MINUTES} minutes."
+        ));
+    }
+    Ok(Some(policy))
+}
+
+/// Snoozes left for the current occurrence; `None` when unlimited.
+pub fn snoozes_remaining(info: &TimerInfo) -> Option<u32> {
+    info.snooze
+        .as_ref()
+        .and_then(|policy| policy.max_count)
+        .map(|max| max.saturating_sub(info.snooze_count))
+}
+
+/// Snooze durations, in minutes, that are still allowed at `now`.
+pub fn available_snoozes(info: &TimerInfo, now: DateTime<Utc>) -> Vec<u32> {
+    if snoozes_remaining(info) == Some(0) {
+        return Vec::new();
+    }
+
+    let durations = info.snooze.as_ref().map_or_else(
+        || vec![DEFAULT_SNOOZE_MINUTES],
+        |policy| policy.durations_minutes.clone(),
+    );
+    let Some(max_total) = info
+        .snooze
+        .as_ref()
+        .and_then(|policy| policy.max_total_minutes)
+    else {
+        return durations;
+    };
+
+    let latest = scheduled_time(info) + ChronoDuration::minutes(max_total as i64);
+    durations
+        .into_iter()
+        .filter(|minutes| now + ChronoDuration::minutes(*minutes as i64) <= latest)
+        .collect()
+}
+
+/// Push the current occurrence back by `minutes`, if the policy allows it.
+pub fn apply_snooze(info: &mut TimerInfo, minutes: u32, now: DateTime<Utc>) -> Result<(), String> {
+    if !available_snoozes(info, now).contains(&minutes) {
+        return Err(match snoozes_remaining(info) {
+            Some(0) => "No snoozes left for this run.".to_string(),
+            _ => format!("Snoozing {minutes} minutes is not allowed for this run."),
+        });
+    }
+
+    info.snoozed_from.get_or_insert(info.target_time);
+    info.snooze_count += 1;
+    info.target_time = now + ChronoDuration::minutes(minutes as i64);
+    Ok(())
+}
+
+/// Clear the occurrence's snooze state, returning when it was scheduled
+/// before any snoozes.
+pub fn end_snooze(info: &mut TimerInfo) -> DateTime<Utc> {
+    info.snooze_count = 0;
+    info.snoozed_from.take().unwrap_or(info.target_time)
+}
+
+fn scheduled_time(info: &TimerInfo) -> DateTime<Utc> {
+    info.snoozed_from.unwrap_or(info.target_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::TimerAction;
+
+    fn timer(snooze: Option<SnoozePolicy>, target_time: DateTime<Utc>) -> TimerInfo {
+        TimerInfo {
+            snooze,
+            ..TimerInfo::fixture(TimerAction::Shutdown, target_time)
+        }
+    }
+
+    fn policy(
+        durations_minutes: Vec<u32>,
+        max_count: Option<u32>,
+        max_total_minutes: Option<u32>,
+    ) -> SnoozePolicy {
+        SnoozePolicy {
+            durations_minutes,
+            max_count,
+            max_total_minutes,
+        }
+    }
+
+    #[test]
+    fn normalizes_and_validates_durations() {
+        let normalized =
+            normalize_snooze_policy(Some(policy(vec![30, 5, 30], None, None))).unwrap();
+        assert_eq!(normalized.unwrap().durations_minutes, vec![5, 30]);
+
+        assert!(normalize_snooze_policy(Some(policy(Vec::new(), None, None))).is_err());
+        assert!(normalize_snooze_policy(Some(policy(vec![0], None, None))).is_err());
+        assert!(normalize_snooze_policy(None).unwrap().is_none());
+    }
+
+    #[test]
+    fn timers_without_a_policy_keep_the_unlimited_default() {
+        let now = Utc::now();
+        let mut info = timer(None, now);
+        for _ in 0..5 {
+            apply_snooze(&mut info, DEFAULT_SNOOZE_MINUTES, now).unwrap();
+        }
+        assert_eq!(snoozes_remaining(&info), None);
+        assert_eq!(info.snoozed_from, Some(now));
+    }
+
+    #[test]
+    fn enforces_count_and_total_delay() {
+        let scheduled = Utc::now();
+        let mut info = timer(Some(policy(vec![5, 15], Some(3), Some(20))), scheduled);
+
+        assert_eq!(available_snoozes(&info, scheduled), vec![5, 15]);
+        apply_snooze(&mut info, 15, scheduled).unwrap();
+        assert_eq!(snoozes_remaining(&info), Some(2));
+
+        // 15 minutes late already: only 5 more fit in the 20-minute budget.
+        let later = info.target_time;
+        assert_eq!(available_snoozes(&info, later), vec![5]);
+        assert!(apply_snooze(&mut info, 15, later).is_err());
+        apply_snooze(&mut info, 5, later).unwrap();
+        assert!(available_snoozes(&info, info.target_time).is_empty());
+
+        assert_eq!(end_snooze(&mut info), scheduled);
+        assert_eq!(snoozes_remaining(&info), Some(3));
+    }
+}
@@ -0,0 +1,75 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::TimerInfo;
+
+/// Snapshot of the scheduled timers, mirrored to `timers.json`.
+///
+/// Only the scheduler loop writes to the store; commands read from it.
+#[derive(Clone)]
+pub struct TimerStore {
+    inner: Arc<Mutex<Vec<TimerInfo>>>,
+    storage_path: Arc<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PersistedTimers {
+    timers: Vec<TimerInfo>,
+}
+
+impl TimerStore {
+    pub fn new(storage_path: PathBuf) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Vec::new())),
+            storage_path: Arc::new(storage_path),
+        }
+    }
+
+    pub fn list(&self) -> Result<Vec<TimerInfo>, String> {
+        let locked = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock timer store".to_string())?;
+        Ok(locked.clone())
+    }
+
+    /// Replace the snapshot and write it to disk.
+    pub(crate) fn save(&self, mut timers: Vec<TimerInfo>) -> Result<(), String> {
+        timers.sort_by_key(|timer| timer.target_time);
+        {
+            let mut locked = self
+                .inner
+                .lock()
+                .map_err(|_| "Failed to lock timer store".to_string())?;
+            locked.clone_from(&timers);
+        }
+
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("Failed to create timer storage directory: {err}"))?;
+        }
+
+        let data = serde_json::to_string_pretty(&PersistedTimers { timers })
+            .map_err(|err| format!("Failed to encode timer data: {err}"))?;
+        fs::write(self.storage_path.as_ref(), data)
+            .map_err(|err| format!("Failed to write timer data: {err}"))?;
+        Ok(())
+    }
+
+    pub fn load_persisted_infos(&self) -> Result<Vec<TimerInfo>, String> {
+        if !self.storage_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let raw = fs::read_to_string(self.storage_path.as_ref())
+            .map_err(|err| format!("Failed to read timer data: {err}"))?;
+        let persisted = serde_json::from_str::<PersistedTimers>(&raw)
+            .map_err(|err| format!("Failed to parse timer data: {err}"))?;
+        Ok(persisted.timers)
+    }
+}
@@ -0,0 +1,409 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use reqwest::blocking::Client;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const GITHUB_OWNER: &str = "maxacode";
+const GITHUB_REPO: &str = "LockPilot-Mac-Win";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    Main,
+    Dev,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GithubAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GithubRelease {
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub body: Option<String>,
+    pub draft: bool,
+    pub prerelease: bool,
+    pub published_at: Option<String>,
+    pub assets: Vec<GithubAsset>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseVersion {
+    pub tag: String,
+    pub name: String,
+    pub published_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub tag: String,
+    pub name: String,
+    pub notes: Option<String>,
+    pub published_at: Option<String>,
+}
+
+/// An installer downloaded to the temp directory, ready to be opened.
+#[derive(Debug)]
+pub struct DownloadedInstaller {
+    pub tag: String,
+    pub path: PathBuf,
+    /// SHA-256 the release publishes for the installer, which the download
+    /// matched. `None` when the release publishes no checksum for it.
+    pub sha256: Option<String>,
+}
+
+/// Installer file extensions (lowercase, with leading dot) a platform can install.
+pub type InstallerExtensions = &'static [&'static str];
+
+// ─── Release queries ──────────────────────────────────────────────
+
+pub fn list_release_versions(
+    extensions: InstallerExtensions,
+) -> Result<Vec<ReleaseVersion>, String> {
+    let mut releases = rollback_releases(fetch_releases()?, extensions);
+    releases.sort_by(release_version_desc);
+
+    Ok(releases
+        .into_iter()
+        .map(|release| ReleaseVersion {
+            tag: release.tag_name.clone(),
+            name: release.name.unwrap_or_else(|| release.tag_name.clone()),
+            published_at: release.published_at,
+        })
+        .collect())
+}
+
+pub fn check_channel_update(
+    current_version: &str,
+    channel: &UpdateChannel,
+    extensions: InstallerExtensions,
+) -> Result<Option<UpdateInfo>, String> {
+    let current = normalize_version(current_version)
+        .ok_or_else(|| format!("Invalid current version: {current_version}"))?;
+
+    let mut releases = releases_for_channel(fetch_releases()?, channel, extensions);
+    releases.sort_by(release_version_desc);
+
+    let update = releases.into_iter().find(|release| {
+        normalize_version(&release.tag_name)
+            .map(|version| is_channel_update(&version, &current, channel))
+            .unwrap_or(false)
+    });
+
+    Ok(update.map(|release| UpdateInfo {
+        tag: release.tag_name.clone(),
+        name: release.name.unwrap_or_else(|| release.tag_name.clone()),
+        notes: release.body,
+        published_at: release.published_at,
+    }))
+}
+
+pub fn download_channel_installer(
+    channel: &UpdateChannel,
+    extensions: InstallerExtensions,
+) -> Result<DownloadedInstaller, String> {
+    let mut releases = releases_for_channel(fetch_releases()?, channel, extensions);
+    releases.sort_by(release_version_desc);
+    let release = releases
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No releases found for {} channel", channel_name(channel)))?;
+
+    download_release_asset(&release, extensions)
+}
+
+pub fn download_release_installer(
+    tag: &str,
+    extensions: InstallerExtensions,
+) -> Result<DownloadedInstaller, String> {
+    let releases = rollback_releases(fetch_releases()?, extensions);
+    let release = releases
+        .into_iter()
+        .find(|release| tags_match(&release.tag_name, tag))
+        .ok_or_else(|| format!("Release not found for tag: {tag}"))?;
+
+    download_release_asset(&release, extensions)
+}
+
+pub fn channel_name(channel: &UpdateChannel) -> &'static str {
+    match channel {
+        UpdateChannel::Main => "main",
+        UpdateChannel::Dev => "dev",
+    }
+}
+
+// ─── GitHub release helpers ───────────────────────────────────────
+
+fn fetch_releases() -> Result<Vec<GithubRelease>, String> {
+    let client = Client::builder()
+        .user_agent("LockPilot-Updater")
+        .build()
+        .map_err(|err| format!("Failed to build HTTP client: {err}"))?;
+
+    let url = format!(
+        "https://api.github.com/repos/{}/{}/releases?per_page=100",
+        GITHUB_OWNER, GITHUB_REPO
+    );
+
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|err| format!("Failed to fetch GitHub releases: {err}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "GitHub releases API returned status {}",
+            response.status()
+        ));
+    }
+
+    response
+        .json::<Vec<GithubRelease>>()
+        .map_err(|err| format!("Failed to parse GitHub releases: {err}"))
+}
+
+fn normalize_version(version: &str) -> Option<Version> {
+    Version::parse(version.trim().trim_start_matches('v')).ok()
+}
+
+fn is_channel_update(version: &Version, current: &Version, channel: &UpdateChannel) -> bool {
+    if version > current {
+        return true;
+    }
+
+    // Strict semver says 0.4.2-dev.19 < 0.4.2.
+    // However, if the user switches from main (0.4.2) to dev (0.4.2-dev.19),
+    // we want to consider the latest prerelease of the same base version as an update.
+    matches!(channel, UpdateChannel::Dev)
+        && version.major == current.major
+        && version.minor == current.minor
+        && version.patch == current.patch
+        && current.pre.is_empty()
+        && !version.pre.is_empty()
+}
+
+fn release_version_desc(a: &GithubRelease, b: &GithubRelease) -> std::cmp::Ordering {
+    let av = normalize_version(&a.tag_name);
+    let bv = normalize_version(&b.tag_name);
+    bv.cmp(&av)
+}
+
+fn rollback_releases(
+    releases: Vec<GithubRelease>,
+    extensions: InstallerExtensions,
+) -> Vec<GithubRelease> {
+    releases
+        .into_iter()
+        .filter(|release| !release.draft)
+        .filter(|release| normalize_version(&release.tag_name).is_some())
+        .filter(|release| pick_installer_asset(&release.assets, extensions).is_some())
+        .collect()
+}
+
+fn releases_for_channel(
+    releases: Vec<GithubRelease>,
+    channel: &UpdateChannel,
+    extensions: InstallerExtensions,
+) -> Vec<GithubRelease> {
+    let base = rollback_releases(releases, extensions).into_iter();
+
+    match channel {
+        UpdateChannel::Main => base.filter(|release| !release.prerelease).collect(),
+        UpdateChannel::Dev => base.filter(|release| release.prerelease).collect(),
+    }
+}
+
+fn tags_match(a: &str, b: &str) -> bool {
+    a.trim() == b.trim() || a.trim_start_matches('v') == b.trim_start_matches('v')
+}
+
+/// Pick the best installer asset for this architecture from a release.
+fn pick_installer_asset(
+    assets: &[GithubAsset],
+    extensions: InstallerExtensions,
+) -> Option<GithubAsset> {
+    let arch = std::env::consts::ARCH;
+
+    let installer_assets: Vec<GithubAsset> = assets
+        .iter()
+        .filter(|asset| {
+            let lower = asset.name.to_lowercase();
+            extensions
+                .iter()
+                .any(|extension| lower.ends_with(extension))
+        })
+        .cloned()
+        .collect();
+
+    // Try to match architecture
+    let arch_match = match arch {
+        "x86_64" => installer_assets
+            .iter()
+            .find(|asset| {
+                let lower = asset.name.to_lowercase();
+                lower.contains("x86_64") || lower.contains("x64") || lower.contains("amd64")
+            })
+            .cloned(),
+        "aarch64" => installer_assets
+            .iter()
+            .find(|asset| {
+                let lower = asset.name.to_lowercase();
+                lower.contains("aarch64") || lower.contains("arm64")
+            })
+            .cloned(),
+        _ => None,
+    };
+
+    arch_match.or_else(|| installer_assets.into_iter().next())
+}
+
+fn download_release_asset(
+    release: &GithubRelease,
+    extensions: InstallerExtensions,
+) -> Result<DownloadedInstaller, String> {
+    let asset = pick_installer_asset(&release.assets, extensions)
+        .ok_or_else(|| format!("No installer asset found for release {}", release.tag_name))?;
+
+    let bytes = fetch_asset(&asset.browser_download_url)?;
+    let sha256 = match published_checksum(release, &asset.name)? {
+        Some(expected) => {
+            if sha256_hex(&bytes) != expected {
+                return Err(format!(
+                    "Downloaded {} does not match its published SHA-256",
+                    asset.name
+                ));
+            }
+            Some(expected)
+        }
+        None => None,
+    };
+
+    let path = write_to_temp(&bytes, &release.tag_name, &asset.name)?;
+    Ok(DownloadedInstaller {
+        tag: release.tag_name.clone(),
+        path,
+        sha256,
+    })
+}
+
+/// The SHA-256 a release publishes for `asset_name`, from the
+/// `<asset>.sha256` file uploaded beside it, if there is one.
+fn published_checksum(release: &GithubRelease, asset_name: &str) -> Result<Option<String>, String> {
+    let checksum_name = format!("{asset_name}.sha256");
+    let Some(checksum) = release
+        .assets
+        .iter()
+        .find(|asset| asset.name == checksum_name)
+    else {
+        return Ok(None);
+    };
+
+    let body = fetch_asset(&checksum.browser_download_url)?;
+    parse_checksum(&String::from_utf8_lossy(&body))
+        .map(Some)
+        .ok_or_else(|| format!("{checksum_name} does not hold a SHA-256 checksum"))
+}
+
+/// Read the hash from `sha256sum` output: 64 hex digits, then the file name.
+fn parse_checksum(text: &str) -> Option<String> {
+    let hash = text.split_whitespace().next()?;
+    (hash.len() == 64 && hash.bytes().all(|byte| byte.is_ascii_hexdigit()))
+        .then(|| hash.to_lowercase())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// SHA-256 of the file at `path`, as lowercase hex.
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let bytes =
+        fs::read(path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    Ok(sha256_hex(&bytes))
+}
+
+fn fetch_asset(url: &str) -> Result<Vec<u8>, String> {
+    let client = Client::builder()
+        .user_agent("LockPilot-Updater")
+        .build()
+        .map_err(|err| format!("Failed to build HTTP client: {err}"))?;
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|err| format!("Failed to download release asset: {err}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Release asset download failed with status {}",
+            response.status()
+        ));
+    }
+
+    let bytes = response
+        .bytes()
+        .map_err(|err| format!("Failed to read release asset body: {err}"))?;
+    Ok(bytes.to_vec())
+}
+
+/// Write a downloaded asset to a temp file, preserving the file extension.
+fn write_to_temp(bytes: &[u8], tag: &str, asset_name: &str) -> Result<PathBuf, String> {
+    let extension = Path::new(asset_name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("bin");
+
+    let safe_tag = tag.replace('/', "-");
+    let path = std::env::temp_dir().join(format!("LockPilot-{safe_tag}.{extension}"));
+    fs::write(&path, bytes).map_err(|err| format!("Failed to write installer: {err}"))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(name: &str) -> GithubAsset {
+        GithubAsset {
+            name: name.to_string(),
+            browser_download_url: format!("https://example.com/{name}"),
+        }
+    }
+
+    #[test]
+    fn picks_linux_assets_by_install_format() {
+        let assets = vec![
+            asset("LockPilot_1.0.3_aarch64.dmg"),
+            asset("LockPilot_1.0.3_x64-setup.exe"),
+            asset("LockPilot_1.0.3_amd64.AppImage"),
+            asset("LockPilot_1.0.3_amd64.deb"),
+        ];
+
+        let appimage = pick_installer_asset(&assets, &[".appimage"]).unwrap();
+        assert_eq!(appimage.name, "LockPilot_1.0.3_amd64.AppImage");
+        let deb = pick_installer_asset(&assets, &[".deb"]).unwrap();
+        assert_eq!(deb.name, "LockPilot_1.0.3_amd64.deb");
+        assert!(pick_installer_asset(&assets[..2], &[".deb"]).is_none());
+    }
+
+    #[test]
+    fn reads_sha256sum_output() {
+        let hash = "9F86D081884C7D659A2FEAA0C55AD015A3BF4F1B2B0B822CD15D6C15B0F00A08";
+        assert_eq!(
+            parse_checksum(&format!("{hash}  LockPilot_1.0.3_amd64.AppImage\n")),
+            Some(hash.to_lowercase())
+        );
+        assert_eq!(sha256_hex(b"test"), hash.to_lowercase());
+        assert_eq!(parse_checksum("not-a-hash  LockPilot.AppImage"), None);
+        assert_eq!(parse_checksum(""), None);
+    }
+}
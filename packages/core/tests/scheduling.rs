@@ -0,0 +1,941 @@
+//! End-to-end runs of the scheduler loop against a recording backend, so
+//! no test ever locks or shuts down the machine.
+
+use std::{
+    path::PathBuf,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{Duration as ChronoDuration, SecondsFormat, Utc};
+use lockpilot_core::{
+    backend::RecordedCall,
+    conditions::SystemState,
+    model::{
+        ActionOutcomePayload, ActionStep, CloseAppsSpec, CommandSpec, CreateTimerRequest,
+        HistoryEvent, HistoryFilter, IdleTrigger, PowerSource, PreActionDecision,
+        PreActionWarningPayload, RecurrenceConfig, RecurrencePreset, ResolvePreActionRequest,
+        SnoozePolicy, TimerAction, TimerConditions, TimerStatus, UnmetPolicy,
+    },
+    HistoryStore, IdleSource, LockStore, PreActionPrompter, RecordingBackend, Scheduler,
+    StaticProbe, SystemProbe, TimerStore,
+};
+
+#[derive(Default)]
+struct RecordingPrompter {
+    opened: Mutex<Vec<PreActionWarningPayload>>,
+    finished: Mutex<Vec<ActionOutcomePayload>>,
+}
+
+impl PreActionPrompter for RecordingPrompter {
+    fn open(&self, payload: &PreActionWarningPayload) -> Result<(), String> {
+        self.opened.lock().unwrap().push(payload.clone());
+        Ok(())
+    }
+
+    fn close(&self, _timer_id: &str) {}
+
+    fn action_finished(&self, payload: &ActionOutcomePayload) {
+        self.finished.lock().unwrap().push(payload.clone());
+    }
+}
+
+struct Harness {
+    scheduler: Scheduler,
+    backend: Arc<RecordingBackend>,
+    probe: Arc<StaticProbe>,
+    prompter: Arc<RecordingPrompter>,
+    dir: PathBuf,
+}
+
+impl Harness {
+    fn new() -> Self {
+        Self::at(std::env::temp_dir().join(format!("lockpilot-e2e-{}", uuid::Uuid::new_v4())))
+    }
+
+    /// A scheduler over the data in `dir`, as after an app restart.
+    fn at(dir: PathBuf) -> Self {
+        let backend = Arc::new(RecordingBackend::new());
+        let probe = Arc::new(StaticProbe::default());
+        let prompter = Arc::new(RecordingPrompter::default());
+        let scheduler = Scheduler::start(
+            TimerStore::new(dir.join("timers.json")),
+            HistoryStore::new(dir.join("history.jsonl")),
+            LockStore::new(dir.join("locks.json")).unwrap(),
+            backend.clone(),
+            probe.clone(),
+            prompter.clone(),
+        );
+        Self {
+            scheduler,
+            backend,
+            probe,
+            prompter,
+            dir,
+        }
+    }
+
+    fn last_prompt(&self) -> PreActionWarningPayload {
+        self.prompter
+            .opened
+            .lock()
+            .unwrap()
+            .last()
+            .cloned()
+            .expect("no prompt opened")
+    }
+
+    fn resolve(&self, decision: PreActionDecision, pin: Option<&str>) -> Result<bool, String> {
+        self.scheduler.resolve_pre_action(ResolvePreActionRequest {
+            prompt_id: self.last_prompt().prompt_id,
+            decision,
+            snooze_minutes: None,
+            pin: pin.map(str::to_string),
+        })
+    }
+
+    fn history_events(&self) -> Vec<HistoryEvent> {
+        self.scheduler
+            .list_history(&HistoryFilter::default())
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.event)
+            .collect()
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn request(
+    action: TimerAction,
+    in_millis: i64,
+    pre_warning_seconds: Vec<u32>,
+) -> CreateTimerRequest {
+    CreateTimerRequest {
+        action,
+        target_time: (Utc::now() + ChronoDuration::milliseconds(in_millis))
+            .to_rfc3339_opts(SecondsFormat::Millis, true),
+        recurrence: None,
+        pre_warning_seconds: Some(pre_warning_seconds),
+        pre_warning_minutes: None,
+        snooze: None,
+        lock_pin: None,
+        message: None,
+        command: None,
+        open_target: None,
+        close_apps: None,
+        steps: None,
+        conditions: None,
+        idle_trigger: None,
+    }
+}
+
+fn wait_for(what: &str, done: impl Fn() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !done() {
+        assert!(Instant::now() < deadline, "timed out waiting for {what}");
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn one_shot_timer_runs_its_action_and_is_logged() {
+    let harness = Harness::new();
+    let mut popup = request(TimerAction::Popup, 300, Vec::new());
+    popup.message = Some("Stretch".to_string());
+    let info = harness.scheduler.create_timer(popup).unwrap();
+
+    wait_for("the popup", || !harness.backend.calls().is_empty());
+    assert_eq!(
+        harness.backend.calls(),
+        vec![RecordedCall::Popup("Stretch".to_string())]
+    );
+
+    wait_for("the outcome", || {
+        !harness.prompter.finished.lock().unwrap().is_empty()
+    });
+    assert_eq!(
+        harness.prompter.finished.lock().unwrap()[0].timer_id,
+        info.id
+    );
+    assert_eq!(harness.history_events(), vec![HistoryEvent::Executed]);
+
+    // The one-shot stays, done, with the outcome of its run.
+    let done = harness.scheduler.list_timers().unwrap().remove(0);
+    assert_eq!(done.status, TimerStatus::Done);
+    let outcome = done.last_outcome.expect("no outcome stored");
+    assert!(outcome.success);
+    assert_eq!(outcome.attempts[0].method, "recording:Popup(\"Stretch\")");
+}
+
+#[test]
+fn failed_actions_keep_their_error_in_history() {
+    let harness = Harness::new();
+    harness.backend.set_failure(Some("permission denied"));
+    harness
+        .scheduler
+        .create_timer(request(TimerAction::Shutdown, 200, Vec::new()))
+        .unwrap();
+
+    wait_for("the outcome", || {
+        !harness.prompter.finished.lock().unwrap().is_empty()
+    });
+    let entries = harness
+        .scheduler
+        .list_history(&HistoryFilter::default())
+        .unwrap();
+    assert_eq!(
+        entries[0].error.as_deref(),
+        Some("recording:Shutdown: permission denied")
+    );
+}
+
+#[test]
+fn logout_goes_through_the_prompt_and_is_logged() {
+    let harness = Harness::new();
+    harness
+        .scheduler
+        .create_timer(request(TimerAction::Logout, 30_500, vec![30]))
+        .unwrap();
+    wait_for("the prompt", || {
+        !harness.prompter.opened.lock().unwrap().is_empty()
+    });
+    assert_eq!(harness.last_prompt().action, TimerAction::Logout);
+    assert!(harness.resolve(PreActionDecision::RunNow, None).unwrap());
+
+    wait_for("the outcome", || {
+        !harness.prompter.finished.lock().unwrap().is_empty()
+    });
+    assert_eq!(harness.backend.calls(), vec![RecordedCall::Logout]);
+    let entries = harness
+        .scheduler
+        .list_history(&HistoryFilter::default())
+        .unwrap();
+    assert_eq!(entries[0].action, TimerAction::Logout);
+    assert_eq!(entries[0].event, HistoryEvent::Executed);
+}
+
+#[test]
+fn run_command_timers_are_validated_and_run_through_the_backend() {
+    let harness = Harness::new();
+    assert!(harness
+        .scheduler
+        .create_timer(request(TimerAction::RunCommand, 200, Vec::new()))
+        .is_err());
+
+    let mut sync = request(TimerAction::RunCommand, 200, Vec::new());
+    sync.command = Some(CommandSpec {
+        program: " git ".to_string(),
+        args: vec!["pull".to_string()],
+        env: Default::default(),
+        working_dir: None,
+        timeout_seconds: Some(30),
+        shell: false,
+    });
+    let info = harness.scheduler.create_timer(sync).unwrap();
+    let command = info.command.expect("command kept on the timer");
+    assert_eq!(command.program, "git");
+
+    wait_for("the command", || !harness.backend.calls().is_empty());
+    assert_eq!(
+        harness.backend.calls(),
+        vec![RecordedCall::RunCommand(command)]
+    );
+}
+
+#[test]
+fn close_apps_prompt_lists_the_apps_it_will_close() {
+    let harness = Harness::new();
+    let mut end_of_day = request(TimerAction::CloseApps, 30_500, vec![30]);
+    end_of_day.close_apps = Some(CloseAppsSpec {
+        apps: vec!["Slack".to_string(), " steam ".to_string()],
+        grace_seconds: None,
+    });
+    harness.scheduler.create_timer(end_of_day).unwrap();
+
+    wait_for("the prompt", || {
+        !harness.prompter.opened.lock().unwrap().is_empty()
+    });
+    assert_eq!(harness.last_prompt().apps, vec!["Slack", "steam"]);
+    assert!(harness.resolve(PreActionDecision::RunNow, None).unwrap());
+    wait_for("the apps to close", || !harness.backend.calls().is_empty());
+    assert_eq!(
+        harness.backend.calls(),
+        vec![RecordedCall::CloseApps(vec![
+            "Slack".to_string(),
+            "steam".to_string()
+        ])]
+    );
+}
+
+fn sequence_step(action: TimerAction, delay_seconds: u32) -> ActionStep {
+    ActionStep {
+        action,
+        delay_seconds,
+        continue_on_error: false,
+        message: None,
+        command: None,
+        open_target: None,
+        close_apps: None,
+    }
+}
+
+fn waiting_at_step(scheduler: &Scheduler, step: usize) -> bool {
+    scheduler
+        .list_timers()
+        .unwrap()
+        .first()
+        .and_then(|timer| timer.sequence_run.as_ref())
+        .is_some_and(|run| run.next_step == step)
+}
+
+#[test]
+fn sequences_run_each_step_and_log_them() {
+    let harness = Harness::new();
+    let mut wind_down = request(TimerAction::Sequence, 200, Vec::new());
+    wind_down.steps = Some(vec![
+        sequence_step(TimerAction::Popup, 0),
+        sequence_step(TimerAction::Lock, 0),
+    ]);
+    let info = harness.scheduler.create_timer(wind_down).unwrap();
+    assert_eq!(info.steps.map(|steps| steps.len()), Some(2));
+
+    wait_for("the outcome", || {
+        !harness.prompter.finished.lock().unwrap().is_empty()
+    });
+    assert_eq!(
+        harness.backend.calls(),
+        vec![
+            RecordedCall::Popup("LockPilot timer reached.".to_string()),
+            RecordedCall::Lock
+        ]
+    );
+    let entries = harness
+        .scheduler
+        .list_history(&HistoryFilter::default())
+        .unwrap();
+    let outcome = entries[0].outcome.as_ref().expect("outcome logged");
+    assert!(outcome.success);
+    assert_eq!(outcome.steps.len(), 2);
+}
+
+fn busy_conditions(when_unmet: UnmetPolicy) -> Option<TimerConditions> {
+    Some(TimerConditions {
+        blocking_processes: vec!["zoom".to_string()],
+        when_unmet,
+        ..TimerConditions::default()
+    })
+}
+
+#[test]
+fn unmet_conditions_skip_defer_or_run_anyway() {
+    let harness = Harness::new();
+    harness.probe.set(SystemState {
+        running: vec!["zoom".to_string()],
+        ..SystemState::default()
+    });
+
+    let mut skipped = request(TimerAction::Shutdown, 200, Vec::new());
+    skipped.conditions = busy_conditions(UnmetPolicy::Skip);
+    harness.scheduler.create_timer(skipped).unwrap();
+    let mut deferred = request(TimerAction::Reboot, 200, Vec::new());
+    deferred.conditions = busy_conditions(UnmetPolicy::Defer);
+    let deferred = harness.scheduler.create_timer(deferred).unwrap();
+    let mut forced = request(TimerAction::Lock, 200, Vec::new());
+    forced.conditions = busy_conditions(UnmetPolicy::RunAnyway);
+    harness.scheduler.create_timer(forced).unwrap();
+
+    wait_for("the forced lock", || {
+        !harness.prompter.finished.lock().unwrap().is_empty()
+    });
+    assert_eq!(harness.backend.calls(), vec![RecordedCall::Lock]);
+
+    let details = |event| {
+        harness
+            .scheduler
+            .list_history(&HistoryFilter::default())
+            .unwrap()
+            .into_iter()
+            .filter(|entry| entry.event == event)
+            .filter_map(|entry| entry.detail)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        details(HistoryEvent::Skipped),
+        vec!["Conditions not met: zoom is running"]
+    );
+    assert_eq!(
+        details(HistoryEvent::Deferred),
+        vec!["Deferred 10m: zoom is running"]
+    );
+    assert_eq!(
+        details(HistoryEvent::Executed),
+        vec!["Ran although conditions were not met: zoom is running"]
+    );
+
+    let timers: Vec<_> = harness
+        .scheduler
+        .list_timers()
+        .unwrap()
+        .into_iter()
+        .filter(|timer| timer.status == TimerStatus::Active)
+        .collect();
+    assert_eq!(timers.len(), 1);
+    assert_eq!(timers[0].id, deferred.id);
+    assert!(timers[0].target_time >= deferred.target_time + ChronoDuration::minutes(9));
+}
+
+/// Probe whose process checks hang until the test lets them finish, like
+/// a slow `tasklist`.
+struct StalledProbe {
+    release: Mutex<mpsc::Receiver<()>>,
+}
+
+impl SystemProbe for StalledProbe {
+    fn power_source(&self) -> Result<PowerSource, String> {
+        Ok(PowerSource::Ac)
+    }
+
+    fn is_running(&self, _name: &str) -> Result<bool, String> {
+        let _ = self.release.lock().unwrap().recv();
+        Ok(false)
+    }
+
+    fn cpu_percent(&self) -> Result<f32, String> {
+        Ok(0.0)
+    }
+}
+
+impl IdleSource for StalledProbe {
+    fn idle_seconds(&self) -> Result<u64, String> {
+        Ok(0)
+    }
+}
+
+/// A scheduler over a fresh data directory that reads the machine through
+/// `probe`.
+fn start_with_probe(probe: Arc<dyn SystemProbe>) -> (Scheduler, Arc<RecordingBackend>, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("lockpilot-e2e-{}", uuid::Uuid::new_v4()));
+    let backend = Arc::new(RecordingBackend::new());
+    let scheduler = Scheduler::start(
+        TimerStore::new(dir.join("timers.json")),
+        HistoryStore::new(dir.join("history.jsonl")),
+        LockStore::new(dir.join("locks.json")).unwrap(),
+        backend.clone(),
+        probe,
+        Arc::new(RecordingPrompter::default()),
+    );
+    (scheduler, backend, dir)
+}
+
+#[test]
+fn slow_condition_checks_do_not_hold_up_other_timers() {
+    let (release, released) = mpsc::channel();
+    let (scheduler, backend, dir) = start_with_probe(Arc::new(StalledProbe {
+        release: Mutex::new(released),
+    }));
+
+    let mut checked = request(TimerAction::Sleep, 100, Vec::new());
+    checked.conditions = busy_conditions(UnmetPolicy::Skip);
+    scheduler.create_timer(checked).unwrap();
+    thread::sleep(Duration::from_millis(200));
+    scheduler
+        .create_timer(request(TimerAction::Lock, 100, Vec::new()))
+        .unwrap();
+    wait_for("the unconditioned timer", || {
+        backend.calls() == vec![RecordedCall::Lock]
+    });
+
+    release.send(()).unwrap();
+    wait_for("the checked timer", || backend.calls().len() == 2);
+    assert_eq!(backend.calls()[1], RecordedCall::Sleep);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// Probe for a desktop with no way to read idle time.
+struct NoIdleProbe;
+
+impl SystemProbe for NoIdleProbe {
+    fn power_source(&self) -> Result<PowerSource, String> {
+        Ok(PowerSource::Ac)
+    }
+
+    fn is_running(&self, _name: &str) -> Result<bool, String> {
+        Ok(false)
+    }
+
+    fn cpu_percent(&self) -> Result<f32, String> {
+        Ok(0.0)
+    }
+}
+
+impl IdleSource for NoIdleProbe {
+    fn idle_seconds(&self) -> Result<u64, String> {
+        Err("no idle monitor".to_string())
+    }
+}
+
+#[test]
+fn idle_timers_are_refused_where_idle_time_cannot_be_read() {
+    let (scheduler, _backend, dir) = start_with_probe(Arc::new(NoIdleProbe));
+    let mut away = request(TimerAction::Lock, 0, Vec::new());
+    away.idle_trigger = Some(IdleTrigger { minutes: 5 });
+    assert_eq!(
+        scheduler.create_timer(away).err(),
+        Some("Idle timers can't run here: no idle monitor".to_string())
+    );
+
+    let info = scheduler
+        .create_timer(request(TimerAction::Lock, 60_000, Vec::new()))
+        .unwrap();
+    let mut away = request(TimerAction::Lock, 0, Vec::new());
+    away.idle_trigger = Some(IdleTrigger { minutes: 5 });
+    assert!(scheduler.update_timer(&info.id, away, None).is_err());
+    assert!(scheduler.list_timers().unwrap()[0].idle_trigger.is_none());
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// Probe whose idle readings hang, after the first, until the test lets
+/// them finish, like an `xprintidle` that never returns.
+struct StalledIdleProbe {
+    reads: Mutex<u32>,
+    release: Mutex<mpsc::Receiver<()>>,
+}
+
+impl SystemProbe for StalledIdleProbe {
+    fn power_source(&self) -> Result<PowerSource, String> {
+        Ok(PowerSource::Ac)
+    }
+
+    fn is_running(&self, _name: &str) -> Result<bool, String> {
+        Ok(false)
+    }
+
+    fn cpu_percent(&self) -> Result<f32, String> {
+        Ok(0.0)
+    }
+}
+
+impl IdleSource for StalledIdleProbe {
+    fn idle_seconds(&self) -> Result<u64, String> {
+        let first = {
+            let mut reads = self.reads.lock().unwrap();
+            *reads += 1;
+            *reads == 1
+        };
+        if !first {
+            let _ = self.release.lock().unwrap().recv();
+        }
+        Ok(0)
+    }
+}
+
+#[test]
+fn slow_idle_readings_do_not_hold_up_other_timers() {
+    let (release, released) = mpsc::channel();
+    let probe = Arc::new(StalledIdleProbe {
+        reads: Mutex::new(0),
+        release: Mutex::new(released),
+    });
+    let (scheduler, backend, dir) = start_with_probe(probe.clone());
+    let idle_lock = || {
+        let mut away = request(TimerAction::Lock, 0, Vec::new());
+        away.idle_trigger = Some(IdleTrigger { minutes: 5 });
+        away
+    };
+
+    scheduler.create_timer(idle_lock()).unwrap();
+    wait_for("the loop to read idle time", || {
+        *probe.reads.lock().unwrap() == 2
+    });
+    // Saving another idle timer reuses the fresh reading rather than
+    // waiting on the stalled one.
+    let started = Instant::now();
+    scheduler.create_timer(idle_lock()).unwrap();
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    scheduler
+        .create_timer(request(TimerAction::Sleep, 100, Vec::new()))
+        .unwrap();
+    wait_for("the timed timer", || {
+        backend.calls() == vec![RecordedCall::Sleep]
+    });
+    let info = scheduler
+        .create_timer(request(TimerAction::Lock, 60_000, Vec::new()))
+        .unwrap();
+    assert!(scheduler.cancel_timer(&info.id, None).unwrap());
+
+    drop(release);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn cancelling_a_sequence_stops_its_remaining_steps() {
+    let harness = Harness::new();
+    let mut wind_down = request(TimerAction::Sequence, 100, Vec::new());
+    wind_down.steps = Some(vec![
+        sequence_step(TimerAction::Popup, 0),
+        sequence_step(TimerAction::CloseApps, 60),
+        sequence_step(TimerAction::Lock, 0),
+    ]);
+    wind_down.steps.as_mut().unwrap()[1].close_apps = Some(CloseAppsSpec {
+        apps: vec!["Slack".to_string()],
+        grace_seconds: None,
+    });
+    let info = harness.scheduler.create_timer(wind_down).unwrap();
+
+    wait_for("the first step", || waiting_at_step(&harness.scheduler, 1));
+    assert!(harness.scheduler.cancel_timer(&info.id, None).unwrap());
+    let entries = harness
+        .scheduler
+        .list_history(&HistoryFilter::default())
+        .unwrap();
+    assert_eq!(entries[0].event, HistoryEvent::Executed);
+    assert_eq!(
+        entries[0].detail.as_deref(),
+        Some("Stopped after 1 of 3 steps: cancelled")
+    );
+    assert_eq!(entries[0].error.as_deref(), Some("stopped before step 2"));
+
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(
+        harness.backend.calls(),
+        vec![RecordedCall::Popup("LockPilot timer reached.".to_string())]
+    );
+}
+
+#[test]
+fn sequences_pick_up_where_they_were_after_a_restart() {
+    let harness = Harness::new();
+    let mut wind_down = request(TimerAction::Sequence, 100, Vec::new());
+    wind_down.steps = Some(vec![
+        sequence_step(TimerAction::Popup, 0),
+        sequence_step(TimerAction::Lock, 1),
+    ]);
+    harness.scheduler.create_timer(wind_down).unwrap();
+
+    // Restart from a copy of the saved timers, taken once the first step is
+    // on disk, so only the new scheduler runs the rest.
+    let saved = Mutex::new(String::new());
+    wait_for("the first step to be saved", || {
+        let raw = std::fs::read_to_string(harness.dir.join("timers.json")).unwrap_or_default();
+        let done = serde_json::from_str::<serde_json::Value>(&raw)
+            .is_ok_and(|data| data["timers"][0]["sequenceRun"]["nextStep"] == 1);
+        *saved.lock().unwrap() = raw;
+        done
+    });
+    let dir = std::env::temp_dir().join(format!("lockpilot-e2e-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("timers.json"), saved.into_inner().unwrap()).unwrap();
+    let restarted = Harness::at(dir);
+    restarted.scheduler.restore_timers().unwrap();
+
+    wait_for("the last step", || {
+        !restarted.prompter.finished.lock().unwrap().is_empty()
+    });
+    assert_eq!(restarted.backend.calls(), vec![RecordedCall::Lock]);
+    let outcome = &restarted.prompter.finished.lock().unwrap()[0].outcome;
+    assert!(outcome.success);
+    assert_eq!(outcome.steps.len(), 2);
+    assert_eq!(
+        restarted.scheduler.list_timers().unwrap()[0].status,
+        TimerStatus::Done
+    );
+}
+
+#[test]
+fn steps_cut_off_by_a_restart_are_not_run_again() {
+    let harness = Harness::new();
+    let mut shut_down = request(TimerAction::Sequence, 100, Vec::new());
+    shut_down.steps = Some(vec![
+        sequence_step(TimerAction::Popup, 0),
+        sequence_step(TimerAction::Shutdown, 100),
+        sequence_step(TimerAction::Lock, 0),
+    ]);
+    harness.scheduler.create_timer(shut_down).unwrap();
+    let saved = Mutex::new(serde_json::Value::Null);
+    wait_for("the shutdown step to be queued", || {
+        let raw = std::fs::read_to_string(harness.dir.join("timers.json")).unwrap_or_default();
+        let data = serde_json::from_str::<serde_json::Value>(&raw).unwrap_or_default();
+        let done = data["timers"][0]["sequenceRun"]["nextStep"] == 1;
+        *saved.lock().unwrap() = data;
+        done
+    });
+
+    // What is on disk once the shutdown step has started and taken the
+    // machine down with it.
+    let mut saved = saved.into_inner().unwrap();
+    let started =
+        (Utc::now() - ChronoDuration::minutes(5)).to_rfc3339_opts(SecondsFormat::Secs, true);
+    saved["timers"][0]["sequenceRun"]["nextAt"] = started.clone().into();
+    saved["timers"][0]["sequenceRun"]["stepStartedAt"] = started.into();
+    let dir = std::env::temp_dir().join(format!("lockpilot-e2e-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("timers.json"), saved.to_string()).unwrap();
+    let restarted = Harness::at(dir);
+    restarted.scheduler.restore_timers().unwrap();
+
+    wait_for("the last step", || {
+        !restarted.prompter.finished.lock().unwrap().is_empty()
+    });
+    assert_eq!(restarted.backend.calls(), vec![RecordedCall::Lock]);
+    let outcome = &restarted.prompter.finished.lock().unwrap()[0].outcome;
+    assert_eq!(outcome.steps.len(), 3);
+    assert_eq!(
+        outcome.steps[1].attempts[0].method,
+        "interrupted by a restart"
+    );
+    assert_eq!(
+        restarted.scheduler.list_timers().unwrap()[0].status,
+        TimerStatus::Done
+    );
+}
+
+#[test]
+fn idle_timers_run_once_the_user_is_away() {
+    let harness = Harness::new();
+    let idle_lock = || {
+        let mut lock = request(TimerAction::Lock, 0, Vec::new());
+        lock.idle_trigger = Some(IdleTrigger { minutes: 5 });
+        lock
+    };
+
+    let mut warned = idle_lock();
+    warned.pre_warning_seconds = Some(vec![60]);
+    assert!(harness.scheduler.create_timer(warned).is_err());
+
+    // Two minutes away is not enough yet.
+    harness.probe.set(SystemState {
+        idle_seconds: 120,
+        ..SystemState::default()
+    });
+    let waiting = harness.scheduler.create_timer(idle_lock()).unwrap();
+    thread::sleep(Duration::from_millis(300));
+    assert!(harness.backend.calls().is_empty());
+
+    harness.probe.set(SystemState {
+        idle_seconds: 6 * 60,
+        ..SystemState::default()
+    });
+    let away = harness.scheduler.create_timer(idle_lock()).unwrap();
+    wait_for("the lock", || {
+        !harness.prompter.finished.lock().unwrap().is_empty()
+    });
+    assert_eq!(harness.backend.calls(), vec![RecordedCall::Lock]);
+
+    // Both stay armed for the next time the user is away.
+    let timers = harness.scheduler.list_timers().unwrap();
+    assert_eq!(timers.len(), 2);
+    let ran = timers.iter().find(|timer| timer.id == away.id).unwrap();
+    assert_eq!(ran.occurrence_count, 1);
+    assert!(ran.target_time > away.target_time);
+    assert!(timers
+        .iter()
+        .any(|timer| timer.id == waiting.id && timer.occurrence_count == 0));
+}
+
+#[test]
+fn prompt_decisions_run_or_skip_the_occurrence() {
+    let harness = Harness::new();
+    // The 30s warning is half a second away, so the prompt opens at once.
+    harness
+        .scheduler
+        .create_timer(request(TimerAction::Lock, 30_500, vec![30]))
+        .unwrap();
+    wait_for("the prompt", || {
+        !harness.prompter.opened.lock().unwrap().is_empty()
+    });
+    assert!(harness.resolve(PreActionDecision::RunNow, None).unwrap());
+    wait_for("the lock", || {
+        harness.backend.calls() == vec![RecordedCall::Lock]
+    });
+
+    harness
+        .scheduler
+        .create_timer(request(TimerAction::Reboot, 30_500, vec![30]))
+        .unwrap();
+    wait_for("the second prompt", || {
+        harness.prompter.opened.lock().unwrap().len() == 2
+    });
+    assert!(harness
+        .resolve(PreActionDecision::CancelAction, None)
+        .unwrap());
+    // Only the one that ran is kept.
+    let timers = harness.scheduler.list_timers().unwrap();
+    assert_eq!(timers.len(), 1);
+    assert_eq!(timers[0].action, TimerAction::Lock);
+    assert_eq!(harness.backend.calls(), vec![RecordedCall::Lock]);
+    assert!(harness.history_events().contains(&HistoryEvent::Skipped));
+}
+
+#[test]
+fn snooze_limits_and_locks_are_enforced() {
+    let harness = Harness::new();
+    let mut shutdown = request(TimerAction::Shutdown, 30_500, vec![30]);
+    shutdown.snooze = Some(SnoozePolicy {
+        durations_minutes: vec![5],
+        max_count: Some(1),
+        max_total_minutes: None,
+    });
+    shutdown.lock_pin = Some("2468".to_string());
+    let info = harness.scheduler.create_timer(shutdown).unwrap();
+
+    wait_for("the prompt", || {
+        !harness.prompter.opened.lock().unwrap().is_empty()
+    });
+    assert_eq!(harness.last_prompt().snoozes_remaining, Some(1));
+    assert!(harness.resolve(PreActionDecision::Snooze, None).is_err());
+    assert!(harness
+        .resolve(PreActionDecision::Snooze, Some("1111"))
+        .is_err());
+    assert!(harness
+        .resolve(PreActionDecision::Snooze, Some("2468"))
+        .unwrap());
+
+    let snoozed = harness.scheduler.list_timers().unwrap().remove(0);
+    assert_eq!(snoozed.snooze_count, 1);
+    assert!(snoozed.target_time > info.target_time);
+    assert!(harness.scheduler.cancel_timer(&info.id, None).is_err());
+    assert!(harness
+        .history_events()
+        .contains(&HistoryEvent::PinRejected));
+    assert!(harness
+        .scheduler
+        .cancel_timer(&info.id, Some("2468"))
+        .unwrap());
+    assert!(harness.backend.calls().is_empty());
+}
+
+#[test]
+fn locked_timers_need_their_pin_to_be_edited() {
+    let harness = Harness::new();
+    let mut lock = request(TimerAction::Lock, 60_000, Vec::new());
+    lock.lock_pin = Some("2468".to_string());
+    let info = harness.scheduler.create_timer(lock).unwrap();
+    let edit = || request(TimerAction::Reboot, 120_000, Vec::new());
+
+    assert!(harness
+        .scheduler
+        .update_timer(&info.id, edit(), None)
+        .is_err());
+    assert_eq!(
+        harness
+            .scheduler
+            .update_timer(&info.id, edit(), Some("1111"))
+            .err(),
+        Some("Incorrect PIN.".to_string())
+    );
+    assert_eq!(
+        harness.scheduler.list_timers().unwrap()[0].action,
+        TimerAction::Lock
+    );
+
+    let edited = harness
+        .scheduler
+        .update_timer(&info.id, edit(), Some("2468"))
+        .unwrap();
+    assert_eq!(edited.action, TimerAction::Reboot);
+    assert!(edited.locked);
+}
+
+#[test]
+fn locked_timers_stay_locked_without_their_pin_data() {
+    let harness = Harness::new();
+    let mut shutdown = request(TimerAction::Shutdown, 60_000, Vec::new());
+    shutdown.lock_pin = Some("2468".to_string());
+    let info = harness.scheduler.create_timer(shutdown).unwrap();
+
+    std::fs::remove_file(harness.dir.join("locks.json")).unwrap();
+    let restarted = Harness::at(harness.dir.clone());
+    restarted.scheduler.restore_timers().unwrap();
+    assert!(restarted.scheduler.cancel_timer(&info.id, None).is_err());
+    assert!(restarted
+        .scheduler
+        .pause_timer(&info.id, Some("2468"))
+        .is_err());
+    assert_eq!(
+        restarted.scheduler.cancel_timer(&info.id, Some("2468")),
+        Err("This timer's PIN data is missing, so it can't be unlocked.".to_string())
+    );
+    assert_eq!(restarted.scheduler.list_timers().unwrap().len(), 1);
+}
+
+#[test]
+fn unlocking_a_timer_in_timers_json_does_not_bypass_its_pin() {
+    let harness = Harness::new();
+    let mut shutdown = request(TimerAction::Shutdown, 60_000, Vec::new());
+    shutdown.lock_pin = Some("2468".to_string());
+    let info = harness.scheduler.create_timer(shutdown).unwrap();
+
+    let path = harness.dir.join("timers.json");
+    let mut saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    saved["timers"][0]["locked"] = serde_json::Value::Bool(false);
+    std::fs::write(&path, saved.to_string()).unwrap();
+    let restarted = Harness::at(harness.dir.clone());
+    restarted.scheduler.restore_timers().unwrap();
+
+    assert!(restarted.scheduler.list_timers().unwrap()[0].locked);
+    assert_eq!(
+        restarted.scheduler.cancel_timer(&info.id, None),
+        Err("This timer is locked. Enter its PIN.".to_string())
+    );
+    assert!(restarted
+        .scheduler
+        .update_timer(
+            &info.id,
+            request(TimerAction::Reboot, 60_000, Vec::new()),
+            None
+        )
+        .is_err());
+    assert!(restarted
+        .scheduler
+        .cancel_timer(&info.id, Some("2468"))
+        .unwrap());
+}
+
+#[test]
+fn recurring_timers_move_on_after_skip_and_pause() {
+    let harness = Harness::new();
+    let mut every_hour = request(TimerAction::Lock, 60_000, Vec::new());
+    every_hour.recurrence = Some(RecurrenceConfig {
+        preset: RecurrencePreset::EveryNMinutes,
+        interval_hours: None,
+        interval_minutes: Some(60),
+        days_of_week: None,
+        cron_expression: None,
+        rrule: None,
+        timezone: Some("UTC".to_string()),
+        local_time: None,
+        start_date: None,
+        ends_at: None,
+        max_occurrences: None,
+        skip_dates: None,
+        holiday_calendar: None,
+    });
+    let info = harness.scheduler.create_timer(every_hour).unwrap();
+
+    assert!(harness
+        .scheduler
+        .skip_next_occurrence(&info.id, None)
+        .unwrap());
+    let skipped = harness.scheduler.list_timers().unwrap().remove(0);
+    assert_eq!(
+        skipped.target_time,
+        info.target_time + ChronoDuration::hours(1)
+    );
+    assert_eq!(skipped.occurrence_count, 1);
+
+    assert!(harness.scheduler.pause_timer(&info.id, None).unwrap());
+    assert!(harness.scheduler.resume_timer(&info.id, None).unwrap());
+    assert_eq!(
+        harness.scheduler.list_timers().unwrap()[0].target_time,
+        skipped.target_time
+    );
+    assert!(harness.backend.calls().is_empty());
+}
@@ -5,5 +5,6 @@ ROOT="$(cd "$(dirname "$0")/.." && pwd)"
 
 diff -qr "$ROOT/packages/ui" "$ROOT/apps/mac/ui" >/dev/null || { echo "Mac UI is out of sync"; exit 1; }
 diff -qr "$ROOT/packages/ui" "$ROOT/apps/windows/ui" >/dev/null || { echo "Windows UI is out of sync"; exit 1; }
+diff -qr "$ROOT/packages/ui" "$ROOT/apps/linux/ui" >/dev/null || { echo "Linux UI is out of sync"; exit 1; }
 
 echo "UI sync OK"
@@ -5,6 +5,7 @@ ROOT="$(cd "$(dirname "$0")/.." && pwd)"
 SHARED="$ROOT/packages/ui"
 MAC_UI="$ROOT/apps/mac/ui"
 WIN_UI="$ROOT/apps/windows/ui"
+LINUX_UI="$ROOT/apps/linux/ui"
 
 sync_dir() {
   local src="$1"
@@ -22,5 +23,6 @@ sync_dir() {
 
 sync_dir "$SHARED" "$MAC_UI"
 sync_dir "$SHARED" "$WIN_UI"
+sync_dir "$SHARED" "$LINUX_UI"
 
-echo "Shared UI synced to mac + windows apps."
+echo "Shared UI synced to mac + windows + linux apps."